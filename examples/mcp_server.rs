@@ -0,0 +1,43 @@
+//! Serve `#[tool]` functions to MCP clients over stdio.
+//!
+//! Register it with an MCP client, e.g. `{"command": "target/debug/examples/mcp_server"}`.
+
+use responses::error::Error;
+use responses::mcp::McpServer;
+use responses::{tool, Result, Toolbox};
+
+#[tool]
+/// Get weather information for a city
+async fn get_weather(city: String, country: Option<String>) -> Result<String> {
+    let location = match country {
+        Some(c) => format!("{}, {}", city, c),
+        None => city,
+    };
+
+    Ok(format!("Weather in {}: Sunny, 22°C", location))
+}
+
+#[tool]
+/// Divide two numbers
+fn divide(a: f64, b: f64) -> Result<f64> {
+    if b == 0.0 {
+        return Err(Error::FunctionValidation { reason: "cannot divide by zero".to_string() });
+    }
+    Ok(a / b)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let toolbox = Toolbox::new()
+        .register(get_weather_handler())
+        .register(divide_handler());
+
+    // Logs go to stderr, since stdout carries the protocol
+    eprintln!("Serving {} tools over stdio", toolbox.len());
+
+    McpServer::new(toolbox)
+        .name("weather")
+        .instructions("Use get_weather for current conditions.")
+        .serve_stdio()
+        .await
+}
@@ -0,0 +1,111 @@
+//! A minimal MCP server speaking newline-delimited JSON-RPC over stdio.
+//!
+//! Connect to it with `McpClient::stdio(Command::new("target/debug/examples/mcp_stdio_server"))`.
+//! It offers `echo`, `add` and `divide` tools, listed over two pages.
+
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+fn main() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let message: Value = match serde_json::from_str(&line?) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+
+        // Notifications need no response
+        let Some(id) = message.get("id").cloned() else {
+            continue;
+        };
+
+        let params = &message["params"];
+        let result = match message["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "calculator", "version": "1.0.0"}
+            }),
+            "tools/list" => list_tools(params["cursor"].as_str()),
+            "tools/call" => {
+                // Servers may send notifications before the response
+                writeln!(stdout, "{}", json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": {"level": "info", "data": "calling a tool"}
+                }))?;
+                call_tool(params["name"].as_str().unwrap_or_default(), &params["arguments"])
+            }
+            method => {
+                writeln!(stdout, "{}", json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32601, "message": format!("Method not found: {}", method)}
+                }))?;
+                stdout.flush()?;
+                continue;
+            }
+        };
+
+        writeln!(stdout, "{}", json!({"jsonrpc": "2.0", "id": id, "result": result}))?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+fn list_tools(cursor: Option<&str>) -> Value {
+    let number = json!({"type": "number"});
+
+    match cursor {
+        None => json!({
+            "tools": [{
+                "name": "echo",
+                "description": "Echo the given text",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"text": {"type": "string"}},
+                    "required": ["text"]
+                }
+            }],
+            "nextCursor": "page-2"
+        }),
+        Some(_) => json!({
+            "tools": [
+                {
+                    "name": "add",
+                    "description": "Add two numbers",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {"a": number, "b": number},
+                        "required": ["a", "b"]
+                    }
+                },
+                {
+                    "name": "divide",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {"a": number, "b": number},
+                        "required": ["a", "b"]
+                    }
+                }
+            ]
+        }),
+    }
+}
+
+fn call_tool(name: &str, arguments: &Value) -> Value {
+    let text = |text: String| json!({"content": [{"type": "text", "text": text}]});
+
+    match name {
+        "echo" => text(arguments["text"].as_str().unwrap_or_default().to_string()),
+        "add" => text((arguments["a"].as_f64().unwrap_or(0.0) + arguments["b"].as_f64().unwrap_or(0.0)).to_string()),
+        "divide" => match arguments["b"].as_f64() {
+            Some(b) if b != 0.0 => text((arguments["a"].as_f64().unwrap_or(0.0) / b).to_string()),
+            _ => json!({"content": [{"type": "text", "text": "division by zero"}], "isError": true}),
+        },
+        _ => json!({"content": [{"type": "text", "text": format!("unknown tool: {}", name)}], "isError": true}),
+    }
+}
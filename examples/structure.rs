@@ -6,12 +6,14 @@ use responses::schemars::JsonSchema;
 use serde::Deserialize;
 
 #[derive(Clone, Debug, JsonSchema, Deserialize)]
+#[allow(dead_code)]
 struct Step {
     explanation: String,
     output: String,
 }
 
 #[derive(Clone, Debug, JsonSchema, Deserialize)]
+#[allow(dead_code)]
 struct MathResponse {
     steps: Vec<Step>,
     final_answer: String,
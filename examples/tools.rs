@@ -1,5 +1,5 @@
 use dotenv::dotenv;
-use responses::{azure, Client, tool, types::ToolChoice, functions::FunctionHandler};
+use responses::{azure, Client, tool, types::ToolChoice};
 use responses::provider::ProviderBuilder;
 
 #[tool]
@@ -1,19 +1,25 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
+mod methods;
+
 use syn::{
-    parse_macro_input, Attribute, FnArg, ItemFn, Lit, LitStr, Meta, 
-    Pat, PatType, Type
+    parse::{Parse, ParseStream},
+    parse_macro_input, Attribute, Expr, ExprArray, FnArg, Generics, ItemFn, Lit, LitBool, LitStr, Meta, 
+    Pat, PatType, Signature, Token, Type, UnOp
 };
 
 /// Main procedural macro that transforms functions into tools
+///
+/// Accepts an optional tool name and the flags `strict` and `requires_approval` (each also as
+/// `flag = bool`), e.g. `#[tool("refund", strict, requires_approval)]`.
+/// Strict mode is the default with the `strict-tools` feature.
+///
+/// `validate` checks the parsed arguments with the `Validate` impl of the parameter struct,
+/// and `validate = path` with a function taking the parameter struct by reference.
 #[proc_macro_attribute]
 pub fn tool(args: TokenStream, input: TokenStream) -> TokenStream {
-    let args = if args.is_empty() {
-        None
-    } else {
-        Some(parse_macro_input!(args as LitStr))
-    };
+    let args = parse_macro_input!(args as ToolArgs);
     let input_fn = parse_macro_input!(input as ItemFn);
     
     match generate_tool_function(args, input_fn) {
@@ -22,18 +28,135 @@ pub fn tool(args: TokenStream, input: TokenStream) -> TokenStream {
     }
 }
 
-fn generate_tool_function(args: Option<LitStr>, input_fn: ItemFn) -> syn::Result<TokenStream2> {
-    let fn_name = &input_fn.sig.ident;
+/// Turns the `#[tool]` methods of an impl block into tools sharing the service's state.
+///
+/// For `impl MyService`, each `#[tool] async fn search(&self, ...)` gets a
+/// `MyServiceSearchHandler` holding an `Arc<MyService>`, created with `service.search_handler()`.
+/// `service.toolbox()` registers all of them.
+#[proc_macro_attribute]
+pub fn tools(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "`#[tools]` takes no arguments")
+            .to_compile_error()
+            .into();
+    }
     
-    // Extract tool name from arguments or use function name
-    let tool_name = args.map(|lit| lit.value()).unwrap_or_else(|| fn_name.to_string());
+    let item = parse_macro_input!(input as syn::ItemImpl);
     
-    // Extract function documentation
-    let fn_doc = extract_doc_comment(&input_fn.attrs);
+    match methods::generate_tools_impl(item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct ToolArgs {
+    name: Option<LitStr>,
+    strict: bool,
+    requires_approval: bool,
+    validate: Option<Validator>,
+}
+
+/// How the parsed arguments are checked before the function runs.
+enum Validator {
+    /// `#[tool(validate)]`: the `Validate` impl of the parameter struct
+    Trait,
+    /// `#[tool(validate = path)]`: a function taking the parameter struct by reference
+    Function(syn::Path),
+}
+
+impl Parse for ToolArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ToolArgs {
+            name: None,
+            strict: cfg!(feature = "strict-tools"),
+            requires_approval: false,
+            validate: None,
+        };
+        
+        if input.peek(LitStr) {
+            args.name = Some(input.parse()?);
+            
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            let has_value = input.parse::<Option<Token![=]>>()?.is_some();
+            
+            if key == "validate" {
+                args.validate = Some(if has_value {
+                    Validator::Function(input.parse()?)
+                } else {
+                    Validator::Trait
+                });
+            } else {
+                // Flags are `key` or `key = bool`
+                let value = if has_value {
+                    input.parse::<LitBool>()?.value
+                } else {
+                    true
+                };
+                
+                if key == "strict" {
+                    args.strict = value;
+                } else if key == "requires_approval" {
+                    args.requires_approval = value;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "expected a tool name, `strict`, `requires_approval` or `validate`",
+                    ));
+                }
+            }
+            
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        
+        Ok(args)
+    }
+}
+
+/// Parse `call.arguments` into `params`, then run the validator, if any.
+fn generate_parse_params(params_name: &Ident, args: &ToolArgs) -> TokenStream2 {
+    let validate = args.validate.as_ref().map(|validator| {
+        let check = match validator {
+            Validator::Trait => quote! { responses::functions::Validate::validate(&params) },
+            Validator::Function(path) => quote! { #path(&params) },
+        };
+        
+        quote! {
+            #check.map_err(|e| responses::error::Error::FunctionValidation {
+                reason: e.to_string()
+            })?;
+        }
+    });
     
+    quote! {
+        let params: #params_name = serde_json::from_str(&call.arguments)
+            .map_err(|e| responses::error::Error::FunctionParameterParsing { 
+                function_name: call.name.clone(), 
+                source: e 
+            })?;
+        #validate
+    }
+}
+
+fn generate_tool_function(args: ToolArgs, mut input_fn: ItemFn) -> syn::Result<TokenStream2> {
     // Parse function parameters (excluding context parameters) 
     let (params, context_param) = parse_function_parameters(&input_fn.sig.inputs)?;
     
+    // Attributes forwarded to the parameter struct are not valid on the function itself
+    strip_parameter_attributes(&mut input_fn.sig);
+    
+    let fn_name = &input_fn.sig.ident;
+    
+    // Extract function documentation
+    let fn_doc = extract_doc_comment(&input_fn.attrs);
+    
     // Generate parameter struct name
     let params_struct_name = format_ident!("{}Params", capitalize_first_letter(&fn_name.to_string()));
     
@@ -42,7 +165,7 @@ fn generate_tool_function(args: Option<LitStr>, input_fn: ItemFn) -> syn::Result
     
     // Generate the function handler
     let handler_struct_name = format_ident!("{}Handler", capitalize_first_letter(&fn_name.to_string()));
-    let handler_impl = generate_handler_impl(&handler_struct_name, &params_struct_name, &tool_name, &fn_doc, &input_fn, &params, &context_param)?;
+    let handler_impl = generate_handler_impl(&handler_struct_name, &params_struct_name, &args, &fn_doc, &input_fn, &params, &context_param)?;
     
     // Generate the wrapper function that calls the original
     let wrapper_fn = generate_wrapper_function(&input_fn, &params, &context_param)?;
@@ -81,34 +204,56 @@ struct Parameter {
     name: Ident,
     ty: Type,
     doc: String,
+    attrs: Vec<Attribute>,
+    constraints: Constraints,
 }
 
-fn parse_function_parameters(inputs: &syn::punctuated::Punctuated<FnArg, syn::Token![,]>) -> syn::Result<(Vec<Parameter>, Option<Ident>)> {
+fn parse_function_parameters<'a>(inputs: impl IntoIterator<Item = &'a FnArg>) -> syn::Result<(Vec<Parameter>, Option<Ident>)> {
     let mut params = Vec::new();
     let mut context_param = None;
     
     for input in inputs {
         match input {
-            FnArg::Typed(PatType { pat, ty, .. }) => {
+            FnArg::Typed(PatType { attrs, pat, ty, .. }) => {
                 if let Pat::Ident(pat_ident) = pat.as_ref() {
                     let param_name = &pat_ident.ident;
                     
                     // Check if this is a context parameter (by name or reference type)
                     if param_name == "context" || is_reference_type(ty) {
+                        if let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("param")) {
+                            return Err(syn::Error::new_spanned(attr, "`#[param]` cannot be used on the context parameter"));
+                        }
+                        
                         context_param = Some(param_name.clone());
                         continue;
                     }
                     
-                    // For now, no parameter documentation since it's not stable
+                    let (description, constraints) = parse_param_attributes(attrs, ty)?;
+                    let doc = extract_doc_comment(attrs);
+                    
+                    if let Some(description) = &description
+                        && !doc.is_empty()
+                    {
+                        return Err(syn::Error::new_spanned(
+                            description,
+                            "parameter has both a doc comment and a `description`; use one of them",
+                        ));
+                    }
+                    
                     params.push(Parameter {
                         name: param_name.clone(),
                         ty: (**ty).clone(),
-                        doc: String::new(),
+                        doc: description.map(|lit| lit.value()).unwrap_or(doc),
+                        attrs: attrs.iter().filter(|attr| is_forwarded_attribute(attr)).cloned().collect(),
+                        constraints,
                     });
                 }
             }
             FnArg::Receiver(_) => {
-                return Err(syn::Error::new_spanned(input, "Self parameter not supported"));
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "`#[tool]` on a method requires `#[tools]` on the impl block",
+                ));
             }
         }
     }
@@ -120,24 +265,281 @@ fn is_reference_type(ty: &Type) -> bool {
     matches!(ty, Type::Reference(_))
 }
 
+/// `#[serde(...)]` and `#[schemars(...)]` on a parameter are moved to its struct field.
+fn is_forwarded_attribute(attr: &Attribute) -> bool {
+    attr.path().is_ident("serde") || attr.path().is_ident("schemars")
+}
+
+/// Attributes consumed by the macro, which are not valid on the function itself.
+fn is_parameter_attribute(attr: &Attribute) -> bool {
+    is_forwarded_attribute(attr) || attr.path().is_ident("param") || attr.path().is_ident("doc")
+}
+
+fn strip_parameter_attributes(sig: &mut Signature) {
+    for input in sig.inputs.iter_mut() {
+        if let FnArg::Typed(pat_type) = input {
+            pat_type.attrs.retain(|attr| !is_parameter_attribute(attr));
+        }
+    }
+}
+
+/// Schema constraints from `#[param(...)]`, emitted as schema keywords on the property.
+#[derive(Clone, Default)]
+struct Constraints {
+    values: Option<Vec<Expr>>,
+    minimum: Option<Expr>,
+    maximum: Option<Expr>,
+    pattern: Option<LitStr>,
+}
+
+/// What the schema constraints need to know about a parameter's type.
+#[derive(Clone, Copy, PartialEq)]
+enum ParamKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Other,
+}
+
+impl ParamKind {
+    fn of(ty: &Type) -> Self {
+        let ty = option_inner_type(ty).unwrap_or(ty);
+        
+        let Type::Path(type_path) = ty else {
+            return ParamKind::Other;
+        };
+        
+        let Some(segment) = type_path.path.segments.last() else {
+            return ParamKind::Other;
+        };
+        
+        match segment.ident.to_string().as_str() {
+            "String" | "str" | "char" => ParamKind::String,
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+            | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => ParamKind::Integer,
+            "f32" | "f64" => ParamKind::Number,
+            "bool" => ParamKind::Boolean,
+            _ => ParamKind::Other,
+        }
+    }
+    
+    fn is_numeric(self) -> bool {
+        matches!(self, ParamKind::Integer | ParamKind::Number)
+    }
+}
+
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+        && segment.ident == "Option"
+        && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(syn::GenericArgument::Type(inner_type)) = args.args.first()
+    {
+        return Some(inner_type);
+    }
+    None
+}
+
+/// Parse `#[param(description = "...", enum = [...], minimum = .., maximum = .., pattern = "..")]`.
+fn parse_param_attributes(attrs: &[Attribute], ty: &Type) -> syn::Result<(Option<LitStr>, Constraints)> {
+    let kind = ParamKind::of(ty);
+    let mut description: Option<LitStr> = None;
+    let mut constraints = Constraints::default();
+    
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("param")) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+            let duplicate = || meta.error(format!("duplicate `{}` in `#[param]`", key));
+            
+            match key.as_str() {
+                "description" => {
+                    if description.is_some() {
+                        return Err(duplicate());
+                    }
+                    description = Some(meta.value()?.parse()?);
+                }
+                "enum" => {
+                    if constraints.values.is_some() {
+                        return Err(duplicate());
+                    }
+                    let array: ExprArray = meta.value()?.parse()?;
+                    constraints.values = Some(parse_enum_values(array, kind)?);
+                }
+                "minimum" | "maximum" => {
+                    let slot = if key == "minimum" { &mut constraints.minimum } else { &mut constraints.maximum };
+                    if slot.is_some() {
+                        return Err(duplicate());
+                    }
+                    if !kind.is_numeric() {
+                        return Err(meta.error(format!("`{}` can only be used on numeric parameters", key)));
+                    }
+                    let value: Expr = meta.value()?.parse()?;
+                    numeric_value(&value)?;
+                    *slot = Some(value);
+                }
+                "pattern" => {
+                    if constraints.pattern.is_some() {
+                        return Err(duplicate());
+                    }
+                    if kind != ParamKind::String {
+                        return Err(meta.error("`pattern` can only be used on string parameters"));
+                    }
+                    constraints.pattern = Some(meta.value()?.parse()?);
+                }
+                _ => {
+                    return Err(meta.error(
+                        "unknown `#[param]` key, expected one of `description`, `enum`, `minimum`, `maximum`, `pattern`",
+                    ));
+                }
+            }
+            
+            Ok(())
+        })?;
+    }
+    
+    validate_constraints(&constraints, attrs)?;
+    
+    Ok((description, constraints))
+}
+
+fn validate_constraints(constraints: &Constraints, attrs: &[Attribute]) -> syn::Result<()> {
+    if constraints.values.is_some() {
+        let conflicting = [
+            ("minimum", constraints.minimum.is_some()),
+            ("maximum", constraints.maximum.is_some()),
+            ("pattern", constraints.pattern.is_some()),
+        ];
+        
+        if let Some((name, _)) = conflicting.iter().find(|(_, present)| *present) {
+            let attr = attrs.iter().find(|attr| attr.path().is_ident("param"));
+            
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("`enum` cannot be combined with `{}`", name),
+            ));
+        }
+    }
+    
+    if let (Some(minimum), Some(maximum)) = (&constraints.minimum, &constraints.maximum)
+        && numeric_value(minimum)? > numeric_value(maximum)?
+    {
+        return Err(syn::Error::new_spanned(maximum, "`maximum` must not be less than `minimum`"));
+    }
+    
+    Ok(())
+}
+
+fn parse_enum_values(array: ExprArray, kind: ParamKind) -> syn::Result<Vec<Expr>> {
+    if array.elems.is_empty() {
+        return Err(syn::Error::new_spanned(array, "`enum` must list at least one value"));
+    }
+    
+    for value in &array.elems {
+        let valid = match kind {
+            ParamKind::String => matches!(value, Expr::Lit(lit) if matches!(lit.lit, Lit::Str(_))),
+            ParamKind::Integer => matches!(numeric_literal(value), Some(Lit::Int(_))),
+            ParamKind::Number => numeric_literal(value).is_some(),
+            ParamKind::Boolean => matches!(value, Expr::Lit(lit) if matches!(lit.lit, Lit::Bool(_))),
+            ParamKind::Other => {
+                return Err(syn::Error::new_spanned(
+                    array,
+                    "`enum` can only be used on string, numeric or boolean parameters; use a Rust enum for other types",
+                ));
+            }
+        };
+        
+        if !valid {
+            return Err(syn::Error::new_spanned(value, "`enum` value does not match the parameter type"));
+        }
+    }
+    
+    Ok(array.elems.into_iter().collect())
+}
+
+/// The literal of a numeric expression such as `5`, `2.5` or `-1`.
+fn numeric_literal(expr: &Expr) -> Option<&Lit> {
+    match expr {
+        Expr::Lit(lit) if matches!(lit.lit, Lit::Int(_) | Lit::Float(_)) => Some(&lit.lit),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => numeric_literal(&unary.expr),
+        _ => None,
+    }
+}
+
+fn numeric_value(expr: &Expr) -> syn::Result<f64> {
+    let value = match numeric_literal(expr) {
+        Some(Lit::Int(int)) => int.base10_parse::<f64>()?,
+        Some(Lit::Float(float)) => float.base10_parse::<f64>()?,
+        _ => return Err(syn::Error::new_spanned(expr, "expected a numeric literal")),
+    };
+    
+    match expr {
+        Expr::Unary(_) => Ok(-value),
+        _ => Ok(value),
+    }
+}
+
+impl Constraints {
+    /// `#[schemars(extend(...))]` adding the constraints to the field's schema.
+    fn to_attribute(&self, ty: &Type) -> Option<TokenStream2> {
+        let mut keywords = Vec::new();
+        
+        if let Some(values) = &self.values {
+            // Optional parameters must still accept null
+            if option_inner_type(ty).is_some() {
+                keywords.push(quote! { "enum" = [#(#values,)* null] });
+            } else {
+                keywords.push(quote! { "enum" = [#(#values),*] });
+            }
+        }
+        
+        if let Some(minimum) = &self.minimum {
+            keywords.push(quote! { "minimum" = #minimum });
+        }
+        
+        if let Some(maximum) = &self.maximum {
+            keywords.push(quote! { "maximum" = #maximum });
+        }
+        
+        if let Some(pattern) = &self.pattern {
+            keywords.push(quote! { "pattern" = #pattern });
+        }
+        
+        if keywords.is_empty() {
+            None
+        } else {
+            Some(quote! { #[schemars(extend(#(#keywords),*))] })
+        }
+    }
+}
+
 fn generate_params_struct(struct_name: &Ident, params: &[Parameter]) -> syn::Result<TokenStream2> {
     let fields = params.iter().map(|param| {
         let name = &param.name;
         let ty = &param.ty;
         let doc = &param.doc;
+        let attrs = &param.attrs;
+        let constraints = param.constraints.to_attribute(ty);
         
         if doc.is_empty() {
-            quote! { pub #name: #ty }
+            quote! {
+                #(#attrs)*
+                #constraints
+                pub #name: #ty
+            }
         } else {
             quote! {
                 #[doc = #doc]
+                #(#attrs)*
+                #constraints
                 pub #name: #ty
             }
         }
     });
     
     Ok(quote! {
-        #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+        #[derive(Clone, Debug, serde::Deserialize, serde::Serialize, responses::schemars::JsonSchema)]
+        #[schemars(crate = "responses::schemars")]
         pub struct #struct_name {
             #(#fields,)*
         }
@@ -147,20 +549,62 @@ fn generate_params_struct(struct_name: &Ident, params: &[Parameter]) -> syn::Res
 fn generate_handler_impl(
     handler_name: &Ident, 
     params_name: &Ident, 
-    tool_name: &str, 
+    args: &ToolArgs, 
     description: &str,
     input_fn: &ItemFn,
     params: &[Parameter],
     context_param: &Option<Ident>,
 ) -> syn::Result<TokenStream2> {
-    let invoke_method = generate_invoke_method(input_fn, params_name, params, context_param)?;
-    let schema_properties = generate_schema_properties(params)?;
+    let parse_params = generate_parse_params(params_name, args);
+    let invoke_method = generate_invoke_method(input_fn, &parse_params, params, context_param)?;
+    let call_impl = generate_call_impl(handler_name, &parse_params, input_fn, params, context_param)?;
+    
+    // Extract tool name from arguments or use function name
+    let tool_name = args.name.as_ref().map(|lit| lit.value()).unwrap_or_else(|| input_fn.sig.ident.to_string());
+    
+    let function_handler = generate_function_handler(
+        &quote! { #handler_name },
+        &Generics::default(),
+        params_name,
+        &tool_name,
+        description,
+        args,
+    );
     
     Ok(quote! {
         #[derive(Clone)]
         pub struct #handler_name;
         
-        impl responses::functions::FunctionHandler for #handler_name {
+        #function_handler
+        
+        #call_impl
+        
+        impl #handler_name {
+            #invoke_method
+        }
+    })
+}
+
+/// `FunctionHandler` for the handler type, plus conversion into a `Tool`.
+fn generate_function_handler(
+    handler_ty: &TokenStream2,
+    generics: &Generics,
+    params_name: &Ident,
+    tool_name: &str,
+    description: &str,
+    args: &ToolArgs,
+) -> TokenStream2 {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    
+    let requires_approval = args.requires_approval;
+    let (strict_flag, make_strict) = if args.strict {
+        (quote! { Some(true) }, quote! { responses::schema::make_strict(&mut schema); })
+    } else {
+        (quote! { None }, quote! {})
+    };
+    
+    quote! {
+        impl #impl_generics responses::functions::FunctionHandler for #handler_ty #where_clause {
             type Parameters = #params_name;
             
             fn name(&self) -> &str {
@@ -169,44 +613,32 @@ fn generate_handler_impl(
             
             fn tool(&self) -> responses::types::Tool {
                 use responses::types::{Tool, ToolFunction};
-                use schemars::Schema;
-                use serde_json::{json, Value};
                 
-                let schema = Self::build_schema();
+                // Derived from the parameter types, so nested structs, enums and
+                // serde attributes are described exactly as they are deserialized
+                let mut schema = responses::schema::from::<#params_name>();
+                schema.remove("title");
+                #make_strict
+                
                 Tool::Function(ToolFunction {
                     name: #tool_name.to_string(),
                     description: if #description.is_empty() { None } else { Some(#description.to_string()) },
                     parameters: schema,
-                    strict: None,
+                    strict: #strict_flag,
                 })
             }
-        }
-        
-        impl From<#handler_name> for responses::types::Tool {
-            fn from(handler: #handler_name) -> responses::types::Tool {
-                handler.tool()
+            
+            fn requires_approval(&self) -> bool {
+                #requires_approval
             }
         }
         
-        impl #handler_name {
-            fn build_schema() -> schemars::Schema {
-                use serde_json::{json, Value, Map};
-                
-                #schema_properties
-                
-                let schema_value = json!({
-                    "type": "object",
-                    "properties": properties,
-                    "required": required_fields,
-                    "additionalProperties": false
-                });
-                
-                serde_json::from_value(schema_value).unwrap()
+        impl #impl_generics From<#handler_ty> for responses::types::Tool #where_clause {
+            fn from(handler: #handler_ty) -> responses::types::Tool {
+                responses::functions::FunctionHandler::tool(&handler)
             }
-            
-            #invoke_method
         }
-    })
+    }
 }
 
 fn generate_wrapper_function(
@@ -230,44 +662,13 @@ fn generate_handler_creator(fn_name: &Ident, handler_name: &Ident) -> syn::Resul
 
 fn generate_invoke_method(
     input_fn: &ItemFn,
-    params_name: &Ident,
+    parse_params: &TokenStream2,
     params: &[Parameter],
     context_param: &Option<Ident>,
 ) -> syn::Result<TokenStream2> {
-    let fn_name = &input_fn.sig.ident;
     let is_async = input_fn.sig.asyncness.is_some();
     let return_type = extract_inner_return_type(&input_fn.sig.output)?;
-    
-    // Generate parameter extraction from the struct
-    let param_extractions = params.iter().map(|param| {
-        let name = &param.name;
-        quote! { params.#name }
-    });
-    
-    // Generate the function call
-    let function_call = if let Some(_context) = context_param {
-        // Function has context parameter
-        if is_async {
-            quote! {
-                #fn_name(#(#param_extractions,)* context).await
-            }
-        } else {
-            quote! {
-                #fn_name(#(#param_extractions,)* context)
-            }
-        }
-    } else {
-        // Function has no context parameter
-        if is_async {
-            quote! {
-                #fn_name(#(#param_extractions,)*).await
-            }
-        } else {
-            quote! {
-                #fn_name(#(#param_extractions,)*)
-            }
-        }
-    };
+    let function_call = generate_function_call(input_fn, params, context_param);
     
     // Generate the invoke method with automatic name validation
     let invoke_method = if let Some(context) = context_param {
@@ -279,15 +680,11 @@ fn generate_invoke_method(
                     call: &responses::types::OutputFunctionCall, 
                     context: &#context_type
                 ) -> responses::error::Result<Option<#return_type>> {
-                    if call.name != self.name() {
+                    if call.name != responses::functions::FunctionHandler::name(self) {
                         return Ok(None);
                     }
                     
-                    let params: #params_name = serde_json::from_str(&call.arguments)
-                        .map_err(|e| responses::error::Error::FunctionParameterParsing { 
-                            function_name: call.name.clone(), 
-                            source: e 
-                        })?;
+                    #parse_params
                     let result = #function_call?;
                     Ok(Some(result))
                 }
@@ -299,15 +696,11 @@ fn generate_invoke_method(
                     call: &responses::types::OutputFunctionCall, 
                     context: &#context_type
                 ) -> responses::error::Result<Option<#return_type>> {
-                    if call.name != self.name() {
+                    if call.name != responses::functions::FunctionHandler::name(self) {
                         return Ok(None);
                     }
                     
-                    let params: #params_name = serde_json::from_str(&call.arguments)
-                        .map_err(|e| responses::error::Error::FunctionParameterParsing { 
-                            function_name: call.name.clone(), 
-                            source: e 
-                        })?;
+                    #parse_params
                     let result = #function_call?;
                     Ok(Some(result))
                 }
@@ -321,15 +714,11 @@ fn generate_invoke_method(
                     &self, 
                     call: &responses::types::OutputFunctionCall
                 ) -> responses::error::Result<Option<#return_type>> {
-                    if call.name != self.name() {
+                    if call.name != responses::functions::FunctionHandler::name(self) {
                         return Ok(None);
                     }
                     
-                    let params: #params_name = serde_json::from_str(&call.arguments)
-                        .map_err(|e| responses::error::Error::FunctionParameterParsing { 
-                            function_name: call.name.clone(), 
-                            source: e 
-                        })?;
+                    #parse_params
                     let result = #function_call?;
                     Ok(Some(result))
                 }
@@ -340,15 +729,11 @@ fn generate_invoke_method(
                     &self, 
                     call: &responses::types::OutputFunctionCall
                 ) -> responses::error::Result<Option<#return_type>> {
-                    if call.name != self.name() {
+                    if call.name != responses::functions::FunctionHandler::name(self) {
                         return Ok(None);
                     }
                     
-                    let params: #params_name = serde_json::from_str(&call.arguments)
-                        .map_err(|e| responses::error::Error::FunctionParameterParsing { 
-                            function_name: call.name.clone(), 
-                            source: e 
-                        })?;
+                    #parse_params
                     let result = #function_call?;
                     Ok(Some(result))
                 }
@@ -359,6 +744,74 @@ fn generate_invoke_method(
     Ok(invoke_method)
 }
 
+/// The call of the original function with the parsed `params` (and `context`).
+fn generate_function_call(input_fn: &ItemFn, params: &[Parameter], context_param: &Option<Ident>) -> TokenStream2 {
+    let fn_name = &input_fn.sig.ident;
+    let is_async = input_fn.sig.asyncness.is_some();
+    
+    // Generate parameter extraction from the struct
+    let param_extractions = params.iter().map(|param| {
+        let name = &param.name;
+        quote! { params.#name }
+    });
+    
+    let context = context_param.as_ref().map(|_| quote! { context });
+    
+    if is_async {
+        quote! { #fn_name(#(#param_extractions,)* #context).await }
+    } else {
+        quote! { #fn_name(#(#param_extractions,)* #context) }
+    }
+}
+
+/// Implement `CallHandler` (or `ContextCallHandler`), which lets the handler be added to a `Toolbox`.
+fn generate_call_impl(
+    handler_name: &Ident,
+    parse_params: &TokenStream2,
+    input_fn: &ItemFn,
+    params: &[Parameter],
+    context_param: &Option<Ident>,
+) -> syn::Result<TokenStream2> {
+    let return_type = extract_inner_return_type(&input_fn.sig.output)?;
+    let function_call = generate_function_call(input_fn, params, context_param);
+    
+    if let Some(context) = context_param {
+        let context_type = get_context_type(input_fn, context)?;
+        
+        Ok(quote! {
+            impl responses::functions::ContextCallHandler for #handler_name {
+                type Context = #context_type;
+                type Output = #return_type;
+                
+                async fn call_with_context(
+                    &self,
+                    call: &responses::types::OutputFunctionCall,
+                    context: &#context_type
+                ) -> responses::error::Result<#return_type> {
+                    #parse_params
+                    let result = #function_call?;
+                    Ok(result)
+                }
+            }
+        })
+    } else {
+        Ok(quote! {
+            impl responses::functions::CallHandler for #handler_name {
+                type Output = #return_type;
+                
+                async fn call(
+                    &self,
+                    call: &responses::types::OutputFunctionCall
+                ) -> responses::error::Result<#return_type> {
+                    #parse_params
+                    let result = #function_call?;
+                    Ok(result)
+                }
+            }
+        })
+    }
+}
+
 fn extract_inner_return_type(output: &syn::ReturnType) -> syn::Result<Type> {
     match output {
         syn::ReturnType::Default => {
@@ -399,103 +852,6 @@ fn get_context_type(input_fn: &ItemFn, context_param: &Ident) -> syn::Result<Typ
 }
 
 
-fn generate_schema_properties(params: &[Parameter]) -> syn::Result<TokenStream2> {
-    let mut property_entries = Vec::new();
-    let mut required_fields = Vec::new();
-    
-    for param in params {
-        let name = param.name.to_string();
-        let field_schema = generate_field_schema(&param.ty)?;
-        
-        property_entries.push(quote! {
-            properties.insert(#name.to_string(), #field_schema);
-        });
-        
-        // Check if the field is required (not Option<T>)
-        if !is_option_type(&param.ty) {
-            required_fields.push(quote! {
-                required_fields.push(#name.to_string());
-            });
-        }
-    }
-    
-    Ok(quote! {
-        let mut properties = serde_json::Map::new();
-        let mut required_fields: Vec<String> = Vec::new();
-        
-        #(#property_entries)*
-        #(#required_fields)*
-    })
-}
-
-fn generate_field_schema(ty: &Type) -> syn::Result<TokenStream2> {
-    // For now, we'll handle basic types. This can be extended later.
-    if is_option_type(ty) {
-        if let Type::Path(type_path) = ty {
-            if let Some(segment) = type_path.path.segments.last() {
-                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                    if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
-                        // For Option<String>, Option<f64>, etc.
-                        return generate_inner_field_schema(inner_type);
-                    }
-                }
-            }
-        }
-    }
-    
-    generate_inner_field_schema(ty)
-}
-
-fn generate_inner_field_schema(ty: &Type) -> syn::Result<TokenStream2> {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            let type_name = segment.ident.to_string();
-            return Ok(match type_name.as_str() {
-                "String" | "str" => quote! { json!({"type": "string", "description": "A text value"}) },
-                "f64" | "f32" => quote! { json!({"type": "number", "format": "double", "description": "A floating point number"}) },
-                "i32" | "i64" | "u32" | "u64" | "isize" | "usize" => quote! { json!({"type": "integer", "description": "An integer number"}) },
-                "i8" | "i16" | "u8" | "u16" => quote! { json!({"type": "integer", "description": "An integer number"}) },
-                "bool" => quote! { json!({"type": "boolean", "description": "A boolean value"}) },
-                "Vec" => {
-                    // Handle Vec<T> types
-                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                        if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
-                            let inner_schema = generate_inner_field_schema(inner_type)?;
-                            return Ok(quote! { 
-                                json!({
-                                    "type": "array", 
-                                    "items": #inner_schema,
-                                    "description": "An array of values"
-                                }) 
-                            });
-                        }
-                    }
-                    quote! { json!({"type": "array", "items": {"type": "string"}, "description": "An array of values"}) }
-                },
-                "HashMap" | "BTreeMap" => quote! { 
-                    json!({
-                        "type": "object", 
-                        "additionalProperties": true,
-                        "description": "A map of key-value pairs"
-                    }) 
-                },
-                _ => quote! { json!({"type": "string", "description": "A text value"}) }, // Default to string
-            });
-        }
-    }
-    
-    Ok(quote! { json!({"type": "string", "description": "A text value"}) }) // Default fallback
-}
-
-fn is_option_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            return segment.ident == "Option";
-        }
-    }
-    false
-}
-
 fn capitalize_first_letter(s: &str) -> String {
     let mut result = String::new();
     let mut capitalize_next = true;
@@ -0,0 +1,250 @@
+//! `#[tools]` on impl blocks: tools implemented as `&self` methods of a service.
+
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, FnArg, ImplItem, ImplItemFn, ItemImpl, Meta, Type, parse_quote,
+};
+
+use crate::{
+    ToolArgs, capitalize_first_letter, extract_doc_comment, extract_inner_return_type,
+    generate_function_handler, generate_params_struct, generate_parse_params, parse_function_parameters,
+    strip_parameter_attributes,
+};
+
+pub(crate) fn generate_tools_impl(mut item: ItemImpl) -> syn::Result<TokenStream2> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(syn::Error::new_spanned(path, "`#[tools]` must be used on an inherent impl block"));
+    }
+
+    let service_name = type_name(&item.self_ty)?;
+    let self_ty = item.self_ty.clone();
+
+    let mut generated = Vec::new();
+    let mut constructors = Vec::new();
+    let mut creator_names = Vec::new();
+
+    for impl_item in item.items.iter_mut() {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+
+        let Some(index) = method.attrs.iter().position(|attr| attr.path().is_ident("tool")) else {
+            continue;
+        };
+
+        let attr = method.attrs.remove(index);
+        let args = parse_tool_args(&attr)?;
+
+        let tool = MethodTool::new(&service_name, &self_ty, &item.generics, method, args)?;
+
+        generated.push(tool.generate()?);
+        constructors.push(tool.generate_constructor());
+        creator_names.push(tool.creator_name());
+    }
+
+    if generated.is_empty() {
+        return Err(syn::Error::new_spanned(&item.self_ty, "`#[tools]` impl block has no `#[tool]` methods"));
+    }
+
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        #item
+
+        #(#generated)*
+
+        impl #impl_generics #self_ty #where_clause {
+            #(#constructors)*
+
+            /// A toolbox with all tools of this service.
+            pub fn toolbox(self: &std::sync::Arc<Self>) -> responses::Toolbox
+            where
+                Self: Send + Sync + 'static,
+            {
+                responses::Toolbox::new()
+                    #(.register(self.#creator_names()))*
+            }
+        }
+    })
+}
+
+fn parse_tool_args(attr: &Attribute) -> syn::Result<ToolArgs> {
+    match &attr.meta {
+        Meta::Path(_) => syn::parse2(TokenStream2::new()),
+        _ => attr.parse_args(),
+    }
+}
+
+fn type_name(ty: &Type) -> syn::Result<String> {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return Ok(segment.ident.to_string());
+    }
+    Err(syn::Error::new_spanned(ty, "`#[tools]` requires a named type"))
+}
+
+/// A `#[tool]` method and the names of the items generated for it.
+struct MethodTool {
+    method: ImplItemFn,
+    args: ToolArgs,
+    self_ty: Type,
+    generics: syn::Generics,
+    params: Vec<crate::Parameter>,
+    params_name: Ident,
+    handler_name: Ident,
+}
+
+impl MethodTool {
+    fn new(
+        service_name: &str,
+        self_ty: &Type,
+        generics: &syn::Generics,
+        method: &mut ImplItemFn,
+        args: ToolArgs,
+    ) -> syn::Result<Self> {
+        match method.sig.inputs.first() {
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &method.sig,
+                    "`#[tool]` methods must take `&self`",
+                ));
+            }
+        }
+
+        let (params, context_param) = parse_function_parameters(method.sig.inputs.iter().skip(1))?;
+
+        if let Some(context) = context_param {
+            return Err(syn::Error::new_spanned(
+                context,
+                "`#[tool]` methods cannot take a context parameter; keep shared state in `self`",
+            ));
+        }
+
+        // Attributes forwarded to the parameter struct are not valid on the method itself
+        strip_parameter_attributes(&mut method.sig);
+
+        let base_name = format!("{}{}", service_name, capitalize_first_letter(&method.sig.ident.to_string()));
+
+        let mut generics = generics.clone();
+        if !generics.params.is_empty() {
+            generics.make_where_clause().predicates.push(parse_quote! { #self_ty: Send + Sync });
+        }
+
+        Ok(Self {
+            method: method.clone(),
+            args,
+            self_ty: self_ty.clone(),
+            generics,
+            params,
+            params_name: format_ident!("{}Params", base_name),
+            handler_name: format_ident!("{}Handler", base_name),
+        })
+    }
+
+    fn handler_ty(&self) -> TokenStream2 {
+        let handler_name = &self.handler_name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        quote! { #handler_name #ty_generics }
+    }
+
+    fn generate(&self) -> syn::Result<TokenStream2> {
+        let handler_name = &self.handler_name;
+        let handler_ty = self.handler_ty();
+        let params_name = &self.params_name;
+        let self_ty = &self.self_ty;
+        let method_name = &self.method.sig.ident;
+
+        let (impl_generics, _, where_clause) = self.generics.split_for_impl();
+        let declared_generics = &self.generics;
+
+        let tool_name = self.args.name.as_ref().map(|lit| lit.value()).unwrap_or_else(|| method_name.to_string());
+        let description = extract_doc_comment(&self.method.attrs);
+
+        let params_struct = generate_params_struct(params_name, &self.params)?;
+        let function_handler = generate_function_handler(
+            &handler_ty,
+            &self.generics,
+            params_name,
+            &tool_name,
+            &description,
+            &self.args,
+        );
+
+        let return_type = extract_inner_return_type(&self.method.sig.output)?;
+
+        let param_names = self.params.iter().map(|param| &param.name);
+        let await_call = self.method.sig.asyncness.map(|_| quote! { .await });
+
+        let parse_params = generate_parse_params(params_name, &self.args);
+        let parse_and_call = quote! {
+            #parse_params
+            let result = self.service.#method_name(#(params.#param_names),*) #await_call?;
+        };
+
+        Ok(quote! {
+            #params_struct
+
+            /// Handler calling the tool method on a shared service.
+            pub struct #handler_name #declared_generics #where_clause {
+                service: std::sync::Arc<#self_ty>,
+            }
+
+            impl #impl_generics Clone for #handler_ty #where_clause {
+                fn clone(&self) -> Self {
+                    Self { service: std::sync::Arc::clone(&self.service) }
+                }
+            }
+
+            #function_handler
+
+            impl #impl_generics responses::functions::CallHandler for #handler_ty #where_clause {
+                type Output = #return_type;
+
+                async fn call(
+                    &self,
+                    call: &responses::types::OutputFunctionCall
+                ) -> responses::error::Result<#return_type> {
+                    #parse_and_call
+                    Ok(result)
+                }
+            }
+
+            impl #impl_generics #handler_ty #where_clause {
+                pub async fn invoke(
+                    &self,
+                    call: &responses::types::OutputFunctionCall
+                ) -> responses::error::Result<Option<#return_type>> {
+                    if call.name != responses::functions::FunctionHandler::name(self) {
+                        return Ok(None);
+                    }
+
+                    #parse_and_call
+                    Ok(Some(result))
+                }
+            }
+        })
+    }
+
+    fn creator_name(&self) -> Ident {
+        format_ident!("{}_handler", self.method.sig.ident)
+    }
+
+    /// `{method}_handler()` on the service, creating the handler from an `Arc` of it.
+    fn generate_constructor(&self) -> TokenStream2 {
+        let creator_name = self.creator_name();
+        let handler_name = &self.handler_name;
+        let handler_ty = self.handler_ty();
+        let doc = format!("Handler for the `{}` tool.", self.method.sig.ident);
+
+        quote! {
+            #[doc = #doc]
+            pub fn #creator_name(self: &std::sync::Arc<Self>) -> #handler_ty {
+                #handler_name { service: std::sync::Arc::clone(self) }
+            }
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Branching conversations for "regenerate" and "edit earlier message" features.
+//!
+//! A [`ConversationTree`] records every input ever added to a conversation. An input
+//! can have alternatives: other replies to the same question, or edited versions of
+//! a user message. One alternative per turn is selected, and the selected path is
+//! the conversation sent to the model.
+//!
+//! ```
+//! use responses::branch::ConversationTree;
+//! use responses::types::{Input, InputMessage, Role};
+//! use responses::Messages;
+//!
+//! let assistant = |content: &str| Input::Message(InputMessage {
+//!     role: Role::Assistant,
+//!     content: content.to_string(),
+//! });
+//!
+//! let mut tree = ConversationTree::from_messages(Messages::new().user("Name a color"));
+//! let blue = tree.push(assistant("Blue"));
+//!
+//! // Regenerate: the new reply is selected, and the old one is kept
+//! let red = tree.add_alternative(blue, assistant("Red")).unwrap();
+//! assert_eq!(tree.alternatives(red).unwrap(), &[blue, red]);
+//! assert_eq!(tree.messages().len(), 2);
+//!
+//! // Switch back to the first reply
+//! tree.select(blue).unwrap();
+//! assert_eq!(tree.head(), Some(blue));
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    messages::Messages,
+    types::Input,
+};
+use serde::{Deserialize, Serialize};
+
+/// The id of an input in a [`ConversationTree`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Node {
+    input: Input,
+    parent: Option<NodeId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<NodeId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selected: Option<NodeId>,
+}
+
+/// A conversation with alternative inputs per turn and a selected path through them.
+///
+/// Nodes are never removed, so ids stay valid and every branch can be revisited.
+/// Trees serialize with serde, keeping the variables and locale of the conversation
+/// they were created from. Deserializing checks that the ids form a tree.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "TreeData")]
+pub struct ConversationTree {
+    nodes: Vec<Node>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    roots: Vec<NodeId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selected_root: Option<NodeId>,
+    settings: Messages,
+}
+
+/// A deserialized tree before its ids are checked.
+#[derive(Deserialize)]
+struct TreeData {
+    nodes: Vec<Node>,
+    #[serde(default)]
+    roots: Vec<NodeId>,
+    #[serde(default)]
+    selected_root: Option<NodeId>,
+    settings: Messages,
+}
+
+impl TryFrom<TreeData> for ConversationTree {
+    type Error = Error;
+
+    /// Every node must follow an earlier node, so there are no cycles, and be listed
+    /// once among the children of its parent. Selections must be children.
+    fn try_from(data: TreeData) -> Result<Self> {
+        let TreeData { nodes, roots, selected_root, settings } = data;
+        let invalid = |reason: String| Error::InvalidConversationTree(reason);
+
+        let mut listed = vec![0; nodes.len()];
+        let siblings = std::iter::once((None, &roots, selected_root))
+            .chain(nodes.iter().enumerate().map(|(index, node)| (Some(index), &node.children, node.selected)));
+
+        for (parent, children, selected) in siblings {
+            for child in children {
+                let node = nodes.get(child.0).ok_or_else(|| invalid(format!("node {} does not exist", child.0)))?;
+                if node.parent.map(|id| id.0) != parent {
+                    return Err(invalid(format!("node {} is listed under a node that is not its parent", child.0)));
+                }
+                listed[child.0] += 1;
+            }
+            if let Some(selected) = selected
+                && !children.contains(&selected)
+            {
+                return Err(invalid(format!("selected node {} is not a child of its parent", selected.0)));
+            }
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            if node.parent.is_some_and(|parent| parent.0 >= index) {
+                return Err(invalid(format!("node {} does not follow an earlier node", index)));
+            }
+            if listed[index] != 1 {
+                return Err(invalid(format!("node {} is listed {} times among the children of its parent", index, listed[index])));
+            }
+        }
+
+        Ok(Self { nodes, roots, selected_root, settings })
+    }
+}
+
+impl ConversationTree {
+    pub fn new() -> Self {
+        Self::from_messages(Messages::new())
+    }
+
+    /// A tree with a single branch holding `messages`, keeping their variables and locale.
+    pub fn from_messages(messages: Messages) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            selected_root: None,
+            settings: messages.with_inputs(Vec::new()),
+        };
+        tree.extend(messages);
+        tree
+    }
+
+    /// Append `input` to the selected path.
+    pub fn push(&mut self, input: Input) -> NodeId {
+        let head = self.head();
+        self.add_child(head, input)
+    }
+
+    /// Append the inputs of `messages` to the selected path, returning the new head.
+    pub fn extend(&mut self, messages: Messages) -> Option<NodeId> {
+        for input in messages.inputs().to_vec() {
+            self.push(input);
+        }
+        self.head()
+    }
+
+    /// Add `input` as an alternative to `id`, such as a regenerated reply or an
+    /// edited user message, and select it.
+    ///
+    /// The alternative starts a new branch: inputs pushed next follow it.
+    pub fn add_alternative(&mut self, id: NodeId, input: Input) -> Result<NodeId> {
+        let parent = self.node(id)?.parent;
+        Ok(self.add_child(parent, input))
+    }
+
+    /// Select `id`, and the inputs leading to it, as the path to continue from.
+    ///
+    /// The inputs after `id` are those selected when its branch was last visited.
+    pub fn select(&mut self, id: NodeId) -> Result<()> {
+        let mut child = id;
+        loop {
+            let parent = self.node(child)?.parent;
+            self.set_selected(parent, child);
+            match parent {
+                Some(parent) => child = parent,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// The alternatives at the turn of `id`, including itself, in the order they were added.
+    pub fn alternatives(&self, id: NodeId) -> Result<&[NodeId]> {
+        let parent = self.node(id)?.parent;
+        Ok(self.children(parent))
+    }
+
+    /// The input of `id`.
+    pub fn get(&self, id: NodeId) -> Option<&Input> {
+        self.nodes.get(id.0).map(|node| &node.input)
+    }
+
+    /// The input `id` follows, or `None` for the first input of a branch.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes.get(id.0).and_then(|node| node.parent)
+    }
+
+    /// The last input of the selected path.
+    pub fn head(&self) -> Option<NodeId> {
+        self.selected_path().last().copied()
+    }
+
+    /// The ids of the selected path, from the first input to the head.
+    pub fn selected_path(&self) -> Vec<NodeId> {
+        let mut path = Vec::new();
+        let mut next = self.selected_root;
+        while let Some(id) = next {
+            path.push(id);
+            next = self.nodes[id.0].selected;
+        }
+        path
+    }
+
+    /// The conversation along the selected path.
+    pub fn messages(&self) -> Messages {
+        self.collect(self.selected_path())
+    }
+
+    /// The conversation from the first input up to and including `id`.
+    ///
+    /// Send `branch(parent)` to regenerate the reply after `parent`.
+    pub fn branch(&self, id: NodeId) -> Result<Messages> {
+        let mut path = vec![id];
+        let mut next = self.node(id)?.parent;
+        while let Some(parent) = next {
+            path.push(parent);
+            next = self.nodes[parent.0].parent;
+        }
+        path.reverse();
+        Ok(self.collect(path))
+    }
+
+    /// The number of inputs in every branch.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn node(&self, id: NodeId) -> Result<&Node> {
+        self.nodes.get(id.0).ok_or(Error::UnknownNode { id: id.0 })
+    }
+
+    fn add_child(&mut self, parent: Option<NodeId>, input: Input) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            input,
+            parent,
+            children: Vec::new(),
+            selected: None,
+        });
+
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.set_selected(parent, id);
+        id
+    }
+
+    fn children(&self, parent: Option<NodeId>) -> &[NodeId] {
+        match parent {
+            Some(parent) => &self.nodes[parent.0].children,
+            None => &self.roots,
+        }
+    }
+
+    fn set_selected(&mut self, parent: Option<NodeId>, child: NodeId) {
+        match parent {
+            Some(parent) => self.nodes[parent.0].selected = Some(child),
+            None => self.selected_root = Some(child),
+        }
+    }
+
+    fn collect(&self, path: Vec<NodeId>) -> Messages {
+        let inputs = path.into_iter().map(|id| self.nodes[id.0].input.clone()).collect();
+        self.settings.with_inputs(inputs)
+    }
+}
+
+impl Default for ConversationTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{InputMessage, Role};
+
+    fn message(role: Role, content: &str) -> Input {
+        Input::Message(InputMessage {
+            role,
+            content: content.to_string(),
+        })
+    }
+
+    fn contents(messages: &Messages) -> Vec<String> {
+        messages
+            .inputs()
+            .iter()
+            .map(|input| match input {
+                Input::Message(message) => message.content.clone(),
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_edit_and_regenerate() {
+        let mut tree = ConversationTree::from_messages(Messages::new().system("Be brief").var("name", "Ada"));
+        let question = tree.push(message(Role::User, "What's 2+2?"));
+        let answer = tree.push(message(Role::Assistant, "5"));
+
+        // Regenerate the reply
+        let retry = tree.add_alternative(answer, message(Role::Assistant, "4")).unwrap();
+        assert_eq!(contents(&tree.messages()), vec!["Be brief", "What's 2+2?", "4"]);
+        assert_eq!(tree.alternatives(answer).unwrap(), &[answer, retry]);
+        assert_eq!(contents(&tree.branch(question).unwrap()), vec!["Be brief", "What's 2+2?"]);
+
+        // Edit the question and answer it on a new branch
+        let edited = tree.add_alternative(question, message(Role::User, "What's 3+3?")).unwrap();
+        assert_eq!(contents(&tree.messages()), vec!["Be brief", "What's 3+3?"]);
+        tree.push(message(Role::Assistant, "6"));
+        assert_eq!(contents(&tree.messages()), vec!["Be brief", "What's 3+3?", "6"]);
+
+        // The first branch keeps the reply selected on it
+        tree.select(question).unwrap();
+        assert_eq!(contents(&tree.messages()), vec!["Be brief", "What's 2+2?", "4"]);
+
+        tree.select(answer).unwrap();
+        assert_eq!(tree.head(), Some(answer));
+        assert_eq!(tree.parent(edited), tree.parent(question));
+        assert_eq!(tree.len(), 6);
+
+        // Variables carry over to every branch
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: ConversationTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(contents(&restored.messages()), vec!["Be brief", "What's 2+2?", "5"]);
+        assert!(restored.messages().to_json().unwrap().contains("Ada"));
+    }
+
+    #[test]
+    fn test_unknown_nodes() {
+        let mut tree = ConversationTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.head().is_none());
+
+        let id = ConversationTree::from_messages(Messages::new().user("Hi")).head().unwrap();
+        assert!(matches!(tree.select(id), Err(Error::UnknownNode { id: 0 })));
+        assert!(tree.add_alternative(id, message(Role::User, "Hello")).is_err());
+        assert!(tree.get(id).is_none());
+    }
+
+    #[test]
+    fn test_deserializing_checks_ids() {
+        let tree = ConversationTree::from_messages(Messages::new().user("Hi").assistant("Hello"));
+        let valid = serde_json::to_value(&tree).unwrap();
+        assert!(serde_json::from_value::<ConversationTree>(valid.clone()).is_ok());
+
+        let broken = |pointer: &str, value: serde_json::Value| {
+            let mut json = valid.clone();
+            *json.pointer_mut(pointer).unwrap() = value;
+            serde_json::from_value::<ConversationTree>(json).unwrap_err().to_string()
+        };
+
+        // Out of range ids
+        assert!(broken("/selected_root", serde_json::json!(7)).contains("selected node 7"));
+        assert!(broken("/nodes/0/children", serde_json::json!([1, 9])).contains("node 9 does not exist"));
+
+        // A cycle between the two nodes
+        let mut cycle = valid.clone();
+        cycle["nodes"][0]["parent"] = serde_json::json!(1);
+        cycle["nodes"][1]["children"] = serde_json::json!([0]);
+        cycle["nodes"][1]["selected"] = serde_json::json!(0);
+        cycle["roots"] = serde_json::json!([]);
+        cycle.as_object_mut().unwrap().remove("selected_root");
+        let error = serde_json::from_value::<ConversationTree>(cycle).unwrap_err().to_string();
+        assert!(error.contains("node 0 does not follow an earlier node"), "{}", error);
+    }
+}
@@ -0,0 +1,129 @@
+//! Cancellation and deadlines for in-flight requests.
+
+use crate::error::{Error, Result};
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{sync::Notify, time::Instant};
+
+/// A token that cancels every request it is attached to.
+///
+/// Clones share the same state, so a token can be handed to a request builder
+/// and cancelled from elsewhere, e.g. when a client disconnects.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Create a new, uncancelled token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all requests using this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        let notified = self.inner.notify.notified();
+        let mut notified = std::pin::pin!(notified);
+
+        // Register for notification before checking the flag to avoid missing a wakeup.
+        notified.as_mut().enable();
+
+        if self.is_cancelled() {
+            return;
+        }
+
+        notified.await;
+    }
+}
+
+/// The timeout and cancellation token that apply to one request.
+///
+/// The deadline is fixed when the guard is created, so it covers every
+/// provider call made for the request, including each event of a stream.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestGuard {
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+}
+
+impl RequestGuard {
+    pub(crate) fn new(timeout: Option<Duration>, token: Option<CancellationToken>) -> Self {
+        Self {
+            timeout,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            token,
+        }
+    }
+
+    /// A guard with the same timeout and token, whose deadline is `remaining` from now.
+    ///
+    /// Used to continue a paused request without counting the pause.
+    pub(crate) fn resumed(&self, remaining: Option<Duration>) -> Self {
+        Self {
+            timeout: self.timeout,
+            deadline: remaining.map(|remaining| Instant::now() + remaining),
+            token: self.token.clone(),
+        }
+    }
+
+    /// The time left until the deadline.
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Run `future`, aborting it if the request is cancelled or its deadline passes.
+    ///
+    /// Aborting drops the future, which closes the underlying HTTP connection.
+    pub(crate) async fn run<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        if self.token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Err(Error::Cancelled);
+        }
+
+        let cancelled = async {
+            match &self.token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+
+        let expired = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = future => result,
+            _ = cancelled => Err(Error::Cancelled),
+            _ = expired => Err(Error::Timeout(self.timeout.unwrap_or_default())),
+        }
+    }
+}
@@ -3,11 +3,13 @@ use crate::{
     provider::Provider,
     response::Response,
     types::CreateResponse,
-    util::output_to_response,
+    util::{output_to_response, parse_structured},
+    stream::StructuredStream,
+    cancel::RequestGuard,
     request::{TextRequestBuilder, StructuredRequestBuilder},
     Options,
 };
-use schemars::JsonSchema;
+use schemars::{JsonSchema, Schema};
 use serde::Deserialize;
 
 pub struct Client<P: Provider> {
@@ -36,6 +38,28 @@ impl<P: Provider> Client<P> {
         StructuredRequestBuilder::new(self, name)
     }
     
+    /// Request structured output using a schema defined at runtime.
+    /// 
+    /// The schema goes through the same strict-mode transforms as schemas derived
+    /// from Rust types, and the output is returned as `serde_json::Value`.
+    pub fn structured_dynamic<S: Into<String>>(
+        &self,
+        name: S,
+        schema: serde_json::Value,
+    ) -> Result<StructuredRequestBuilder<'_, P, serde_json::Value>> {
+        let schema = crate::schema::from_value(schema)?;
+        Ok(self.structured_with_schema(name, schema))
+    }
+    
+    /// Request structured output using a prepared schema, e.g. one loaded with `schema::load`.
+    pub fn structured_with_schema<S: Into<String>>(
+        &self,
+        name: S,
+        schema: Schema,
+    ) -> StructuredRequestBuilder<'_, P, serde_json::Value> {
+        StructuredRequestBuilder::new(self, name.into()).with_schema(schema)
+    }
+    
     pub fn structured_with_name<T>(&self, name: String) -> StructuredRequestBuilder<'_, P, T>
     where
         T: JsonSchema + for<'a> Deserialize<'a>,
@@ -45,7 +69,13 @@ impl<P: Provider> Client<P> {
     
 
     pub async fn text_with_options(&self, options: Options) -> Result<Response<String>> {
-        let create_response = CreateResponse {
+        let guard = RequestGuard::new(options.timeout, options.cancel_token.clone());
+        self.text_with_guard(options, &guard).await
+    }
+    
+    /// Send a text request under an existing guard, so several requests share one deadline.
+    pub(crate) async fn text_with_guard(&self, options: Options, guard: &RequestGuard) -> Result<Response<String>> {
+        let mut create_response = CreateResponse {
             safety_identifier: options.safety_identifier,
             model: options.model,
             tools: options.tools,
@@ -53,9 +83,14 @@ impl<P: Provider> Client<P> {
             input: options.input,
             store: Some(false),
             text: None,
+            stream: None,
         };
         
-        let output = self.provider.create_response(&create_response).await?;
+        if let Some(policy) = &options.context_policy {
+            policy.apply_to_request(&mut create_response);
+        }
+        
+        let output = guard.run(self.provider.create_response(&create_response)).await?;
         let response = output_to_response(output)?;
         Ok(response)
     }
@@ -64,46 +99,69 @@ impl<P: Provider> Client<P> {
     where
         T: JsonSchema + for<'a> Deserialize<'a>,
     {
-        use crate::{
-            schema,
-            types::{Text, TextFormat, TextFormatJsonSchema},
-        };
+        self.structure_with_schema_and_options(name, crate::schema::from::<T>(), options).await
+    }
+    
+    pub async fn structure_with_schema_and_options<T>(&self, name: String, schema: Schema, options: Options) -> Result<Response<T>>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        let validate_schema = options.validate_schema;
+        let guard = RequestGuard::new(options.timeout, options.cancel_token.clone());
+        let create_response = Self::structured_request(name, &schema, options);
+        
+        let output = guard.run(self.provider.create_response(&create_response)).await?;
+        let response = output_to_response(output)?;
+        
+        parse_structured(response, &schema, validate_schema)
+    }
+    
+    /// Stream structured output, yielding partial snapshots before the final response.
+    pub async fn stream_structure_with_schema_and_options<T>(&self, name: String, schema: Schema, options: Options) -> Result<StructuredStream<T>>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        let validate_schema = options.validate_schema;
+        let guard = RequestGuard::new(options.timeout, options.cancel_token.clone());
+        let mut create_response = Self::structured_request(name, &schema, options);
+        create_response.stream = Some(true);
+        
+        let stream = guard.run(self.provider.create_response_stream(&create_response)).await?;
         
-        let create_response = CreateResponse {
+        Ok(StructuredStream::new(stream, schema, validate_schema, guard))
+    }
+    
+    fn structured_request(name: String, schema: &Schema, options: Options) -> CreateResponse {
+        use crate::types::{Text, TextFormat, TextFormatJsonSchema};
+        
+        let mut create_response = CreateResponse {
             safety_identifier: options.safety_identifier,
             model: options.model,
             text: Some(Text {
-                format: TextFormat::JsonSchema(TextFormatJsonSchema {
-                    name,
-                    schema: schema::from::<T>(),
-                    strict: true,
-                    description: None,
-                }),
+                format: if options.json_object {
+                    TextFormat::JsonObject
+                } else {
+                    TextFormat::JsonSchema(TextFormatJsonSchema {
+                        name,
+                        schema: schema.clone(),
+                        strict: options.strict.unwrap_or(true),
+                        description: options.description,
+                    })
+                },
                 verbosity: None,
             }),
             tools: options.tools,
             tool_choice: options.tool_choice,
             input: options.input,
             store: Some(false),
+            stream: None,
         };
         
-        let output = self.provider.create_response(&create_response).await?;
-        
-        let Response { message, function_calls } = output_to_response(output)?;
-        
-        let message = match message {
-            Some(result) => match result {
-                Ok(string) => {
-                    let parsed = serde_json::from_str::<T>(&string)
-                        .map_err(crate::error::Error::Json)?;
-                    Some(Ok(parsed))
-                }
-                Err(refusal) => Some(Err(refusal)),
-            },
-            None => None,
-        };
+        if let Some(policy) = &options.context_policy {
+            policy.apply_to_request(&mut create_response);
+        }
         
-        Ok(Response { message, function_calls })
+        create_response
     }
     
     pub fn provider(&self) -> &P {
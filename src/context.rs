@@ -0,0 +1,587 @@
+//! Fitting conversations into a model's context window.
+//!
+//! A [`ContextPolicy`] drops the oldest turns of a conversation once its estimated
+//! size exceeds the token budget, so long chats keep working instead of failing with
+//! context-length errors. System and developer messages are always kept, and turns
+//! are dropped whole, so a function call is never separated from its output.
+//!
+//! ```
+//! use responses::context::ContextPolicy;
+//! use responses::Messages;
+//!
+//! let conversation = Messages::new()
+//!     .system("You are a helpful assistant")
+//!     .user("First question")
+//!     .assistant("First answer")
+//!     .user("Second question");
+//!
+//! let policy = ContextPolicy::new().max_tokens(20);
+//! let trimmed = conversation.trim(&policy, "gpt-4o");
+//!
+//! assert_eq!(trimmed.len(), 2);
+//! ```
+//!
+//! For sessions too long to drop turns from, [`Messages::compact`](crate::Messages::compact)
+//! asks a model to fold the older turns into a summary message instead.
+
+use crate::{
+    client::Client,
+    error::{Error, Result},
+    messages::Messages,
+    options::Options,
+    provider::Provider,
+    tokens::{Encoding, input_tokens, request_tokens},
+    types::{CreateResponse, FunctionOutput, FunctionOutputContent, Input, InputMessage, Role},
+};
+use std::sync::Arc;
+
+/// Tokens kept free for the response when the budget comes from the model's context window.
+const DEFAULT_RESERVE: usize = 4_096;
+
+/// Tokens kept free for the summary of dropped turns when a summarizer is set.
+const DEFAULT_SUMMARY_TOKENS: usize = 256;
+
+/// Context windows of known models, matched by prefix in order.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1-mini", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+];
+
+/// The context window of `model` in tokens, if known.
+///
+/// Azure deployment names are matched like model names, so deployments named after
+/// their model are recognized. Set [`ContextPolicy::max_tokens`] for others.
+pub fn context_window(model: &str) -> Option<usize> {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+/// Summarizes dropped inputs into the text of a message that replaces them.
+type Summarizer = Arc<dyn Fn(&[Input]) -> Option<String> + Send + Sync>;
+
+/// When and how to trim a conversation to fit the context window.
+///
+/// Set it on a request with `.context_policy()` to trim the input before every send,
+/// leaving room for the request's tools and output schema, or apply it to a history
+/// with [`Messages::trim`](crate::Messages::trim).
+#[derive(Clone, Default)]
+pub struct ContextPolicy {
+    max_tokens: Option<usize>,
+    reserve: Option<usize>,
+    summary_tokens: Option<usize>,
+    summarizer: Option<Summarizer>,
+}
+
+impl ContextPolicy {
+    /// Fit the context window of the request's model, keeping 4096 tokens for the response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a fixed budget for the input instead of the model's context window.
+    pub fn max_tokens(mut self, tokens: usize) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    /// Tokens of the context window kept free for the response.
+    pub fn reserve(mut self, tokens: usize) -> Self {
+        self.reserve = Some(tokens);
+        self
+    }
+
+    /// Replace dropped turns with a developer message summarizing them.
+    ///
+    /// The function receives the dropped inputs and returns the text of the message,
+    /// or `None` to drop them without a trace. It is called once, after the turns to
+    /// drop have been chosen with room left for the summary.
+    pub fn summarize<F>(mut self, summarizer: F) -> Self
+    where
+        F: Fn(&[Input]) -> Option<String> + Send + Sync + 'static,
+    {
+        self.summarizer = Some(Arc::new(summarizer));
+        self
+    }
+
+    /// Tokens of the budget kept free for the summary, 256 by default.
+    ///
+    /// A longer summary is still sent, even if it takes the input past the budget.
+    pub fn summary_tokens(mut self, tokens: usize) -> Self {
+        self.summary_tokens = Some(tokens);
+        self
+    }
+
+    /// The token budget for the input sent to `model`, if known.
+    pub fn budget(&self, model: &str) -> Option<usize> {
+        match self.max_tokens {
+            Some(tokens) => Some(tokens),
+            None => context_window(model).map(|window| window.saturating_sub(self.reserve.unwrap_or(DEFAULT_RESERVE))),
+        }
+    }
+
+    /// Drop the oldest turns of `inputs` until they fit the budget for `model`.
+    ///
+    /// A turn starts at a user message and holds everything up to the next one,
+    /// including function calls and their outputs. The latest turn is always kept,
+    /// even if it alone exceeds the budget.
+    pub fn apply(&self, inputs: Vec<Input>, model: &str) -> Vec<Input> {
+        self.fit(inputs, model, 0)
+    }
+
+    /// Trim the input of `request`, leaving room for its tools and output schema.
+    pub(crate) fn apply_to_request(&self, request: &mut CreateResponse) {
+        let Some(inputs) = request.input.take() else {
+            return;
+        };
+
+        // Without its input, the request counts its tools, output format and reply priming
+        let reserved = request_tokens(request);
+        request.input = Some(self.fit(inputs, request.model.as_deref().unwrap_or_default(), reserved));
+    }
+
+    fn fit(&self, inputs: Vec<Input>, model: &str, reserved: usize) -> Vec<Input> {
+        let Some(budget) = self.budget(model) else {
+            return inputs;
+        };
+        let budget = budget.saturating_sub(reserved);
+
+        let encoding = Encoding::for_model(model);
+        let tokens: Vec<usize> = inputs.iter().map(|input| input_tokens(input, encoding)).collect();
+        let mut total: usize = tokens.iter().sum();
+        if total <= budget {
+            return inputs;
+        }
+
+        let turns = turns(&inputs);
+        let mut dropped = vec![false; inputs.len()];
+
+        // Choose the turns to drop first, so the summarizer runs only once
+        let allowance = match self.summarizer {
+            Some(_) => self.summary_tokens.unwrap_or(DEFAULT_SUMMARY_TOKENS),
+            None => 0,
+        };
+
+        for turn in &turns[..turns.len().saturating_sub(1)] {
+            for &index in turn {
+                dropped[index] = true;
+                total -= tokens[index];
+            }
+
+            if total + allowance <= budget {
+                break;
+            }
+        }
+
+        let mut summary = self.summary(&inputs, &dropped);
+
+        let mut trimmed = Vec::with_capacity(inputs.len());
+
+        for (input, dropped) in inputs.into_iter().zip(dropped) {
+            if !dropped {
+                trimmed.push(input);
+            } else if let Some(summary) = summary.take() {
+                // The summary takes the place of the first dropped input
+                trimmed.push(summary);
+            }
+        }
+
+        trimmed
+    }
+
+    fn summary(&self, inputs: &[Input], dropped: &[bool]) -> Option<Input> {
+        let summarizer = self.summarizer.as_ref()?;
+
+        let dropped_inputs: Vec<Input> = inputs
+            .iter()
+            .zip(dropped)
+            .filter(|(_, dropped)| **dropped)
+            .map(|(input, _)| input.clone())
+            .collect();
+
+        summarizer(&dropped_inputs).map(|content| {
+            Input::Message(InputMessage {
+                role: Role::Developer,
+                content,
+            })
+        })
+    }
+}
+
+impl std::fmt::Debug for ContextPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextPolicy")
+            .field("max_tokens", &self.max_tokens)
+            .field("reserve", &self.reserve)
+            .field("summary_tokens", &self.summary_tokens)
+            .field("summarize", &self.summarizer.is_some())
+            .finish()
+    }
+}
+
+/// Starts the content of summary messages, so later compactions fold them in too.
+const SUMMARY_HEADER: &str = "Summary of the earlier conversation:";
+
+const DEFAULT_SUMMARY_INSTRUCTIONS: &str = "Summarize the conversation below for the assistant who will \
+continue it. Keep facts, decisions, user preferences, open questions and the results of function calls. \
+Merge in any earlier summary. Be concise, and write in the language of the conversation.";
+
+/// How [`Messages::compact`](crate::Messages::compact) summarizes older turns.
+#[derive(Clone, Debug)]
+pub struct CompactionPolicy {
+    model: String,
+    keep_turns: usize,
+    role: Role,
+    instructions: String,
+}
+
+impl CompactionPolicy {
+    /// Summarize with `model`, keeping the last 4 turns verbatim in a developer message.
+    pub fn new<S: Into<String>>(model: S) -> Self {
+        Self {
+            model: model.into(),
+            keep_turns: 4,
+            role: Role::Developer,
+            instructions: DEFAULT_SUMMARY_INSTRUCTIONS.to_string(),
+        }
+    }
+
+    /// The number of latest turns kept verbatim.
+    pub fn keep_turns(mut self, turns: usize) -> Self {
+        self.keep_turns = turns;
+        self
+    }
+
+    /// The role of the summary message, `Role::Developer` or `Role::System`.
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Instructions for the summarizing model, replacing the default ones.
+    pub fn instructions<S: Into<String>>(mut self, instructions: S) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+}
+
+/// The result of compacting a conversation.
+#[derive(Clone, Debug)]
+pub struct Compaction {
+    /// The compacted conversation, with the summary in place of the folded inputs.
+    pub messages: Messages,
+
+    /// The summary, or `None` when there were no turns to fold.
+    pub summary: Option<String>,
+
+    /// Indices of the original inputs folded into the summary, including earlier summaries.
+    pub folded: Vec<usize>,
+}
+
+pub(crate) async fn compact<P: Provider>(
+    messages: &Messages,
+    client: &Client<P>,
+    policy: &CompactionPolicy,
+    options: Options,
+) -> Result<Compaction> {
+    let inputs = messages.inputs();
+    let rendered = messages.try_render_inputs()?;
+
+    let turns = turns(inputs);
+    let fold_turns = turns.len().saturating_sub(policy.keep_turns);
+    if fold_turns == 0 {
+        return Ok(Compaction {
+            messages: messages.clone(),
+            summary: None,
+            folded: Vec::new(),
+        });
+    }
+
+    let earlier_summaries = inputs.iter().enumerate().filter_map(|(index, input)| match input {
+        Input::Message(message) if message.role == policy.role && message.content.starts_with(SUMMARY_HEADER) => {
+            Some(index)
+        }
+        _ => None,
+    });
+
+    let mut folded: Vec<usize> = turns[..fold_turns].iter().flatten().copied().chain(earlier_summaries).collect();
+    folded.sort_unstable();
+
+    let transcript = transcript(folded.iter().map(|&index| &rendered[index]));
+
+    let request = Messages::new().system(&policy.instructions).user(transcript);
+    let options = Options {
+        model: Some(policy.model.clone()),
+        input: Some(request.into_inputs()),
+        ..options
+    };
+    let response = client.text_with_options(options).await?;
+
+    let summary = response
+        .message
+        .ok_or_else(|| Error::InvalidResponse("No summary received".to_string()))?
+        .map_err(|refusal| Error::Refusal(refusal.to_string()))?;
+
+    let summary_input = Input::Message(InputMessage {
+        role: policy.role,
+        content: format!("{}\n\n{}", SUMMARY_HEADER, summary),
+    });
+
+    // The summary takes the place of the first folded input
+    let mut compacted = Vec::with_capacity(inputs.len() - folded.len() + 1);
+    let mut summary_input = Some(summary_input);
+    for (index, input) in inputs.iter().enumerate() {
+        if folded.binary_search(&index).is_err() {
+            compacted.push(input.clone());
+        } else if let Some(summary_input) = summary_input.take() {
+            compacted.push(summary_input);
+        }
+    }
+
+    Ok(Compaction {
+        messages: messages.with_inputs(compacted),
+        summary: Some(summary),
+        folded,
+    })
+}
+
+/// The inputs as a plain-text transcript for the summarizing model.
+fn transcript<'a>(inputs: impl Iterator<Item = &'a Input>) -> String {
+    let mut transcript = String::new();
+
+    for input in inputs {
+        let entry = match input {
+            Input::Message(message) if message.content.starts_with(SUMMARY_HEADER) => {
+                format!("Earlier summary: {}", message.content[SUMMARY_HEADER.len()..].trim())
+            }
+            Input::Message(message) => {
+                let role = match message.role {
+                    Role::System => "System",
+                    Role::User => "User",
+                    Role::Assistant => "Assistant",
+                    Role::Developer => "Developer",
+                };
+                format!("{}: {}", role, message.content)
+            }
+            Input::FunctionCall(call) => format!("Function call: {}({})", call.name, call.arguments),
+            Input::FunctionCallOutput(output) => {
+                let output = match &output.output {
+                    FunctionOutput::Text(text) => text.clone(),
+                    FunctionOutput::Content(items) => items
+                        .iter()
+                        .map(|item| match item {
+                            FunctionOutputContent::InputText { text } => text.as_str(),
+                            FunctionOutputContent::InputImage { .. } => "[Image]",
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                format!("Function output: {}", output)
+            }
+            // Rendered inputs hold no templates
+            Input::Template(_) => continue,
+        };
+
+        transcript.push_str(&entry);
+        transcript.push_str("\n\n");
+    }
+
+    transcript
+}
+
+/// The indices of the droppable inputs, grouped into turns.
+///
+/// System and developer messages are not part of any turn, so they are never dropped.
+fn turns(inputs: &[Input]) -> Vec<Vec<usize>> {
+    let mut turns: Vec<Vec<usize>> = Vec::new();
+
+    for (index, input) in inputs.iter().enumerate() {
+        let role = match input {
+            Input::Message(message) => Some(message.role),
+            Input::Template(template) => Some(template.role),
+            Input::FunctionCall(_) | Input::FunctionCallOutput(_) => None,
+        };
+
+        match role {
+            Some(Role::System | Role::Developer) => continue,
+            Some(Role::User) => turns.push(vec![index]),
+            _ => match turns.last_mut() {
+                Some(turn) => turn.push(index),
+                None => turns.push(vec![index]),
+            },
+        }
+    }
+
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::Messages;
+    use crate::types::{FunctionOutput, OutputFunctionCall};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tokens(inputs: &[Input], indices: &[usize]) -> usize {
+        indices.iter().map(|&i| input_tokens(&inputs[i], Encoding::O200kBase)).sum()
+    }
+
+    fn contents(inputs: &[Input]) -> Vec<String> {
+        inputs
+            .iter()
+            .map(|input| match input {
+                Input::Message(message) => message.content.clone(),
+                Input::FunctionCall(call) => format!("call {}", call.name),
+                Input::FunctionCallOutput(output) => format!("output {}", output.call_id),
+                Input::Template(_) => "template".to_string(),
+            })
+            .collect()
+    }
+
+    fn call(id: &str) -> OutputFunctionCall {
+        OutputFunctionCall {
+            call_id: id.to_string(),
+            name: "search".to_string(),
+            arguments: "{}".to_string(),
+        }
+    }
+
+    fn conversation() -> Messages {
+        Messages::new()
+            .system("Be brief")
+            .user("Look something up")
+            .function_call(call("call_1"))
+            .function_output("call_1", FunctionOutput::text("a result"))
+            .assistant("Found it")
+            .developer("Answer in French")
+            .user("And another thing")
+            .assistant("Done")
+            .user("Last question")
+    }
+
+    #[test]
+    fn test_context_windows() {
+        assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("gpt-4"), Some(8_192));
+        assert_eq!(context_window("gpt-4.1-nano"), Some(1_047_576));
+        assert_eq!(context_window("my-deployment"), None);
+
+        assert_eq!(ContextPolicy::new().budget("gpt-4"), Some(4_096));
+        assert_eq!(ContextPolicy::new().reserve(1_000).budget("gpt-4"), Some(7_192));
+        assert_eq!(ContextPolicy::new().max_tokens(500).budget("my-deployment"), Some(500));
+        assert_eq!(ContextPolicy::new().budget("my-deployment"), None);
+    }
+
+    #[test]
+    fn test_fitting_conversations_are_unchanged() {
+        let inputs = conversation().into_inputs();
+        let trimmed = ContextPolicy::new().apply(inputs.clone(), "gpt-4o");
+        assert_eq!(contents(&trimmed), contents(&inputs));
+
+        let trimmed = ContextPolicy::new().apply(inputs.clone(), "my-deployment");
+        assert_eq!(trimmed.len(), inputs.len());
+    }
+
+    #[test]
+    fn test_drops_whole_turns_and_keeps_instructions() {
+        let inputs = conversation().into_inputs();
+
+        // Room for the instructions and the last two turns only
+        let budget = tokens(&inputs, &[0, 5, 6, 7, 8]);
+        let trimmed = ContextPolicy::new().max_tokens(budget).apply(inputs.clone(), "gpt-4o");
+        assert_eq!(
+            contents(&trimmed),
+            vec!["Be brief", "Answer in French", "And another thing", "Done", "Last question"]
+        );
+
+        // Without room for the middle turn, the first goes with its function call and output
+        let budget = tokens(&inputs, &[0, 5, 8]);
+        let trimmed = ContextPolicy::new().max_tokens(budget).apply(inputs.clone(), "gpt-4o");
+        assert_eq!(contents(&trimmed), vec!["Be brief", "Answer in French", "Last question"]);
+
+        // The latest turn is kept even when it does not fit
+        let trimmed = ContextPolicy::new().max_tokens(1).apply(inputs, "gpt-4o");
+        assert_eq!(contents(&trimmed), vec!["Be brief", "Answer in French", "Last question"]);
+    }
+
+    #[test]
+    fn test_summary_replaces_dropped_turns() {
+        let inputs = conversation().into_inputs();
+
+        let summary = Input::Message(InputMessage {
+            role: Role::Developer,
+            content: "6 earlier messages omitted".to_string(),
+        });
+        let summary_tokens = input_tokens(&summary, Encoding::O200kBase);
+        let budget = tokens(&inputs, &[0, 5, 8]) + summary_tokens;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let policy = ContextPolicy::new()
+            .max_tokens(budget)
+            .summary_tokens(summary_tokens)
+            .summarize(move |dropped| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Some(format!("{} earlier messages omitted", dropped.len()))
+            });
+        let trimmed = policy.apply(inputs.clone(), "gpt-4o");
+
+        assert_eq!(
+            contents(&trimmed),
+            vec!["Be brief", "6 earlier messages omitted", "Answer in French", "Last question"]
+        );
+        assert!(matches!(&trimmed[1], Input::Message(message) if message.role == Role::Developer));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // With no allowance, the cut is made as if there were no summary
+        let budget = tokens(&inputs, &[0, 5, 6, 7, 8]);
+        let trimmed = policy.max_tokens(budget).summary_tokens(0).apply(inputs, "gpt-4o");
+        assert_eq!(contents(&trimmed)[..3], ["Be brief", "4 earlier messages omitted", "Answer in French"]);
+        assert_eq!(trimmed.len(), 6);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_tools_count_against_the_budget() {
+        use crate::types::{Tool, ToolFunction};
+
+        let inputs = conversation().into_inputs();
+        let tool = Tool::Function(ToolFunction {
+            name: "search".to_string(),
+            description: Some("Search the knowledge base for documents about a topic".to_string()),
+            parameters: crate::schema::from_value(serde_json::json!({
+                "type": "object",
+                "properties": {"query": {"type": "string"}, "limit": {"type": "integer"}},
+                "required": ["query", "limit"]
+            }))
+            .unwrap(),
+            strict: None,
+        });
+        let request = CreateResponse {
+            model: Some("gpt-4o".to_string()),
+            tools: Some(vec![tool]),
+            ..Default::default()
+        };
+        let reserved = request_tokens(&request);
+        let last_two_turns = tokens(&inputs, &[0, 5, 6, 7, 8]);
+
+        let mut fitting = CreateResponse { input: Some(inputs.clone()), ..request.clone() };
+        ContextPolicy::new().max_tokens(last_two_turns + reserved).apply_to_request(&mut fitting);
+        assert_eq!(fitting.input.unwrap().len(), 5);
+
+        // The same budget without room for the tool keeps one turn less
+        let mut crowded = CreateResponse { input: Some(inputs), ..request };
+        ContextPolicy::new().max_tokens(last_two_turns).apply_to_request(&mut crowded);
+        assert_eq!(contents(&crowded.input.unwrap()), vec!["Be brief", "Answer in French", "Last question"]);
+    }
+}
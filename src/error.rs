@@ -31,6 +31,22 @@ pub enum Error {
         #[source] source: std::io::Error 
     },
     
+    #[error("Failed to render the template of message {index} ({template:?}): {source}")]
+    MessageRendering { 
+        index: usize, 
+        template: String, 
+        #[source] source: Box<Error> 
+    },
+    
+    #[error("Message index {index} is out of range for a conversation of {len} inputs")]
+    MessageIndexOutOfRange { index: usize, len: usize },
+    
+    #[error("No node {id} in the conversation tree\nHelp: Use the node ids returned by the same ConversationTree.")]
+    UnknownNode { id: usize },
+    
+    #[error("Invalid conversation tree: {0}\nHelp: Stored trees must come from serializing a ConversationTree; node ids can't be edited by hand.")]
+    InvalidConversationTree(String),
+    
     #[error("Template variable not found: {name}\nHelp: Define this variable using .var(\"{name}\", value) or ensure it's included in your template variables.")]
     TemplateVariableNotFound { name: String },
     
@@ -66,8 +82,67 @@ pub enum Error {
     #[error("Function '{function_name}' expected arguments but received none\nHelp: This function requires parameters. Check the function definition and provide the required arguments.")]
     FunctionMissingArguments { function_name: String },
     
+    #[error("No tool registered for function '{name}'\nHelp: Add the handler to the toolbox with .register() or .register_with_context().")]
+    FunctionNotFound { name: String },
+    
+    #[error("MCP server returned error {code}: {message}")]
+    McpServer { code: i64, message: String },
+    
+    #[error("MCP connection failed: {0}\nHelp: Check the server command or URL, and that the server speaks MCP over this transport.")]
+    McpConnection(String),
+    
+    #[error("Tool loop stopped after {turns} requests without a final answer\nHelp: Raise the limit with Toolbox::max_turns() or check whether the tool outputs give the model what it needs.")]
+    ToolLoopLimit { turns: usize },
+    
     #[error("Function call validation failed: {reason}\nHelp: Review the function parameters and ensure they meet the validation requirements.")]
     FunctionValidation { reason: String },
+    
+    #[error("Structured output failed schema validation:\n{}", 
+        violations.iter().map(|v| format!("  {}", v)).collect::<Vec<_>>().join("\n")
+    )]
+    SchemaValidation { violations: Vec<crate::validation::SchemaViolation> },
+    
+    #[error("Invalid JSON schema: {0}\nHelp: Provide an object schema such as {{\"type\": \"object\", \"properties\": {{...}}}}.")]
+    InvalidSchema(String),
+    
+    #[error("Request timed out after {0:?}\nHelp: Increase the timeout set with .timeout() or reduce the size of the request.")]
+    Timeout(std::time::Duration),
+    
+    #[error("Request was cancelled")]
+    Cancelled,
+    
+    #[error("Failed to read schema file: {path} - {source}")]
+    SchemaFileRead { 
+        path: String, 
+        #[source] source: std::io::Error 
+    },
+
+    #[error("Failed to access conversation file: {path} - {source}")]
+    ConversationFile { 
+        path: String, 
+        #[source] source: std::io::Error 
+    },
+
+    #[error("Invalid session id: {id:?}\nHelp: Session ids may only contain letters, digits, '-', '_' and '.', and must not start with '.'.")]
+    InvalidSessionId { id: String },
+
+    #[error("Conversation store failed: {0}")]
+    ConversationStore(String),
+
+    #[error("Invalid transcript: {0}\nHelp: Only text content and function calls convert between Messages and the Chat Completions, ShareGPT and fine-tuning formats, and Markdown transcripts hold messages only.")]
+    InvalidTranscript(String),
+    
+    #[error("Invalid conversation entry on line {line}: {source}\nHelp: Each line of a JSONL conversation must be one input, such as {{\"type\": \"message\", \"role\": \"user\", \"content\": \"...\"}}.")]
+    ConversationLine { 
+        line: usize, 
+        #[source] source: serde_json::Error 
+    },
+    
+    #[error("Invalid redaction pattern '{pattern}': {source}\nHelp: Custom redaction entities use the regex crate's syntax, which has no look-around or backreferences.")]
+    InvalidRedactionPattern { 
+        pattern: String, 
+        #[source] source: regex::Error 
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
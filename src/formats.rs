@@ -0,0 +1,559 @@
+//! Converting conversations to and from common transcript formats.
+//!
+//! - Chat Completions: `[{"role": "user", "content": "..."}]`, with assistant
+//!   `tool_calls` and `tool` messages for function calls.
+//! - ShareGPT: `{"conversations": [{"from": "human", "value": "..."}]}`, with
+//!   `function_call` and `observation` turns for function calls.
+//! - OpenAI fine-tuning JSONL: one `{"messages": [...], "tools": [...]}` row per line.
+//! - Markdown with `## User` style headers, as read by
+//!   [`ConversationTemplate`](crate::prompt::ConversationTemplate). Messages only.
+//!
+//! Only text content and function calls convert. Images and other content parts
+//! are reported as errors rather than dropped.
+//!
+//! ```
+//! use responses::Messages;
+//! use serde_json::json;
+//!
+//! let archived = json!([
+//!     {"role": "system", "content": "You are a helpful assistant"},
+//!     {"role": "user", "content": "What's the weather in Paris?"},
+//!     {"role": "assistant", "content": null, "tool_calls": [{
+//!         "id": "call_1",
+//!         "type": "function",
+//!         "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+//!     }]},
+//!     {"role": "tool", "tool_call_id": "call_1", "content": "Sunny, 22°C"}
+//! ]);
+//!
+//! let conversation = Messages::from_chat_completions(&archived).unwrap();
+//! assert_eq!(conversation.len(), 4);
+//! assert_eq!(conversation.to_chat_completions().unwrap(), archived);
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    messages::Messages,
+    response::Response,
+    types::{
+        FunctionOutput, FunctionOutputContent, Input, InputFunctionCallOutput, InputMessage,
+        OutputFunctionCall, Role, Tool, ToolFunction,
+    },
+};
+use schemars::Schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::VecDeque;
+
+/// One row of a fine-tuning dataset: a conversation and the tools it may call.
+#[derive(Clone, Debug)]
+pub struct FineTuningExample {
+    pub messages: Messages,
+    pub tools: Vec<Tool>,
+}
+
+impl FineTuningExample {
+    pub fn new(messages: Messages) -> Self {
+        Self {
+            messages,
+            tools: Vec::new(),
+        }
+    }
+
+    /// The tools offered to the model in this example.
+    pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// The example as a single JSONL row, without the trailing newline.
+    pub fn to_row(&self) -> Result<String> {
+        let messages = to_chat_completions(&self.messages.try_render_inputs()?)?;
+        Ok(serde_json::to_string(&row(messages, &self.tools))?)
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<Value>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ChatToolCall>>,
+    #[serde(default)]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatToolCall {
+    id: String,
+    function: ChatFunction,
+}
+
+#[derive(Deserialize)]
+struct ChatFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct ShareGptTurn {
+    from: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct FineTuningRow {
+    messages: Vec<Value>,
+    #[serde(default)]
+    tools: Option<Vec<ChatTool>>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ChatTool {
+    function: ChatToolFunction,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ChatToolFunction {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default = "empty_parameters")]
+    parameters: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strict: Option<bool>,
+}
+
+fn empty_parameters() -> Value {
+    json!({"type": "object", "properties": {}})
+}
+
+/// Inputs from Chat Completions messages, given as an array or as an object with a `messages` array.
+pub fn from_chat_completions(messages: &Value) -> Result<Vec<Input>> {
+    let messages = match messages {
+        Value::Object(object) => object.get("messages").unwrap_or(&Value::Null),
+        messages => messages,
+    };
+    let Value::Array(messages) = messages else {
+        return Err(invalid("expected an array of messages"));
+    };
+
+    let mut inputs = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        let message: ChatMessage = serde_json::from_value(message.clone())
+            .map_err(|e| invalid(format!("message {}: {}", index, e)))?;
+        let content = chat_content(message.content.as_ref()).map_err(|e| invalid(format!("message {}: {}", index, e)))?;
+
+        let role = match message.role.as_str() {
+            "system" => Role::System,
+            "developer" => Role::Developer,
+            "user" => Role::User,
+            "assistant" => Role::Assistant,
+            "tool" => {
+                let call_id = message
+                    .tool_call_id
+                    .ok_or_else(|| invalid(format!("message {}: tool message without tool_call_id", index)))?;
+                inputs.push(function_output(call_id, content.unwrap_or_default()));
+                continue;
+            }
+            role => return Err(invalid(format!("message {}: unsupported role {:?}", index, role))),
+        };
+
+        if let Some(content) = content {
+            inputs.push(Input::Message(InputMessage { role, content }));
+        }
+        for call in message.tool_calls.into_iter().flatten() {
+            inputs.push(Input::FunctionCall(OutputFunctionCall {
+                call_id: call.id,
+                name: call.function.name,
+                arguments: arguments(call.function.arguments),
+            }));
+        }
+    }
+
+    Ok(inputs)
+}
+
+/// Chat Completions messages from rendered inputs.
+///
+/// Consecutive function calls become the `tool_calls` of one assistant message.
+pub fn to_chat_completions(inputs: &[Input]) -> Result<Value> {
+    let mut messages: Vec<Value> = Vec::new();
+
+    for (index, input) in inputs.iter().enumerate() {
+        match input {
+            Input::Message(message) => {
+                messages.push(json!({"role": role_name(message.role), "content": message.content}));
+            }
+            Input::FunctionCall(call) => {
+                let tool_call = json!({
+                    "id": call.call_id,
+                    "type": "function",
+                    "function": {"name": call.name, "arguments": call.arguments}
+                });
+
+                // Calls join the assistant message right before them
+                match messages.last_mut() {
+                    Some(Value::Object(last)) if last["role"] == "assistant" => {
+                        match last.get_mut("tool_calls") {
+                            Some(Value::Array(calls)) => calls.push(tool_call),
+                            _ => {
+                                last.insert("tool_calls".to_string(), json!([tool_call]));
+                            }
+                        }
+                    }
+                    _ => messages.push(json!({"role": "assistant", "content": null, "tool_calls": [tool_call]})),
+                }
+            }
+            Input::FunctionCallOutput(output) => {
+                let content = output_text(&output.output).map_err(|e| invalid(format!("input {}: {}", index, e)))?;
+                messages.push(json!({"role": "tool", "tool_call_id": output.call_id, "content": content}));
+            }
+            Input::Template(_) => return Err(invalid(format!("input {} is a template; render it first", index))),
+        }
+    }
+
+    Ok(Value::Array(messages))
+}
+
+/// Inputs from a ShareGPT conversation, given as an object with a `conversations`
+/// array or as the array itself.
+///
+/// ShareGPT has no call ids, so calls get ids `call_1`, `call_2`, ... and each
+/// observation answers the oldest unanswered call.
+pub fn from_sharegpt(conversation: &Value) -> Result<Vec<Input>> {
+    let turns = match conversation {
+        Value::Object(object) => object.get("conversations").unwrap_or(&Value::Null),
+        turns => turns,
+    };
+    let turns: Vec<ShareGptTurn> = serde_json::from_value(turns.clone())
+        .map_err(|e| invalid(format!("expected an array of {{\"from\", \"value\"}} turns: {}", e)))?;
+
+    let mut inputs = Vec::new();
+    let mut pending_calls = VecDeque::new();
+    let mut call_count = 0;
+
+    for (index, turn) in turns.into_iter().enumerate() {
+        let role = match turn.from.as_str() {
+            "system" => Role::System,
+            "human" | "user" => Role::User,
+            "gpt" | "assistant" => Role::Assistant,
+            "function_call" => {
+                let call: ChatFunction = serde_json::from_str(&turn.value)
+                    .map_err(|e| invalid(format!("turn {}: invalid function call: {}", index, e)))?;
+                call_count += 1;
+                let call_id = format!("call_{}", call_count);
+                pending_calls.push_back(call_id.clone());
+                inputs.push(Input::FunctionCall(OutputFunctionCall {
+                    call_id,
+                    name: call.name,
+                    arguments: arguments(call.arguments),
+                }));
+                continue;
+            }
+            "observation" | "tool" | "function_response" => {
+                let call_id = pending_calls
+                    .pop_front()
+                    .ok_or_else(|| invalid(format!("turn {}: observation without a function call", index)))?;
+                inputs.push(function_output(call_id, turn.value));
+                continue;
+            }
+            from => return Err(invalid(format!("turn {}: unsupported speaker {:?}", index, from))),
+        };
+
+        inputs.push(Input::Message(InputMessage { role, content: turn.value }));
+    }
+
+    Ok(inputs)
+}
+
+/// A ShareGPT conversation from rendered inputs. Developer messages become system turns.
+pub fn to_sharegpt(inputs: &[Input]) -> Result<Value> {
+    let mut turns = Vec::new();
+
+    for (index, input) in inputs.iter().enumerate() {
+        let (from, value) = match input {
+            Input::Message(message) => {
+                let from = match message.role {
+                    Role::System | Role::Developer => "system",
+                    Role::User => "human",
+                    Role::Assistant => "gpt",
+                };
+                (from, message.content.clone())
+            }
+            Input::FunctionCall(call) => {
+                // Arguments are written as an object, as ShareGPT tool datasets expect
+                let arguments = serde_json::from_str(&call.arguments).unwrap_or_else(|_| Value::String(call.arguments.clone()));
+                ("function_call", json!({"name": call.name, "arguments": arguments}).to_string())
+            }
+            Input::FunctionCallOutput(output) => {
+                let text = output_text(&output.output).map_err(|e| invalid(format!("input {}: {}", index, e)))?;
+                ("observation", text)
+            }
+            Input::Template(_) => return Err(invalid(format!("input {} is a template; render it first", index))),
+        };
+        turns.push(json!({"from": from, "value": value}));
+    }
+
+    Ok(json!({"conversations": turns}))
+}
+
+/// A Markdown transcript that loads back with [`ConversationTemplate`](crate::prompt::ConversationTemplate).
+///
+/// Each message starts with a `## System`, `## User`, `## Assistant` or `## Developer`
+/// header. The `transcript: true` frontmatter makes the file literal text, so `{{` in
+/// a message is not rendered, and messages load back exactly, including whitespace
+/// and empty messages. Content lines that would read as a header are escaped as `\## ...`.
+pub fn to_markdown(inputs: &[Input]) -> Result<String> {
+    let mut markdown = format!("{}\n", crate::prompt::conversation::TRANSCRIPT_FRONTMATTER);
+
+    for (index, input) in inputs.iter().enumerate() {
+        let message = match input {
+            Input::Message(message) => message,
+            Input::FunctionCall(_) | Input::FunctionCallOutput(_) => {
+                return Err(invalid(format!("input {} is a function call; Markdown transcripts hold messages only", index)));
+            }
+            Input::Template(_) => return Err(invalid(format!("input {} is a template; render it first", index))),
+        };
+        markdown.push_str(&crate::prompt::conversation::transcript_section(message.role, &message.content));
+    }
+
+    Ok(markdown)
+}
+
+/// The examples of a fine-tuning JSONL dataset. Blank lines are skipped.
+pub fn from_fine_tuning_jsonl(jsonl: &str) -> Result<Vec<FineTuningExample>> {
+    let mut examples = Vec::new();
+
+    for (index, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        let row: FineTuningRow = serde_json::from_str(line)
+            .map_err(|e| invalid(format!("line {} is not a fine-tuning example: {}", line_number, e)))?;
+
+        let inputs = from_chat_completions(&Value::Array(row.messages))
+            .map_err(|e| invalid(format!("line {}: {}", line_number, e)))?;
+        let tools = row
+            .tools
+            .into_iter()
+            .flatten()
+            .map(|tool| {
+                Ok(Tool::Function(ToolFunction {
+                    name: tool.function.name,
+                    description: tool.function.description,
+                    // Kept as given: the strict transform would rewrite the dataset
+                    parameters: Schema::try_from(tool.function.parameters)
+                        .map_err(|e| Error::InvalidSchema(e.to_string()))?,
+                    strict: tool.function.strict,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| invalid(format!("line {}: {}", line_number, e)))?;
+
+        examples.push(FineTuningExample {
+            messages: Messages::from_inputs(inputs),
+            tools,
+        });
+    }
+
+    Ok(examples)
+}
+
+/// A fine-tuning JSONL dataset, one example per line.
+pub fn to_fine_tuning_jsonl(examples: &[FineTuningExample]) -> Result<String> {
+    let mut jsonl = String::new();
+    for example in examples {
+        jsonl.push_str(&example.to_row()?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// A fine-tuning row with `conversation` as the prompt and `response` as the assistant's answer.
+pub(crate) fn fine_tuning_row<T: Serialize>(
+    conversation: &Messages,
+    response: &Response<T>,
+    tools: &[Tool],
+) -> Result<String> {
+    let Value::Array(mut messages) = to_chat_completions(&conversation.try_render_inputs()?)? else {
+        unreachable!("Chat Completions messages are an array")
+    };
+
+    let mut answer = Map::new();
+    answer.insert("role".to_string(), json!("assistant"));
+    match &response.message {
+        Some(Ok(message)) => {
+            // Text answers are written as is, and structured ones as their JSON
+            let content = match serde_json::to_value(message)? {
+                Value::String(text) => text,
+                value => value.to_string(),
+            };
+            answer.insert("content".to_string(), json!(content));
+        }
+        Some(Err(refusal)) => {
+            answer.insert("content".to_string(), Value::Null);
+            answer.insert("refusal".to_string(), json!(refusal.to_string()));
+        }
+        None => {
+            answer.insert("content".to_string(), Value::Null);
+        }
+    }
+    if !response.function_calls.is_empty() {
+        let calls: Vec<Value> = response
+            .function_calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "id": call.call_id,
+                    "type": "function",
+                    "function": {"name": call.name, "arguments": call.arguments}
+                })
+            })
+            .collect();
+        answer.insert("tool_calls".to_string(), Value::Array(calls));
+    }
+    messages.push(Value::Object(answer));
+
+    Ok(serde_json::to_string(&row(Value::Array(messages), tools))?)
+}
+
+fn row(messages: Value, tools: &[Tool]) -> Value {
+    let mut row = json!({"messages": messages});
+    if !tools.is_empty() {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|Tool::Function(function)| {
+                json!({
+                    "type": "function",
+                    "function": ChatToolFunction {
+                        name: function.name.clone(),
+                        description: function.description.clone(),
+                        parameters: function.parameters.as_value().clone(),
+                        strict: function.strict,
+                    }
+                })
+            })
+            .collect();
+        row["tools"] = Value::Array(tools);
+    }
+    row
+}
+
+/// The text of Chat Completions content: a string, null, or an array of text parts.
+fn chat_content(content: Option<&Value>) -> std::result::Result<Option<String>, String> {
+    match content {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text.clone())),
+        Some(Value::Array(parts)) => {
+            let texts = parts
+                .iter()
+                .map(|part| match (part["type"].as_str(), part["text"].as_str()) {
+                    (Some("text"), Some(text)) => Ok(text),
+                    (kind, _) => Err(format!("unsupported content part {:?}", kind.unwrap_or("unknown"))),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(Some(texts.join("\n")))
+        }
+        Some(other) => Err(format!("unsupported content {}", other)),
+    }
+}
+
+fn output_text(output: &FunctionOutput) -> std::result::Result<String, String> {
+    match output {
+        FunctionOutput::Text(text) => Ok(text.clone()),
+        FunctionOutput::Content(items) => items
+            .iter()
+            .map(|item| match item {
+                FunctionOutputContent::InputText { text } => Ok(text.as_str()),
+                FunctionOutputContent::InputImage { .. } => Err("image outputs cannot be converted".to_string()),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map(|texts| texts.join("\n")),
+    }
+}
+
+/// Arguments as a JSON string; some archives store them as objects.
+fn arguments(arguments: Value) -> String {
+    match arguments {
+        Value::String(arguments) => arguments,
+        Value::Null => "{}".to_string(),
+        arguments => arguments.to_string(),
+    }
+}
+
+fn function_output(call_id: String, text: String) -> Input {
+    Input::FunctionCallOutput(InputFunctionCallOutput {
+        call_id,
+        output: FunctionOutput::Text(text),
+    })
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::Developer => "developer",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidTranscript(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_parts_and_object_arguments() {
+        let inputs = from_chat_completions(&json!({"messages": [
+            {"role": "user", "content": [{"type": "text", "text": "Hello"}, {"type": "text", "text": "there"}]},
+            {"role": "assistant", "tool_calls": [{"id": "a", "function": {"name": "f", "arguments": {"x": 1}}}]}
+        ]}))
+        .unwrap();
+
+        assert!(matches!(&inputs[0], Input::Message(m) if m.content == "Hello\nthere"));
+        assert!(matches!(&inputs[1], Input::FunctionCall(call) if call.arguments == r#"{"x":1}"#));
+
+        let image = json!([{"role": "user", "content": [{"type": "image_url", "image_url": {"url": "x"}}]}]);
+        let error = from_chat_completions(&image).unwrap_err().to_string();
+        assert!(error.contains("message 0: unsupported content part \"image_url\""));
+
+        let unknown = json!([{"role": "narrator", "content": "Once upon a time"}]);
+        assert!(matches!(from_chat_completions(&unknown), Err(Error::InvalidTranscript(_))));
+    }
+
+    #[test]
+    fn test_sharegpt_pairs_observations_with_calls() {
+        let inputs = from_sharegpt(&json!({"conversations": [
+            {"from": "human", "value": "Weather in Paris and Rome?"},
+            {"from": "function_call", "value": "{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Paris\"}}"},
+            {"from": "function_call", "value": "{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Rome\"}}"},
+            {"from": "observation", "value": "Sunny"},
+            {"from": "observation", "value": "Rainy"},
+            {"from": "gpt", "value": "Paris is sunny, Rome is rainy."}
+        ]}))
+        .unwrap();
+
+        let call_ids: Vec<_> = inputs
+            .iter()
+            .filter_map(|input| match input {
+                Input::FunctionCallOutput(output) => Some(output.call_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(call_ids, vec!["call_1", "call_2"]);
+
+        let exported = to_sharegpt(&inputs).unwrap();
+        assert_eq!(exported["conversations"][1]["from"], "function_call");
+        assert_eq!(from_sharegpt(&exported).unwrap().len(), inputs.len());
+
+        let orphan = json!([{"from": "observation", "value": "Sunny"}]);
+        assert!(from_sharegpt(&orphan).is_err());
+    }
+}
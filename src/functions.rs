@@ -1,8 +1,10 @@
 use crate::{
     error::{Error, Result},
-    types::{OutputFunctionCall, Tool},
+    types::{FunctionOutput, OutputFunctionCall, Tool},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
 
 /// A function call with automatically deserialized parameters
 #[derive(Clone, Debug)]
@@ -21,6 +23,12 @@ pub trait FunctionHandler: Send + Sync {
     /// Get the tool definition for this function
     fn tool(&self) -> Tool;
     
+    /// Whether calls must be approved before they run, set with `#[tool(requires_approval)]`.
+    /// The tool loop pauses with [`ToolLoop::PendingApproval`](crate::tool_loop::ToolLoop::PendingApproval) for such calls.
+    fn requires_approval(&self) -> bool {
+        false
+    }
+    
     /// Parse raw function call arguments into typed parameters
     fn parse_call(&self, call: &OutputFunctionCall) -> Result<FunctionCall<Self::Parameters>> {
         if call.name != self.name() {
@@ -40,14 +48,85 @@ pub trait FunctionHandler: Send + Sync {
         })
     }
 }
+/// Calls a function without a context parameter, implemented by `#[tool]`.
+///
+/// Handlers implementing this trait (with a serializable output) can be added to a
+/// [`Toolbox`](crate::toolbox::Toolbox).
+pub trait CallHandler: FunctionHandler {
+    type Output;
+    
+    /// Parse the arguments and call the function, without checking the name.
+    fn call(&self, call: &OutputFunctionCall) -> impl Future<Output = Result<Self::Output>> + Send;
+}
 
+/// Calls a function with a context parameter, implemented by `#[tool]`.
+///
+/// Added to a [`Toolbox`](crate::toolbox::Toolbox) together with its context.
+pub trait ContextCallHandler: FunctionHandler {
+    type Context: ?Sized;
+    
+    type Output;
+    
+    /// Parse the arguments and call the function with the context, without checking the name.
+    fn call_with_context(
+        &self,
+        call: &OutputFunctionCall,
+        context: &Self::Context,
+    ) -> impl Future<Output = Result<Self::Output>> + Send;
+}
 
+/// Converts the value returned by a tool into the output sent back to the model.
+///
+/// Implemented for every `Serialize` type: strings are sent as they are, other
+/// values as JSON (so `()` and `None` become `null`). Return a [`FunctionOutput`]
+/// to send images or other content.
+pub trait ToolOutput {
+    fn into_output(self) -> Result<FunctionOutput>;
+}
 
+impl<T: Serialize> ToolOutput for T {
+    fn into_output(self) -> Result<FunctionOutput> {
+        match serde_json::to_value(self)? {
+            Value::String(text) => Ok(FunctionOutput::Text(text)),
+            value => Ok(FunctionOutput::Text(value.to_string())),
+        }
+    }
+}
 
+impl ToolOutput for FunctionOutput {
+    fn into_output(self) -> Result<FunctionOutput> {
+        Ok(self)
+    }
+}
 
-
-
-
+/// Checks of tool arguments that the schema cannot express, run by `#[tool(validate)]`.
+///
+/// Implement it for the generated parameter struct (`{Function}Params`). It runs after the
+/// arguments are parsed and before the function is called. An `Err` becomes
+/// `Error::FunctionValidation`, which the toolbox can report back to the model.
+///
+/// ```no_run
+/// use responses::{tool, Result};
+/// use responses::functions::Validate;
+///
+/// #[tool(validate)]
+/// /// Book a room for a date range
+/// async fn book(check_in: String, check_out: String) -> Result<String> {
+///     Ok(format!("Booked from {} to {}", check_in, check_out))
+/// }
+///
+/// impl Validate for BookParams {
+///     fn validate(&self) -> std::result::Result<(), String> {
+///         if self.check_out <= self.check_in {
+///             return Err("check_out must be after check_in".to_string());
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Validate {
+    fn validate(&self) -> std::result::Result<(), String>;
+}
 
 /// Helper macro for simplifying function handler dispatch
 /// 
@@ -152,11 +231,12 @@ macro_rules! try_function_handlers {
 /// - ✅ **No manual name matching required** - handlers return `None` if name doesn't match
 /// - ✅ Context parameter support
 /// - ✅ Custom tool naming with `#[tool("custom_name")]`
+/// - ✅ Strict mode with `#[tool(strict)]` (the default with the `strict-tools` feature)
 /// - ✅ Clone-able handlers for concurrent execution
 /// 
 /// # Example Usage
 /// ```no_run
-/// use responses::{tool, functions::FunctionHandler, Result};
+/// use responses::{tool, Result};
 /// 
 /// #[tool]
 /// /// Get weather information for a city
@@ -9,7 +9,7 @@ pub mod assertions;
 use crate::client::Client;
 use crate::prompt::{PromptTemplate, TemplateSet};
 use crate::messages::Messages;
-use crate::types::{Role, Input};
+use crate::types::{FunctionOutput, FunctionOutputContent, Input, Role};
 use crate::response::Response;
 use crate::provider::Provider;
 use crate::error::{Error, Result};
@@ -214,13 +214,13 @@ impl<P: Provider> Judge<P> {
         expected_behavior: &str
     ) -> Result<Messages> {
         let formatted_response = self.format_response(actual_response);
-        let formatted_conversation = self.format_conversation(conversation_history);
+        let formatted_conversation = self.format_conversation(conversation_history)?;
         
         match self.prompt.as_ref().unwrap() { // Safe because we checked in evaluate()
             JudgePrompt::String(system_prompt) => {
                 Ok(Messages::new()
                     .system(system_prompt)
-                    .user(&format!(
+                    .user(format!(
                         "## Conversation History:\n{}\n\n## Actual Response:\n{}\n\n## Expected Behavior:\n{}\n\nEvaluate whether the actual response matches the expected behavior.",
                         formatted_conversation,
                         formatted_response,
@@ -279,11 +279,11 @@ impl<P: Provider> Judge<P> {
         formatted
     }
     
-    fn format_conversation(&self, messages: &Messages) -> String {
+    fn format_conversation(&self, messages: &Messages) -> Result<String> {
         let mut formatted = String::new();
         
-        // Use render_inputs() to get resolved templates
-        let rendered_inputs = messages.render_inputs();
+        // Fail rather than judge a conversation with a turn missing
+        let rendered_inputs = messages.try_render_inputs()?;
         for input in &rendered_inputs {
             match input {
                 Input::Message(msg) => {
@@ -292,16 +292,28 @@ impl<P: Provider> Judge<P> {
                         msg.content
                     ));
                 }
-                Input::Template(template_input) => {
-                    // Templates should be resolved by render_inputs(), but handle gracefully
-                    formatted.push_str(&format!("**{}:** [Template not resolved]\n\n", 
-                        self.format_role(&template_input.role)
-                    ));
+                Input::FunctionCall(call) => {
+                    formatted.push_str(&format!("**Function call:** {}({})\n\n", call.name, call.arguments));
+                }
+                Input::FunctionCallOutput(output) => {
+                    let output = match &output.output {
+                        FunctionOutput::Text(text) => text.clone(),
+                        FunctionOutput::Content(content) => content
+                            .iter()
+                            .map(|item| match item {
+                                FunctionOutputContent::InputText { text } => text.clone(),
+                                FunctionOutputContent::InputImage { .. } => "[Image]".to_string(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    };
+                    formatted.push_str(&format!("**Function output:** {}\n\n", output));
                 }
+                Input::Template(_) => unreachable!("templates are rendered by try_render_inputs()"),
             }
         }
         
-        formatted
+        Ok(formatted)
     }
     
     fn format_role(&self, role: &Role) -> String {
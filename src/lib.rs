@@ -12,6 +12,12 @@ pub mod refusal;
 
 pub mod schema;
 
+pub mod validation;
+
+pub mod stream;
+
+pub mod cancel;
+
 pub mod error;
 
 pub mod provider;
@@ -27,8 +33,26 @@ pub mod request;
 
 pub mod messages;
 
+pub mod branch;
+
+pub mod formats;
+
+pub mod redact;
+
+pub mod context;
+
+pub mod tokens;
+
+pub mod store;
+
 pub mod functions;
 
+pub mod toolbox;
+
+pub mod tool_loop;
+
+pub mod mcp;
+
 pub mod prompt;
 
 pub mod judge;
@@ -42,6 +66,8 @@ pub use refusal::Refusal;
 
 pub use error::{Error, Result};
 
+pub use cancel::CancellationToken;
+
 pub use provider::Provider;
 
 pub use client::Client;
@@ -52,8 +78,14 @@ pub use builders::{azure, openai};
 
 pub use messages::{Messages, messages};
 
-// Re-export the macro
-pub use responses_macros::tool;
+pub use store::{Conversation, ConversationStore};
+
+pub use toolbox::Toolbox;
+
+pub use tool_loop::ToolLoop;
+
+// Re-export the macros
+pub use responses_macros::{tool, tools};
 
 // Re-export schemars to prevent version conflicts
 // Users can now use `responses::schemars` instead of adding their own dependency
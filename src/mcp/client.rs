@@ -0,0 +1,202 @@
+//! Client for the tools of an MCP server.
+
+use super::{
+    protocol::{CallToolResult, JsonRpcMessage, ListToolsResult, McpToolInfo, initialize_params},
+    transport::{Http, Stdio, Transport},
+};
+use crate::{
+    error::{Error, Result},
+    toolbox::{ToolFuture, ToolboxHandler},
+    types::{FunctionOutput, OutputFunctionCall, Tool, ToolFunction},
+};
+use reqwest::header::HeaderMap;
+use schemars::Schema;
+use serde_json::{Value, json};
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::Mutex,
+};
+
+/// A connection to an MCP server.
+///
+/// Cloning is cheap; clones share the connection. A server started with
+/// [`stdio`](Self::stdio) is stopped when the last clone is dropped.
+#[derive(Clone)]
+pub struct McpClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    transport: Transport,
+    next_id: AtomicU64,
+    server_name: String,
+}
+
+impl McpClient {
+    /// Start the server with `command` and talk to it over its stdin and stdout.
+    ///
+    /// The server's stderr is inherited, so its logs appear in the parent's output.
+    pub async fn stdio(command: std::process::Command) -> Result<Self> {
+        let transport = Transport::Stdio(Mutex::new(Stdio::spawn(command)?));
+        Self::connect(transport).await
+    }
+
+    /// Talk to a server over an existing connection, such as a socket or an in-process
+    /// [`McpServer`](super::McpServer), reading its messages from `reader` and writing to `writer`.
+    pub async fn streams<R, W>(reader: R, writer: W) -> Result<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let transport = Transport::Stdio(Mutex::new(Stdio::streams(reader, writer)));
+        Self::connect(transport).await
+    }
+
+    /// Connect to a streamable HTTP endpoint, e.g. `http://localhost:8080/mcp`.
+    pub async fn http<U: AsRef<str>>(url: U) -> Result<Self> {
+        Self::http_with_headers(url, HeaderMap::new()).await
+    }
+
+    /// Connect to a streamable HTTP endpoint, sending `headers` (such as `Authorization`) with every message.
+    pub async fn http_with_headers<U: AsRef<str>>(url: U, headers: HeaderMap) -> Result<Self> {
+        let transport = Transport::Http(Http::new(url.as_ref(), headers)?);
+        Self::connect(transport).await
+    }
+
+    async fn connect(transport: Transport) -> Result<Self> {
+        let mut inner = Inner {
+            transport,
+            next_id: AtomicU64::new(1),
+            server_name: String::new(),
+        };
+
+        let result = inner.request("initialize", initialize_params()).await?;
+        inner.server_name = result["serverInfo"]["name"].as_str().unwrap_or_default().to_string();
+
+        inner.transport.notify(JsonRpcMessage::notification("notifications/initialized")).await?;
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// The name the server reported during initialization.
+    pub fn server_name(&self) -> &str {
+        &self.inner.server_name
+    }
+
+    /// List the tools of the server, following pagination.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+
+            let page: ListToolsResult = serde_json::from_value(self.inner.request("tools/list", params).await?)?;
+            tools.extend(page.tools);
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(tools),
+            }
+        }
+    }
+
+    /// The tools of the server as handlers for a [`Toolbox`](crate::Toolbox).
+    ///
+    /// A tool whose `inputSchema` is not a JSON schema is left out rather than
+    /// failing the others; use [`tool`](Self::tool) to see why.
+    pub async fn tools(&self) -> Result<Vec<McpTool>> {
+        Ok(self
+            .list_tools()
+            .await?
+            .into_iter()
+            .filter_map(|info| self.tool(info).ok())
+            .collect())
+    }
+
+    /// A handler for one tool listed by the server.
+    ///
+    /// The `inputSchema` is forwarded to the model as the server wrote it.
+    pub fn tool(&self, info: McpToolInfo) -> Result<McpTool> {
+        McpTool::new(self.clone(), info)
+    }
+
+    /// Call a tool with `arguments`, converting its content into the output for the model.
+    ///
+    /// A result the server flags with `isError` is returned as an error output
+    /// (`{"error": "..."}`) rather than an `Err`, since it is meant for the model.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<FunctionOutput> {
+        let params = json!({ "name": name, "arguments": arguments });
+        let result: CallToolResult = serde_json::from_value(self.inner.request("tools/call", params).await?)?;
+
+        Ok(result.into_output())
+    }
+}
+
+impl Inner {
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.transport.request(JsonRpcMessage::request(id, method, params), id).await
+    }
+}
+
+/// A tool of an MCP server, dispatched like a `#[tool]` handler.
+#[derive(Clone)]
+pub struct McpTool {
+    client: McpClient,
+    info: McpToolInfo,
+    tool: Tool,
+}
+
+impl McpTool {
+    fn new(client: McpClient, info: McpToolInfo) -> Result<Self> {
+        let parameters = Schema::try_from(info.input_schema.clone())
+            .map_err(|e| Error::InvalidSchema(format!("MCP tool '{}': {}", info.name, e)))?;
+
+        let tool = Tool::Function(ToolFunction {
+            name: info.name.clone(),
+            description: info.description.clone(),
+            parameters,
+            strict: None,
+        });
+
+        Ok(Self { client, info, tool })
+    }
+
+    /// The tool as listed by the server.
+    pub fn info(&self) -> &McpToolInfo {
+        &self.info
+    }
+}
+
+impl ToolboxHandler for McpTool {
+    fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    fn tool(&self) -> Tool {
+        self.tool.clone()
+    }
+
+    fn call<'a>(&'a self, call: &'a OutputFunctionCall) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let arguments = if call.arguments.trim().is_empty() {
+                json!({})
+            } else {
+                serde_json::from_str(&call.arguments).map_err(|e| Error::FunctionParameterParsing {
+                    function_name: call.name.clone(),
+                    source: e,
+                })?
+            };
+
+            self.client.call_tool(&self.info.name, arguments).await
+        })
+    }
+}
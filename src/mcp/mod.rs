@@ -0,0 +1,34 @@
+//! Model Context Protocol (MCP) support.
+//!
+//! [`McpServer`] serves the tools of a [`Toolbox`](crate::Toolbox) to other MCP clients.
+//! [`McpClient`] connects to an MCP server over stdio or streamable HTTP and exposes
+//! its tools as [`McpTool`] handlers, which are added to a [`Toolbox`](crate::Toolbox)
+//! next to `#[tool]` handlers:
+//!
+//! ```no_run
+//! use responses::Toolbox;
+//! use responses::mcp::McpClient;
+//! use std::process::Command;
+//!
+//! # async fn example() -> responses::Result<()> {
+//! let github = McpClient::stdio(Command::new("github-mcp-server")).await?;
+//! let docs = McpClient::http("http://localhost:8080/mcp").await?;
+//!
+//! let toolbox = Toolbox::new()
+//!     .register_all(github.tools().await?)
+//!     .register_all(docs.tools().await?);
+//! # Ok(())
+//! # }
+//! ```
+
+mod client;
+mod protocol;
+mod server;
+mod transport;
+
+pub use client::{McpClient, McpTool};
+pub use protocol::McpToolInfo;
+pub use server::McpServer;
+
+/// The protocol revision sent during initialization.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
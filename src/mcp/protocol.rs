@@ -0,0 +1,302 @@
+//! JSON-RPC messages and MCP payloads.
+
+use crate::{
+    error::{Error, Result},
+    types::{FunctionOutput, FunctionOutputContent},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// JSON-RPC error code for messages that are not valid JSON.
+pub(crate) const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC error code for unknown methods.
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for invalid parameters, such as an unknown tool.
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC 2.0 request, notification or response.
+///
+/// Requests have an `id` and a `method`, notifications only a `method`, and
+/// responses an `id` with either a `result` or an `error`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct JsonRpcMessage {
+    pub jsonrpc: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct RpcError {
+    pub code: i64,
+
+    pub message: String,
+}
+
+impl JsonRpcMessage {
+    pub fn request(id: u64, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id.into()),
+            method: Some(method.to_string()),
+            params: Some(params),
+            ..Default::default()
+        }
+    }
+
+    pub fn notification(method: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: Some(method.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn response(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            result: Some(result),
+            ..Default::default()
+        }
+    }
+
+    pub fn error_response(id: Value, code: i64, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            error: Some(RpcError { code, message }),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_response_to(&self, id: u64) -> bool {
+        self.method.is_none() && self.id.as_ref().and_then(Value::as_u64) == Some(id)
+    }
+
+    /// The result of a response, or its error as `Error::McpServer`.
+    pub fn into_result(self) -> Result<Value> {
+        match self.error {
+            Some(error) => Err(Error::McpServer {
+                code: error.code,
+                message: error.message,
+            }),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// A tool offered by an MCP server, as returned by `tools/list`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// JSON schema of the arguments.
+    pub input_schema: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ListToolsResult {
+    pub tools: Vec<McpToolInfo>,
+
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<Value>,
+
+    #[serde(default)]
+    pub structured_content: Option<Value>,
+
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl CallToolResult {
+    /// Convert the content into the output for the model.
+    ///
+    /// Text-only results become a text output, images an `input_image`. A result
+    /// flagged with `isError` becomes an error output, so the model can react to it.
+    pub fn into_output(self) -> FunctionOutput {
+        let mut items = Vec::new();
+
+        for content in &self.content {
+            match content["type"].as_str() {
+                Some("text") => items.push(FunctionOutputContent::InputText {
+                    text: content["text"].as_str().unwrap_or_default().to_string(),
+                }),
+                Some("image") => items.push(FunctionOutputContent::InputImage {
+                    image_url: format!(
+                        "data:{};base64,{}",
+                        content["mimeType"].as_str().unwrap_or("image/png"),
+                        content["data"].as_str().unwrap_or_default()
+                    ),
+                }),
+                Some("resource") if content["resource"]["text"].is_string() => {
+                    items.push(FunctionOutputContent::InputText {
+                        text: content["resource"]["text"].as_str().unwrap_or_default().to_string(),
+                    })
+                }
+                _ => items.push(FunctionOutputContent::InputText {
+                    text: content.to_string(),
+                }),
+            }
+        }
+
+        if items.is_empty()
+            && let Some(structured) = &self.structured_content
+        {
+            items.push(FunctionOutputContent::InputText {
+                text: structured.to_string(),
+            });
+        }
+
+        let all_text = items.iter().all(|item| matches!(item, FunctionOutputContent::InputText { .. }));
+
+        if self.is_error || all_text {
+            let text = items
+                .into_iter()
+                .filter_map(|item| match item {
+                    FunctionOutputContent::InputText { text } => Some(text),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            return if self.is_error {
+                FunctionOutput::error(text)
+            } else {
+                FunctionOutput::Text(text)
+            };
+        }
+
+        FunctionOutput::Content(items)
+    }
+}
+
+/// The MCP content of a tool output: text items, and images for `data:` URLs.
+pub(crate) fn output_content(output: FunctionOutput) -> Vec<Value> {
+    let items = match output {
+        FunctionOutput::Text(text) => return vec![json!({ "type": "text", "text": text })],
+        FunctionOutput::Content(items) => items,
+    };
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            FunctionOutputContent::InputText { text } => json!({ "type": "text", "text": text }),
+            FunctionOutputContent::InputImage { image_url } => {
+                let data_url = image_url
+                    .strip_prefix("data:")
+                    .and_then(|rest| rest.split_once(";base64,"));
+
+                match data_url {
+                    Some((mime_type, data)) => json!({ "type": "image", "data": data, "mimeType": mime_type }),
+                    None => json!({ "type": "text", "text": image_url }),
+                }
+            }
+        })
+        .collect()
+}
+
+/// The `initialize` parameters sent by the client.
+pub(crate) fn initialize_params() -> Value {
+    json!({
+        "protocolVersion": super::PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": {
+            "name": "responses",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(value: Value) -> CallToolResult {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_text_content_is_joined() {
+        let output = result(json!({
+            "content": [{"type": "text", "text": "first"}, {"type": "text", "text": "second"}]
+        }))
+        .into_output();
+
+        assert_eq!(output, FunctionOutput::text("first\nsecond"));
+    }
+
+    #[test]
+    fn test_images_become_data_urls() {
+        let output = result(json!({
+            "content": [
+                {"type": "text", "text": "chart"},
+                {"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"}
+            ]
+        }))
+        .into_output();
+
+        assert_eq!(
+            output,
+            FunctionOutput::Content(vec![
+                FunctionOutputContent::InputText { text: "chart".to_string() },
+                FunctionOutputContent::InputImage {
+                    image_url: "data:image/png;base64,iVBORw0KGgo=".to_string()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_output_content_round_trip() {
+        let output = FunctionOutput::Content(vec![
+            FunctionOutputContent::InputText { text: "chart".to_string() },
+            FunctionOutputContent::InputImage { image_url: "data:image/png;base64,iVBORw0KGgo=".to_string() },
+            FunctionOutputContent::InputImage { image_url: "https://example.com/chart.png".to_string() },
+        ]);
+
+        let content = output_content(output);
+        assert_eq!(content[1], json!({"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"}));
+        assert_eq!(content[2], json!({"type": "text", "text": "https://example.com/chart.png"}));
+
+        let output = result(json!({ "content": content[..2] })).into_output();
+        assert!(matches!(output, FunctionOutput::Content(items) if items.len() == 2));
+    }
+
+    #[test]
+    fn test_tool_errors_become_error_outputs() {
+        let output = result(json!({
+            "content": [{"type": "text", "text": "repository not found"}],
+            "isError": true
+        }))
+        .into_output();
+
+        assert_eq!(output, FunctionOutput::error("repository not found"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Serving the tools of a [`Toolbox`] to MCP clients.
+
+use super::protocol::{
+    INVALID_PARAMS, JsonRpcMessage, METHOD_NOT_FOUND, PARSE_ERROR, output_content,
+};
+use crate::{
+    error::{Error, Result},
+    toolbox::Toolbox,
+    types::{OutputFunctionCall, Tool},
+};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Protocol revisions whose tool messages the server understands.
+const SUPPORTED_VERSIONS: [&str; 3] = [super::PROTOCOL_VERSION, "2025-03-26", "2024-11-05"];
+
+/// Serves the tools of a [`Toolbox`] over MCP, so other agents and IDEs can call them.
+///
+/// `tools/list` lists the tool definitions and `tools/call` runs the handlers. A failing
+/// call is returned as a result with `isError`, so the client's model sees the error.
+///
+/// Tools marked `#[tool(requires_approval)]` are not served, since nobody would approve
+/// their calls; opt in with [`allow_unapproved`](Self::allow_unapproved).
+///
+/// ```no_run
+/// use responses::{tool, Result, Toolbox};
+/// use responses::mcp::McpServer;
+///
+/// #[tool]
+/// /// Get weather information for a city
+/// async fn get_weather(city: String) -> Result<String> {
+///     Ok(format!("Weather in {}: Sunny", city))
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<()> {
+///     let toolbox = Toolbox::new().register(get_weather_handler());
+///
+///     McpServer::new(toolbox).name("weather").serve_stdio().await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct McpServer {
+    toolbox: Toolbox,
+    name: String,
+    version: String,
+    instructions: Option<String>,
+    allow_unapproved: bool,
+}
+
+impl McpServer {
+    pub fn new(toolbox: Toolbox) -> Self {
+        Self {
+            toolbox,
+            name: "responses".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instructions: None,
+            allow_unapproved: false,
+        }
+    }
+
+    /// The server name reported to clients.
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The server version reported to clients.
+    pub fn version<S: Into<String>>(mut self, version: S) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Instructions on using the tools, which clients may add to their prompt.
+    pub fn instructions<S: Into<String>>(mut self, instructions: S) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Also serve tools that require approval, running their calls without asking.
+    ///
+    /// Use it only when the client asks a human before calling them.
+    pub fn allow_unapproved(mut self) -> Self {
+        self.allow_unapproved = true;
+        self
+    }
+
+    /// Whether `name` is served to clients.
+    fn serves(&self, name: &str) -> bool {
+        self.toolbox.contains(name) && (self.allow_unapproved || !self.toolbox.requires_approval(name))
+    }
+
+    /// Serve over the process's stdin and stdout until stdin is closed.
+    ///
+    /// Stdout carries the protocol, so tools must not print to it; log to stderr instead.
+    pub async fn serve_stdio(self) -> Result<()> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serve newline-delimited JSON-RPC messages read from `reader`, writing responses to `writer`.
+    ///
+    /// Requests are handled one at a time, in the order they arrive.
+    pub async fn serve<R, W>(self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await.map_err(io_error)? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<JsonRpcMessage>(&line) {
+                Ok(message) => self.handle(message).await,
+                Err(e) => Some(JsonRpcMessage::error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    format!("Invalid JSON-RPC message: {}", e),
+                )),
+            };
+
+            if let Some(response) = response {
+                let mut line = serde_json::to_vec(&response)?;
+                line.push(b'\n');
+
+                writer.write_all(&line).await.map_err(io_error)?;
+                writer.flush().await.map_err(io_error)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The response to `message`, or `None` for notifications and responses.
+    async fn handle(&self, message: JsonRpcMessage) -> Option<JsonRpcMessage> {
+        let (Some(method), Some(id)) = (message.method, message.id) else {
+            return None;
+        };
+
+        let params = message.params.unwrap_or(Value::Null);
+
+        let result = match method.as_str() {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(&id, &params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => JsonRpcMessage::response(id, result),
+            Err((code, message)) => JsonRpcMessage::error_response(id, code, message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        // Answer with the client's revision when supported, otherwise propose the latest
+        let protocol_version = params["protocolVersion"]
+            .as_str()
+            .filter(|version| SUPPORTED_VERSIONS.contains(version))
+            .unwrap_or(super::PROTOCOL_VERSION);
+
+        let mut result = json!({
+            "protocolVersion": protocol_version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": self.name, "version": self.version },
+        });
+
+        if let Some(instructions) = &self.instructions {
+            result["instructions"] = json!(instructions);
+        }
+
+        result
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .toolbox
+            .tools()
+            .into_iter()
+            .filter_map(|tool| {
+                let Tool::Function(function) = tool;
+                if !self.serves(&function.name) {
+                    return None;
+                }
+
+                let mut info = json!({
+                    "name": function.name,
+                    "inputSchema": function.parameters,
+                });
+                if let Some(description) = function.description {
+                    info["description"] = json!(description);
+                }
+                Some(info)
+            })
+            .collect();
+
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, id: &Value, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let Some(name) = params["name"].as_str() else {
+            return Err((INVALID_PARAMS, "Missing tool name".to_string()));
+        };
+
+        if !self.toolbox.contains(name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool: {}", name)));
+        }
+
+        if !self.serves(name) {
+            return Err((INVALID_PARAMS, format!("Tool requires approval and is not served: {}", name)));
+        }
+
+        let arguments = match &params["arguments"] {
+            Value::Null => json!({}),
+            arguments => arguments.clone(),
+        };
+
+        let call = OutputFunctionCall {
+            call_id: id.to_string(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        };
+
+        Ok(match self.toolbox.call(&call).await {
+            Ok(output) => json!({ "content": output_content(output), "isError": false }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::McpConnection(format!("failed to exchange messages with the client: {}", e))
+}
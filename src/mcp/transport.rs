@@ -0,0 +1,230 @@
+//! Connections to MCP servers: a spawned process speaking over stdio, any other
+//! byte stream carrying the same messages, or a streamable HTTP endpoint.
+
+use super::protocol::{JsonRpcMessage, METHOD_NOT_FOUND};
+use crate::{
+    error::{Error, Result},
+    stream::sse::SseDecoder,
+};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap};
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    process::Child,
+    sync::Mutex,
+};
+use url::Url;
+
+const SESSION_ID: &str = "Mcp-Session-Id";
+
+const PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+
+pub(crate) enum Transport {
+    Stdio(Mutex<Stdio>),
+    Http(Http),
+}
+
+impl Transport {
+    /// Send a request and wait for its response.
+    pub async fn request(&self, request: JsonRpcMessage, id: u64) -> Result<Value> {
+        match self {
+            Transport::Stdio(stdio) => stdio.lock().await.request(&request, id).await,
+            Transport::Http(http) => http.request(&request, id).await,
+        }
+    }
+
+    /// Send a notification, which has no response.
+    pub async fn notify(&self, notification: JsonRpcMessage) -> Result<()> {
+        match self {
+            Transport::Stdio(stdio) => stdio.lock().await.send(&notification).await,
+            Transport::Http(http) => http.post(&notification).await.map(drop),
+        }
+    }
+}
+
+/// A server exchanging newline-delimited JSON-RPC messages, usually a child process.
+///
+/// Requests are sent one at a time. The process is killed when the transport is dropped.
+pub(crate) struct Stdio {
+    _child: Option<Child>,
+    stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    stdout: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+}
+
+impl Stdio {
+    pub fn spawn(command: std::process::Command) -> Result<Self> {
+        let program = command.get_program().to_string_lossy().into_owned();
+
+        let mut child = tokio::process::Command::from(command)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::McpConnection(format!("failed to start '{}': {}", program, e)))?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(Error::McpConnection(format!("no stdio pipes for '{}'", program)));
+        };
+
+        Ok(Self {
+            _child: Some(child),
+            ..Self::streams(stdout, stdin)
+        })
+    }
+
+    /// Exchange messages over `reader` and `writer` instead of a child's pipes.
+    pub fn streams<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self {
+            _child: None,
+            stdin: Box::new(writer),
+            stdout: BufReader::new(Box::new(reader)),
+        }
+    }
+
+    async fn send(&mut self, message: &JsonRpcMessage) -> Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+
+        self.stdin.write_all(&line).await.map_err(closed)?;
+        self.stdin.flush().await.map_err(closed)
+    }
+
+    async fn receive(&mut self) -> Result<JsonRpcMessage> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+
+            if self.stdout.read_line(&mut line).await.map_err(closed)? == 0 {
+                return Err(Error::McpConnection("the server closed the connection".to_string()));
+            }
+
+            if !line.trim().is_empty() {
+                return serde_json::from_str(&line)
+                    .map_err(|e| Error::McpConnection(format!("invalid message from the server: {}", e)));
+            }
+        }
+    }
+
+    async fn request(&mut self, request: &JsonRpcMessage, id: u64) -> Result<Value> {
+        self.send(request).await?;
+
+        loop {
+            let message = self.receive().await?;
+
+            if message.is_response_to(id) {
+                return message.into_result();
+            }
+
+            // Answer requests from the server; notifications and stale responses are skipped
+            if let (Some(method), Some(request_id)) = (&message.method, message.id) {
+                let reply = match method.as_str() {
+                    "ping" => JsonRpcMessage::response(request_id, json!({})),
+                    _ => JsonRpcMessage::error_response(
+                        request_id,
+                        METHOD_NOT_FOUND,
+                        format!("Method not supported by the client: {}", method),
+                    ),
+                };
+                self.send(&reply).await?;
+            }
+        }
+    }
+}
+
+fn closed(e: std::io::Error) -> Error {
+    Error::McpConnection(format!("lost the connection to the server: {}", e))
+}
+
+/// A streamable HTTP endpoint. Each message is a POST answered with JSON or an event stream.
+pub(crate) struct Http {
+    client: reqwest::Client,
+    url: Url,
+    headers: HeaderMap,
+    session_id: std::sync::Mutex<Option<String>>,
+}
+
+impl Http {
+    pub fn new(url: &str, headers: HeaderMap) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: Url::parse(url)?,
+            headers,
+            session_id: std::sync::Mutex::new(None),
+        })
+    }
+
+    async fn post(&self, message: &JsonRpcMessage) -> Result<reqwest::Response> {
+        let mut request = self.client
+            .post(self.url.clone())
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(PROTOCOL_VERSION_HEADER, super::PROTOCOL_VERSION)
+            .json(message);
+
+        let session_id = self.session_id.lock().unwrap().clone();
+        if let Some(session_id) = session_id {
+            request = request.header(SESSION_ID, session_id);
+        }
+
+        let response = request.send().await?;
+
+        if let Some(session_id) = response.headers().get(SESSION_ID).and_then(|value| value.to_str().ok()) {
+            *self.session_id.lock().unwrap() = Some(session_id.to_string());
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(Error::McpConnection(format!("HTTP {}: {}", status, body)));
+        }
+
+        Ok(response)
+    }
+
+    async fn request(&self, request: &JsonRpcMessage, id: u64) -> Result<Value> {
+        let mut response = self.post(request).await?;
+
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+
+        if !is_event_stream {
+            let message: JsonRpcMessage = response.json().await?;
+            return message.into_result();
+        }
+
+        // The response arrives as one of the events, possibly after notifications
+        let mut decoder = SseDecoder::new();
+
+        loop {
+            let events = match response.chunk().await? {
+                Some(chunk) => decoder.push(&chunk),
+                None => {
+                    if let Some(message) = decoder.finish().and_then(|data| response_in(&data, id)) {
+                        return message.into_result();
+                    }
+                    return Err(Error::McpConnection("the event stream ended without a response".to_string()));
+                }
+            };
+
+            if let Some(message) = events.iter().find_map(|data| response_in(data, id)) {
+                return message.into_result();
+            }
+        }
+    }
+}
+
+/// The response to request `id`, if `data` is one.
+fn response_in(data: &str, id: u64) -> Option<JsonRpcMessage> {
+    serde_json::from_str::<JsonRpcMessage>(data)
+        .ok()
+        .filter(|message| message.is_response_to(id))
+}
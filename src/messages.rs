@@ -1,5 +1,9 @@
-use crate::types::{Input, InputMessage, Role};
+use crate::error::{Error, Result};
+use crate::types::{FunctionOutput, Input, InputFunctionCallOutput, InputMessage, OutputFunctionCall, Role};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
 
 /// A builder for constructing and managing conversation histories.
 /// 
@@ -23,12 +27,19 @@ use std::collections::HashMap;
 /// 
 /// println!("Conversation has {} messages", history.len());
 /// ```
-#[derive(Clone, Debug)]
+///
+/// Conversations serialize with serde, keeping template inputs unrendered together
+/// with their variables, and can be saved as JSON or JSONL with [`Messages::save_json`]
+/// and [`Messages::save_jsonl`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Messages {
     messages: Vec<Input>,
     // Fluent API support
+    #[serde(default, rename = "variables", skip_serializing_if = "HashMap::is_empty")]
     accumulated_variables: HashMap<String, serde_json::Value>,
+    #[serde(default, rename = "locale", skip_serializing_if = "Option::is_none")]
     current_locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     locale_paths: Vec<String>,
 }
 
@@ -93,6 +104,21 @@ impl Messages {
         self
     }
     
+    /// Add a function call made by the model.
+    /// Must precede the output of the call.
+    pub fn function_call(mut self, call: OutputFunctionCall) -> Self {
+        self.messages.push(Input::FunctionCall(call));
+        self
+    }
+    
+    /// Add the output of a function call.
+    pub fn function_output<S: Into<String>>(mut self, call_id: S, output: FunctionOutput) -> Self {
+        self.messages.push(Input::FunctionCallOutput(InputFunctionCallOutput {
+            call_id: call_id.into(),
+            output,
+        }));
+        self
+    }
     
     /// Add multiple messages at once.
     pub fn add_messages<I, S>(mut self, messages: I) -> Self 
@@ -146,6 +172,9 @@ impl Messages {
     
     /// Convert to raw Input vector (consumes self).
     /// Templates are rendered using accumulated variables.
+    ///
+    /// Templates that fail to render are kept unrendered and must not be sent as they are;
+    /// the request builders' `.messages()` renders with [`try_render_inputs`](Self::try_render_inputs) instead.
     pub fn into_inputs(self) -> Vec<Input> {
         self.render_inputs()
     }
@@ -157,27 +186,25 @@ impl Messages {
     
     /// Get rendered inputs, lazily evaluating any templates.
     /// Templates are rendered using accumulated variables.
+    ///
+    /// Templates that fail to render are kept unrendered, with the variables and locale
+    /// applied. Use [`try_render_inputs`](Self::try_render_inputs) to get the error instead.
     pub fn render_inputs(&self) -> Vec<Input> {
         self.messages.iter().map(|input| {
             match input {
-                Input::Message(msg) => Input::Message(msg.clone()),
+                Input::Message(_) | Input::FunctionCall(_) | Input::FunctionCallOutput(_) => input.clone(),
                 Input::Template(template_input) => {
-                    // Apply accumulated variables and render the template
-                    let template_with_vars = Self::apply_accumulated_variables_static(&self.accumulated_variables, template_input.template.clone());
-                    let template_with_locale = if let Some(ref locale) = self.current_locale {
-                        template_with_vars.clone().with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>()).unwrap_or(template_with_vars)
-                    } else {
-                        template_with_vars
+                    let Ok(template) = self.prepare_template(&template_input.template) else {
+                        return input.clone();
                     };
-                    
-                    match template_with_locale.render_with_vars() {
+                    match template.render_with_vars() {
                         Ok(content) => Input::Message(InputMessage {
-                            role: template_input.role.clone(),
+                            role: template_input.role,
                             content,
                         }),
-                        Err(_) => Input::Message(InputMessage {
-                            role: template_input.role.clone(),
-                            content: format!("<!-- Template rendering failed -->"),
+                        Err(_) => Input::Template(crate::types::TemplateInput {
+                            role: template_input.role,
+                            template,
                         }),
                     }
                 }
@@ -185,6 +212,46 @@ impl Messages {
         }).collect()
     }
     
+    /// Get rendered inputs, failing on the first template that cannot be rendered.
+    ///
+    /// The error is `Error::MessageRendering`, with the index of the message and the
+    /// start of its template.
+    pub fn try_render_inputs(&self) -> Result<Vec<Input>> {
+        self.messages.iter().enumerate().map(|(index, input)| {
+            match input {
+                Input::Message(_) | Input::FunctionCall(_) | Input::FunctionCallOutput(_) => Ok(input.clone()),
+                Input::Template(template_input) => {
+                    let content = self.prepare_template(&template_input.template)
+                        .and_then(|template| template.render_with_vars())
+                        .map_err(|source| rendering_error(index, &template_input.template, source))?;
+                    Ok(Input::Message(InputMessage {
+                        role: template_input.role,
+                        content,
+                    }))
+                }
+            }
+        }).collect()
+    }
+    
+    /// The template with the accumulated variables and the locale applied.
+    fn prepare_template(&self, template: &crate::prompt::PromptTemplate) -> Result<crate::prompt::PromptTemplate> {
+        let template = Self::apply_accumulated_variables_static(&self.accumulated_variables, template.clone());
+        match self.current_locale {
+            Some(ref locale) => template.with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>()),
+            None => Ok(template),
+        }
+    }
+    
+    /// A copy with other inputs, keeping the variables and locale settings.
+    pub(crate) fn with_inputs(&self, inputs: Vec<Input>) -> Messages {
+        Messages {
+            messages: inputs,
+            accumulated_variables: self.accumulated_variables.clone(),
+            current_locale: self.current_locale.clone(),
+            locale_paths: self.locale_paths.clone(),
+        }
+    }
+    
     /// Get the number of messages.
     pub fn len(&self) -> usize {
         self.messages.len()
@@ -238,6 +305,78 @@ impl Messages {
         self.messages.truncate(new_len);
         self
     }
+
+    /// Insert an input at `index`, shifting the later inputs back.
+    pub fn insert(&mut self, index: usize, input: Input) -> Result<()> {
+        if index > self.messages.len() {
+            return Err(Error::MessageIndexOutOfRange { index, len: self.messages.len() });
+        }
+        self.messages.insert(index, input);
+        Ok(())
+    }
+
+    /// Replace the input at `index`, e.g. to edit an earlier message, returning the old input.
+    pub fn replace(&mut self, index: usize, input: Input) -> Result<Input> {
+        let len = self.messages.len();
+        let slot = self.messages.get_mut(index).ok_or(Error::MessageIndexOutOfRange { index, len })?;
+        Ok(std::mem::replace(slot, input))
+    }
+
+    /// Remove the input at `index` and return it.
+    pub fn remove(&mut self, index: usize) -> Result<Input> {
+        if index >= self.messages.len() {
+            return Err(Error::MessageIndexOutOfRange { index, len: self.messages.len() });
+        }
+        Ok(self.messages.remove(index))
+    }
+
+    /// A new branch with the first `n` inputs, keeping the variables and locale settings.
+    ///
+    /// Continue the branch to regenerate a reply or to follow an edited message,
+    /// while this conversation stays as it was.
+    pub fn fork(&self, n: usize) -> Messages {
+        self.with_inputs(self.messages[..n.min(self.messages.len())].to_vec())
+    }
+
+    /// The tokens of the conversation as sent to `model`, with templates rendered.
+    pub fn estimate_tokens(&self, model: &str) -> Result<usize> {
+        let encoding = crate::tokens::Encoding::for_model(model);
+        Ok(self.try_render_inputs()?
+            .iter()
+            .map(|input| crate::tokens::input_tokens(input, encoding))
+            .sum())
+    }
+    
+    /// Summarize the older turns into one message with a model, keeping the latest turns verbatim.
+    ///
+    /// System and developer messages are kept, except summaries from earlier compactions,
+    /// which are folded into the new one. See [`Compaction`](crate::context::Compaction).
+    pub async fn compact<P: crate::Provider>(
+        &self,
+        client: &crate::Client<P>,
+        policy: &crate::context::CompactionPolicy,
+    ) -> Result<crate::context::Compaction> {
+        self.compact_with_options(client, policy, crate::Options::default()).await
+    }
+    
+    /// Like [`compact`](Self::compact), sending the summary request with `options`,
+    /// such as a timeout or cancellation token. The model and input come from the policy.
+    pub async fn compact_with_options<P: crate::Provider>(
+        &self,
+        client: &crate::Client<P>,
+        policy: &crate::context::CompactionPolicy,
+        options: crate::Options,
+    ) -> Result<crate::context::Compaction> {
+        crate::context::compact(self, client, policy, options).await
+    }
+    
+    /// Drop the oldest turns that do not fit the context budget of `model`.
+    ///
+    /// See [`ContextPolicy`](crate::context::ContextPolicy) for what is kept.
+    pub fn trim(mut self, policy: &crate::context::ContextPolicy, model: &str) -> Self {
+        self.messages = policy.apply(self.messages, model);
+        self
+    }
     
     /// Filter messages by role.
     pub fn filter_by_role(&self, role: Role) -> Messages {
@@ -247,6 +386,7 @@ impl Messages {
                 match input {
                     Input::Message(msg) => msg.role == role,
                     Input::Template(template_input) => template_input.role == role,
+                    Input::FunctionCall(_) | Input::FunctionCallOutput(_) => false,
                 }
             })
             .cloned()
@@ -263,6 +403,127 @@ impl Messages {
     
     
 
+    // === PERSISTENCE ===
+
+    /// Serialize as pretty-printed JSON, including variables and locale settings.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize from the JSON written by [`Messages::to_json`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Save as a JSON file, replacing any existing file.
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()?).map_err(|e| file_error(path, e))
+    }
+
+    /// Load from a JSON file written by [`Messages::save_json`].
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+        Self::from_json(&json)
+    }
+
+    /// Serialize as JSONL, one input per line.
+    ///
+    /// Only the inputs are written: variables and locale set on the `Messages`
+    /// are not, though template inputs keep their own variables.
+    pub fn to_jsonl(&self) -> Result<String> {
+        inputs_to_jsonl(&self.messages)
+    }
+
+    /// Deserialize from JSONL, one input per line. Blank lines are skipped.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self> {
+        let inputs = jsonl
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|source| Error::ConversationLine { line: index + 1, source })
+            })
+            .collect::<Result<Vec<Input>>>()?;
+
+        Ok(Self::from_inputs(inputs))
+    }
+
+    /// Save as a JSONL file, replacing any existing file.
+    pub fn save_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_jsonl()?).map_err(|e| file_error(path, e))
+    }
+
+    /// Append the inputs to a JSONL file, creating it if needed.
+    ///
+    /// Use with [`Messages::take_last`] to log each new turn of a session.
+    pub fn append_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let jsonl = self.to_jsonl()?;
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(jsonl.as_bytes()))
+            .map_err(|e| file_error(path, e))
+    }
+
+    /// Load from a JSONL file written by [`Messages::save_jsonl`] or [`Messages::append_jsonl`].
+    pub fn load_jsonl<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let jsonl = std::fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+        Self::from_jsonl(&jsonl)
+    }
+
+    /// Create Messages from Chat Completions messages, as an array or a `{"messages": [...]}` object.
+    ///
+    /// See [`formats`](crate::formats) for how tool calls convert.
+    pub fn from_chat_completions(messages: &serde_json::Value) -> Result<Self> {
+        crate::formats::from_chat_completions(messages).map(Self::from_inputs)
+    }
+
+    /// The conversation as Chat Completions messages, with templates rendered.
+    pub fn to_chat_completions(&self) -> Result<serde_json::Value> {
+        crate::formats::to_chat_completions(&self.try_render_inputs()?)
+    }
+
+    /// Create Messages from a ShareGPT conversation.
+    pub fn from_sharegpt(conversation: &serde_json::Value) -> Result<Self> {
+        crate::formats::from_sharegpt(conversation).map(Self::from_inputs)
+    }
+
+    /// The conversation as a ShareGPT `{"conversations": [...]}` object, with templates rendered.
+    pub fn to_sharegpt(&self) -> Result<serde_json::Value> {
+        crate::formats::to_sharegpt(&self.try_render_inputs()?)
+    }
+
+    /// A fine-tuning JSONL row with this conversation as the prompt and `response` as the answer.
+    ///
+    /// Structured responses are written as their JSON, and refusals as an assistant `refusal`.
+    pub fn to_fine_tuning_row<T: Serialize>(&self, response: &crate::Response<T>, tools: &[crate::types::Tool]) -> Result<String> {
+        crate::formats::fine_tuning_row(self, response, tools)
+    }
+
+    /// The conversation as a Markdown transcript with `## User` style headers, with templates rendered.
+    ///
+    /// The transcript loads back with [`ConversationTemplate`](crate::prompt::ConversationTemplate),
+    /// so real conversations can be saved as templates and test fixtures.
+    pub fn to_markdown(&self) -> Result<String> {
+        crate::formats::to_markdown(&self.try_render_inputs()?)
+    }
+
+    /// The conversation with personal data replaced by placeholders, with templates rendered.
+    ///
+    /// Restore the originals in the response with the returned [`RedactionMap`](crate::redact::RedactionMap).
+    pub fn redact(&self, redactor: &crate::redact::Redactor) -> Result<(Messages, crate::redact::RedactionMap)> {
+        let mut map = crate::redact::RedactionMap::new();
+        let inputs = redactor.redact_inputs(&self.try_render_inputs()?, &mut map);
+        Ok((self.with_inputs(inputs), map))
+    }
+
     /// Create Messages from a conversation template file.
     pub fn from_conversation_template<P: AsRef<std::path::Path>>(path: P, vars: &serde_json::Value) -> crate::error::Result<Self> {
         let conversation = crate::prompt::ConversationTemplate::load(path)?;
@@ -337,6 +598,40 @@ impl Messages {
     }
 }
 
+/// The error for a template that failed to render, naming the message and template.
+pub(crate) fn rendering_error(index: usize, template: &crate::prompt::PromptTemplate, source: Error) -> Error {
+    // Name the template by its first line of content, after any frontmatter
+    let mut body = template.source().trim_start();
+    if let Some(rest) = body.strip_prefix("---")
+        && let Some(end) = rest.find("\n---")
+    {
+        body = &rest[end + 4..];
+    }
+    let first_line = body.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    let excerpt = match first_line.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &first_line[..end]),
+        None => first_line.to_string(),
+    };
+    Error::MessageRendering { index, template: excerpt, source: Box::new(source) }
+}
+
+/// Serialize inputs as JSONL, one per line.
+pub(crate) fn inputs_to_jsonl(inputs: &[Input]) -> Result<String> {
+    let mut jsonl = String::new();
+    for input in inputs {
+        jsonl.push_str(&serde_json::to_string(input)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+pub(crate) fn file_error(path: &Path, source: std::io::Error) -> Error {
+    Error::ConversationFile {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
 impl Default for Messages {
     fn default() -> Self {
         Self::new()
@@ -409,8 +704,52 @@ mod tests {
         let system_only = conversation.filter_by_role(Role::System);
         assert_eq!(system_only.len(), 1);
     }
+
+    #[test]
+    fn test_editing_at_an_index() {
+        let edited = InputMessage { role: Role::User, content: "Question 1, edited".to_string() };
+        let mut conversation = Messages::new()
+            .system("System prompt")
+            .user("Question 1")
+            .assistant("Answer 1")
+            .var("topic", "math");
+
+        let previous = conversation.replace(1, Input::Message(edited)).unwrap();
+        assert!(matches!(previous, Input::Message(msg) if msg.content == "Question 1"));
+        let removed = conversation.remove(2).unwrap();
+        assert!(matches!(removed, Input::Message(msg) if msg.content == "Answer 1"));
+        conversation
+            .insert(0, Input::Message(InputMessage { role: Role::Developer, content: "Be brief".to_string() }))
+            .unwrap();
+
+        let contents = |conversation: &Messages| -> Vec<String> {
+            conversation.inputs().iter().map(|input| match input {
+                Input::Message(msg) => msg.content.clone(),
+                _ => String::new(),
+            }).collect()
+        };
+        assert_eq!(contents(&conversation), vec!["Be brief", "System prompt", "Question 1, edited"]);
+
+        // Out of range edits fail and leave the conversation as it was
+        assert!(matches!(
+            conversation.remove(3),
+            Err(Error::MessageIndexOutOfRange { index: 3, len: 3 })
+        ));
+        let first = conversation.inputs()[0].clone();
+        assert!(conversation.insert(4, first.clone()).is_err());
+        assert!(conversation.replace(3, first).is_err());
+        assert_eq!(contents(&conversation), vec!["Be brief", "System prompt", "Question 1, edited"]);
+
+        // A fork keeps the variables, and the original is unchanged
+        let fork = conversation.fork(2).user("Another question");
+        assert_eq!(fork.len(), 3);
+        assert_eq!(fork.accumulated_variables.get("topic"), Some(&serde_json::json!("math")));
+        assert_eq!(conversation.len(), 3);
+        assert_eq!(conversation.fork(10).len(), 3);
+    }
     
     #[test]
+    #[allow(clippy::get_first)]
     fn test_multiline_messages() {
         let multiline_system = r#"You are a helpful assistant.
 
@@ -1,4 +1,9 @@
-use crate::types::{Input, Tool, ToolChoice};
+use crate::{
+    cancel::CancellationToken,
+    context::ContextPolicy,
+    types::{Input, Tool, ToolChoice},
+};
+use std::time::Duration;
 
 #[derive(Clone, Debug, Default)]
 pub struct Options {
@@ -13,4 +18,18 @@ pub struct Options {
     pub input: Option<Vec<Input>>,
 
     pub temperature: Option<f32>,
+
+    pub validate_schema: bool,
+
+    pub strict: Option<bool>,
+
+    pub description: Option<String>,
+
+    pub json_object: bool,
+
+    pub timeout: Option<Duration>,
+
+    pub cancel_token: Option<CancellationToken>,
+
+    pub context_policy: Option<ContextPolicy>,
 }
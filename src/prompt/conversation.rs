@@ -1,8 +1,9 @@
 //! Conversation template support for multi-turn interactions.
 
+use std::borrow::Cow;
 use std::path::Path;
 use std::collections::HashMap;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::messages::Messages;
 use crate::prompt::template::PromptTemplate;
 use crate::types::Role;
@@ -23,6 +24,16 @@ impl ConversationTemplate {
         })
     }
 
+    /// Create a conversation template from content, such as the output of
+    /// [`Messages::to_markdown`](crate::Messages::to_markdown).
+    pub fn from_content(content: &str) -> Result<Self> {
+        let template = PromptTemplate::from_content(content)?;
+        Ok(Self { 
+            template,
+            accumulated_variables: HashMap::new(),
+        })
+    }
+
     /// Set a template variable (fluent API)
     pub fn var<K: Into<String>, V: serde::Serialize>(mut self, key: K, value: V) -> Self {
         if let Ok(json_value) = serde_json::to_value(value) {
@@ -50,7 +61,14 @@ impl ConversationTemplate {
     /// ## User  
     /// ## Assistant
     /// ## Developer
+    ///
+    /// Transcripts, marked with `transcript: true` in the frontmatter, are read exactly
+    /// as [`Messages::to_markdown`](crate::Messages::to_markdown) wrote them.
     fn parse_conversation(&self, content: &str) -> Result<Messages> {
+        if self.template.is_transcript() {
+            return parse_transcript(content);
+        }
+        
         let mut messages = Messages::new();
         let mut current_role: Option<Role> = None;
         let mut current_content = String::new();
@@ -61,10 +79,10 @@ impl ConversationTemplate {
             // Check for role headers
             if let Some(role) = self.parse_role_header(trimmed) {
                 // Save previous message if any
-                if let Some(prev_role) = current_role {
-                    if !current_content.trim().is_empty() {
-                        messages = messages.add_message(prev_role, current_content.trim());
-                    }
+                if let Some(prev_role) = current_role
+                    && !current_content.trim().is_empty()
+                {
+                    messages = messages.add_message(prev_role, current_content.trim());
                 }
                 
                 // Start new message
@@ -81,10 +99,10 @@ impl ConversationTemplate {
         }
 
         // Save final message
-        if let Some(role) = current_role {
-            if !current_content.trim().is_empty() {
-                messages = messages.add_message(role, current_content.trim());
-            }
+        if let Some(role) = current_role
+            && !current_content.trim().is_empty()
+        {
+            messages = messages.add_message(role, current_content.trim());
         }
 
         // If no role headers found, treat entire content as system message
@@ -97,17 +115,109 @@ impl ConversationTemplate {
 
     /// Parse role headers like "## System", "## User", etc.
     fn parse_role_header(&self, line: &str) -> Option<Role> {
-        if !line.starts_with("## ") {
-            return None;
+        role_header(line)
+    }
+}
+
+fn role_header(line: &str) -> Option<Role> {
+    if !line.starts_with("## ") {
+        return None;
+    }
+
+    let role_text = line[3..].trim().to_lowercase();
+    match role_text.as_str() {
+        "system" => Some(Role::System),
+        "user" => Some(Role::User),
+        "assistant" => Some(Role::Assistant),
+        "developer" => Some(Role::Developer),
+        _ => None,
+    }
+}
+
+/// Frontmatter marking a template as a literal transcript.
+pub(crate) const TRANSCRIPT_FRONTMATTER: &str = "---\ntranscript: true\n---\n";
+
+/// One message of a transcript: the header, a blank line, the content with header-like
+/// lines escaped, and a blank line. Nothing is trimmed, so the content reads back exactly.
+pub(crate) fn transcript_section(role: Role, content: &str) -> String {
+    let header = match role {
+        Role::System => "System",
+        Role::Developer => "Developer",
+        Role::User => "User",
+        Role::Assistant => "Assistant",
+    };
+    let lines: Vec<_> = content.split('\n').map(escape_line).collect();
+    format!("## {}\n\n{}\n\n", header, lines.join("\n"))
+}
+
+/// Read the messages of a transcript written with [`transcript_section`].
+pub(crate) fn parse_transcript(content: &str) -> Result<Messages> {
+    let mut sections: Vec<(Role, Vec<&str>)> = Vec::new();
+
+    for line in content.strip_suffix('\n').unwrap_or(content).split('\n') {
+        if let Some(role) = role_header(line.trim()) {
+            sections.push((role, Vec::new()));
+            continue;
+        }
+        match sections.last_mut() {
+            Some((_, lines)) => lines.push(line),
+            None if line.trim().is_empty() => {}
+            None => return Err(invalid_transcript("text before the first message header")),
         }
+    }
+
+    let mut messages = Messages::new();
+    for (index, (role, lines)) in sections.into_iter().enumerate() {
+        let ["", lines @ .., ""] = lines.as_slice() else {
+            return Err(invalid_transcript(&format!("message {} must start and end with a blank line", index)));
+        };
+        let lines: Vec<_> = lines.iter().map(|line| unescape_line(line)).collect();
+        messages = messages.add_message(role, lines.join("\n"));
+    }
+    Ok(messages)
+}
+
+fn invalid_transcript(reason: &str) -> Error {
+    Error::InvalidTranscript(reason.to_string())
+}
+
+/// Whether `line` would be read as a header, or is an escaped header, once trimmed.
+fn is_header_like(line: &str) -> bool {
+    line.trim().trim_start_matches('\\').starts_with("## ")
+}
 
-        let role_text = line[3..].trim().to_lowercase();
-        match role_text.as_str() {
-            "system" => Some(Role::System),
-            "user" => Some(Role::User),
-            "assistant" => Some(Role::Assistant),
-            "developer" => Some(Role::Developer),
-            _ => None,
+/// Escape a content line that would otherwise start a new message, by adding a backslash.
+pub(crate) fn escape_line(line: &str) -> Cow<'_, str> {
+    if !is_header_like(line) {
+        return Cow::Borrowed(line);
+    }
+    let indent = line.len() - line.trim_start().len();
+    Cow::Owned(format!("{}\\{}", &line[..indent], &line[indent..]))
+}
+
+/// Undo [`escape_line`].
+pub(crate) fn unescape_line(line: &str) -> Cow<'_, str> {
+    let indent = line.len() - line.trim_start().len();
+    if !is_header_like(line) || !line[indent..].starts_with('\\') {
+        return Cow::Borrowed(line);
+    }
+    Cow::Owned(format!("{}{}", &line[..indent], &line[indent + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping_header_lines() {
+        for line in ["## User", "  ## Assistant", "\\## User", "\\\\## Notes", "## Notes"] {
+            let escaped = escape_line(line);
+            assert!(!escaped.trim().starts_with("## "));
+            assert_eq!(unescape_line(&escaped), line);
         }
+
+        assert_eq!(escape_line("# Title"), "# Title");
+        assert_eq!(escape_line("##"), "##");
+        assert_eq!(unescape_line("\\n## User"), "\\n## User");
     }
-}
\ No newline at end of file
+}
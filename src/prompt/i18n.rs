@@ -136,7 +136,7 @@ impl LocaleManager {
                 
                 if entry.path().extension().and_then(|s| s.to_str()) == Some("yaml") ||
                    entry.path().extension().and_then(|s| s.to_str()) == Some("yml") {
-                    let content = fs::read_to_string(&entry.path()).map_err(|e| Error::PromptFileRead {
+                    let content = fs::read_to_string(entry.path()).map_err(|e| Error::PromptFileRead {
                         path: entry.path().display().to_string(),
                         source: e,
                     })?;
@@ -253,28 +253,25 @@ impl LocaleData {
     }
 
     fn collect_keys(&self, prefix: &str, value: &serde_yaml::Value, keys: &mut Vec<String>) {
-        match value {
-            serde_yaml::Value::Mapping(map) => {
-                for (key, val) in map {
-                    if let Some(key_str) = key.as_str() {
-                        let full_key = if prefix.is_empty() {
-                            key_str.to_string()
-                        } else {
-                            format!("{}.{}", prefix, key_str)
-                        };
-                        
-                        match val {
-                            serde_yaml::Value::Mapping(_) => {
-                                self.collect_keys(&full_key, val, keys);
-                            }
-                            _ => {
-                                keys.push(full_key);
-                            }
+        if let serde_yaml::Value::Mapping(map) = value {
+            for (key, val) in map {
+                if let Some(key_str) = key.as_str() {
+                    let full_key = if prefix.is_empty() {
+                        key_str.to_string()
+                    } else {
+                        format!("{}.{}", prefix, key_str)
+                    };
+                    
+                    match val {
+                        serde_yaml::Value::Mapping(_) => {
+                            self.collect_keys(&full_key, val, keys);
+                        }
+                        _ => {
+                            keys.push(full_key);
                         }
                     }
                 }
             }
-            _ => {}
         }
     }
 
@@ -291,7 +288,7 @@ impl LocaleData {
         for key in keys.iter().skip(1) {
             if let Some(value) = current {
                 if let Some(mapping) = value.as_mapping() {
-                    current = mapping.get(&serde_yaml::Value::String(key.to_string()));
+                    current = mapping.get(serde_yaml::Value::String(key.to_string()));
                 } else {
                     return None;
                 }
@@ -137,18 +137,18 @@ impl TemplateParser {
             self.position += end + 2; // Skip "}}"
             
             // Parse different expression types
-            if expr.starts_with("#if ") {
-                self.parse_if_block(&expr[4..])
-            } else if expr.starts_with("#each ") {
-                self.parse_each_block(&expr[6..])
-            } else if expr.starts_with("#switch ") {
-                self.parse_switch_block(&expr[8..])
-            } else if expr.starts_with("#if_locale ") {
-                self.parse_if_locale_block(&expr[11..])
-            } else if expr.starts_with("> ") {
-                self.parse_include_expression(&expr[2..])
-            } else if expr.starts_with("i18n ") {
-                self.parse_i18n_expression(&expr[5..])
+            if let Some(condition) = expr.strip_prefix("#if ") {
+                self.parse_if_block(condition)
+            } else if let Some(variable) = expr.strip_prefix("#each ") {
+                self.parse_each_block(variable)
+            } else if let Some(variable) = expr.strip_prefix("#switch ") {
+                self.parse_switch_block(variable)
+            } else if let Some(locale_expr) = expr.strip_prefix("#if_locale ") {
+                self.parse_if_locale_block(locale_expr)
+            } else if let Some(include) = expr.strip_prefix("> ") {
+                self.parse_include_expression(include)
+            } else if let Some(key) = expr.strip_prefix("i18n ") {
+                self.parse_i18n_expression(key)
             } else if self.looks_like_helper_function(&expr) {
                 self.parse_helper_expression(&expr)
             } else if expr.contains('.') {
@@ -295,7 +295,7 @@ impl TemplateParser {
         let mut key = String::new();
         if chars.peek() == Some(&'"') {
             chars.next(); // skip opening quote
-            while let Some(ch) = chars.next() {
+            for ch in chars.by_ref() {
                 if ch == '"' {
                     break;
                 }
@@ -344,7 +344,7 @@ impl TemplateParser {
                 // Parenthesized expression - find matching closing paren
                 chars.next(); // skip opening paren
                 let mut paren_count = 1;
-                while let Some(ch) = chars.next() {
+                for ch in chars.by_ref() {
                     if ch == '(' {
                         paren_count += 1;
                     } else if ch == ')' {
@@ -358,7 +358,7 @@ impl TemplateParser {
             } else if chars.peek() == Some(&'"') {
                 // Quoted string
                 chars.next(); // skip opening quote
-                while let Some(ch) = chars.next() {
+                for ch in chars.by_ref() {
                     if ch == '"' {
                         break;
                     }
@@ -455,6 +455,12 @@ pub struct TemplateExecutor {
     include_stack: Vec<String>, // For circular reference detection
 }
 
+impl Default for TemplateExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TemplateExecutor {
     pub fn new() -> Self {
         Self {
@@ -481,10 +487,7 @@ impl TemplateExecutor {
         let mut result = String::new();
         
         for node in nodes {
-            match self.render_node(node, vars)? {
-                Some(content) => result.push_str(&content),
-                None => {}
-            }
+            if let Some(content) = self.render_node(node, vars)? { result.push_str(&content) }
         }
         
         Ok(result)
@@ -650,7 +653,7 @@ impl TemplateExecutor {
                 let mut parser = TemplateParser::new(&format!("{{{{{}}}}}", param_value));
                 if let Ok(nodes) = parser.parse() {
                     if let Some(TemplateNode::Helper { name, args, params: helper_params }) = nodes.first() {
-                        if let Ok(Some(result)) = self.render_helper(&name, &args, &helper_params, vars) {
+                        if let Ok(Some(result)) = self.render_helper(name, args, helper_params, vars) {
                             let final_result = result;
                             interpolation_vars.insert(param_name.clone(), serde_json::Value::String(final_result));
                         } else {
@@ -862,8 +865,13 @@ impl TemplateExecutor {
 }
 
 /// A compiled template ready for fast rendering with variables.
-#[derive(Debug, Clone)]
+///
+/// Serializes as its source, locale and accumulated variables, and is compiled again
+/// when deserialized. Locale files are not included; apply them again with `with_locale()`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(into = "TemplateRecord", try_from = "TemplateRecord")]
 pub struct PromptTemplate {
+    source: String,
     frontmatter: Option<TemplateFrontmatter>,
     ast: Vec<TemplateNode>,
     default_variables: HashMap<String, serde_json::Value>,
@@ -884,6 +892,44 @@ pub struct TemplateFrontmatter {
     pub i18n_key: Option<String>,
     #[serde(default)]
     pub includes: Vec<String>,
+    /// Literal text, such as a saved conversation: nothing in it is rendered.
+    #[serde(default)]
+    pub transcript: bool,
+}
+
+/// The serialized form of a [`PromptTemplate`].
+#[derive(Deserialize, Serialize)]
+struct TemplateRecord {
+    source: String,
+    #[serde(default = "default_locale")]
+    locale: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    variables: HashMap<String, serde_json::Value>,
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+impl From<PromptTemplate> for TemplateRecord {
+    fn from(template: PromptTemplate) -> Self {
+        Self {
+            source: template.source,
+            locale: template.current_locale,
+            variables: template.accumulated_variables,
+        }
+    }
+}
+
+impl TryFrom<TemplateRecord> for PromptTemplate {
+    type Error = Error;
+
+    fn try_from(record: TemplateRecord) -> Result<Self> {
+        let mut template = Self::from_content(&record.source)?;
+        template.current_locale = record.locale;
+        template.accumulated_variables = record.variables;
+        Ok(template)
+    }
 }
 
 impl PromptTemplate {
@@ -903,8 +949,11 @@ impl PromptTemplate {
         let (frontmatter, template_content) = Self::parse_frontmatter(content)?;
         
         // Parse template content into AST
-        let mut parser = TemplateParser::new(&template_content);
-        let ast = parser.parse()?;
+        let ast = if frontmatter.as_ref().is_some_and(|fm| fm.transcript) {
+            vec![TemplateNode::Text(template_content.clone())]
+        } else {
+            TemplateParser::new(&template_content).parse()?
+        };
         
         // Extract default variables from frontmatter
         let mut default_variables = HashMap::new();
@@ -920,6 +969,7 @@ impl PromptTemplate {
         }
 
         Ok(Self {
+            source: content.to_string(),
             frontmatter,
             ast,
             default_variables,
@@ -953,6 +1003,16 @@ impl PromptTemplate {
         Ok(self)
     }
 
+    /// Whether the frontmatter marks the template as a literal transcript.
+    pub fn is_transcript(&self) -> bool {
+        self.frontmatter.as_ref().is_some_and(|fm| fm.transcript)
+    }
+
+    /// The source the template was compiled from, including any frontmatter.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
     /// Get the list of required variables that must be provided at render time.
     pub fn required_variables(&self) -> &[String] {
         &self.required_variables
@@ -1135,39 +1195,39 @@ impl TemplateSet {
         if let Ok(entries) = fs::read_dir(&base_path) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                    if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                        let mut template = PromptTemplate::load(&path)?;
-                        
-                        // Auto-configure locale if locale paths were detected
-                        if !auto_locale_paths.is_empty() {
-                            template = template.with_locale("en", &locale_path_refs)?;
-                        }
-                        
-                        templates.insert(name.to_string(), template);
+                if path.extension().and_then(|s| s.to_str()) == Some("md")
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    let mut template = PromptTemplate::load(&path)?;
+                    
+                    // Auto-configure locale if locale paths were detected
+                    if !auto_locale_paths.is_empty() {
+                        template = template.with_locale("en", &locale_path_refs)?;
                     }
+                    
+                    templates.insert(name.to_string(), template);
                 }
             }
         }
 
         // Load conversation templates from conversations/ subdirectory
         let conversations_dir = base_path.join("conversations");
-        if conversations_dir.exists() {
-            if let Ok(entries) = fs::read_dir(&conversations_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                            let mut conversation = ConversationTemplate::load(&path)?;
-                            
-                            // Auto-configure locale for conversations too
-                            if !auto_locale_paths.is_empty() {
-                                conversation = conversation.with_locale("en", &locale_path_refs)?;
-                            }
-                            
-                            conversations.insert(name.to_string(), conversation);
-                        }
+        if conversations_dir.exists()
+            && let Ok(entries) = fs::read_dir(&conversations_dir)
+        {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) == Some("md")
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    let mut conversation = ConversationTemplate::load(&path)?;
+                    
+                    // Auto-configure locale for conversations too
+                    if !auto_locale_paths.is_empty() {
+                        conversation = conversation.with_locale("en", &locale_path_refs)?;
                     }
+                    
+                    conversations.insert(name.to_string(), conversation);
                 }
             }
         }
@@ -1185,11 +1245,11 @@ impl TemplateSet {
         self.current_locale = locale.to_string();
         
         // Apply locale to all templates
-        for (_, template) in &mut self.templates {
+        for template in self.templates.values_mut() {
             *template = template.clone().with_locale(locale, locale_paths)?;
         }
         
-        for (_, conversation) in &mut self.conversations {
+        for conversation in self.conversations.values_mut() {
             *conversation = conversation.clone().with_locale(locale, locale_paths)?;
         }
         
@@ -1262,18 +1322,20 @@ impl TemplateSet {
         
         // Check for locales/ subdirectory in the template directory
         let locales_dir = base_path.join("locales");
-        if locales_dir.exists() && locales_dir.is_dir() {
-            if let Some(path_str) = locales_dir.to_str() {
-                locale_paths.push(path_str.to_string());
-            }
+        if locales_dir.exists()
+            && locales_dir.is_dir()
+            && let Some(path_str) = locales_dir.to_str()
+        {
+            locale_paths.push(path_str.to_string());
         }
         
         // Also check for ../locales (parent directory pattern)
         let parent_locales = base_path.join("../locales");
-        if parent_locales.exists() && parent_locales.is_dir() {
-            if let Some(path_str) = parent_locales.to_str() {
-                locale_paths.push(path_str.to_string());
-            }
+        if parent_locales.exists()
+            && parent_locales.is_dir()
+            && let Some(path_str) = parent_locales.to_str()
+        {
+            locale_paths.push(path_str.to_string());
         }
         
         locale_paths
@@ -1375,41 +1437,41 @@ impl TemplateSetBuilder {
         if let Ok(entries) = fs::read_dir(directory) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                    if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                        let mut template = PromptTemplate::load(&path)?;
-                        
-                        // Configure locale if paths are available
-                        if !locale_paths.is_empty() {
-                            let locale_path_refs: Vec<&str> = locale_paths.iter().map(|s| s.as_str()).collect();
-                            template = template.with_locale(&self.default_locale, &locale_path_refs)?;
-                        }
-                        
-                        templates.insert(name.to_string(), template);
+                if path.extension().and_then(|s| s.to_str()) == Some("md")
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    let mut template = PromptTemplate::load(&path)?;
+                    
+                    // Configure locale if paths are available
+                    if !locale_paths.is_empty() {
+                        let locale_path_refs: Vec<&str> = locale_paths.iter().map(|s| s.as_str()).collect();
+                        template = template.with_locale(&self.default_locale, &locale_path_refs)?;
                     }
+                    
+                    templates.insert(name.to_string(), template);
                 }
             }
         }
 
         // Load conversation templates
         let conversations_dir = directory.join("conversations");
-        if conversations_dir.exists() {
-            if let Ok(entries) = fs::read_dir(&conversations_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                            let mut conversation = ConversationTemplate::load(&path)?;
-                            
-                            // Configure locale for conversations
-                            if !locale_paths.is_empty() {
-                                let locale_path_refs: Vec<&str> = locale_paths.iter().map(|s| s.as_str()).collect();
-                                conversation = conversation.with_locale(&self.default_locale, &locale_path_refs)?;
-                            }
-                            
-                            conversations.insert(name.to_string(), conversation);
-                        }
+        if conversations_dir.exists()
+            && let Ok(entries) = fs::read_dir(&conversations_dir)
+        {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) == Some("md")
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    let mut conversation = ConversationTemplate::load(&path)?;
+                    
+                    // Configure locale for conversations
+                    if !locale_paths.is_empty() {
+                        let locale_path_refs: Vec<&str> = locale_paths.iter().map(|s| s.as_str()).collect();
+                        conversation = conversation.with_locale(&self.default_locale, &locale_path_refs)?;
                     }
+                    
+                    conversations.insert(name.to_string(), conversation);
                 }
             }
         }
@@ -1463,6 +1525,10 @@ impl ConversationTemplate {
     }
 
     fn parse_conversation_content(&self, content: &str) -> Result<Messages> {
+        if self.template.is_transcript() {
+            return crate::prompt::conversation::parse_transcript(content);
+        }
+        
         let mut messages = Messages::new();
         let lines: Vec<&str> = content.lines().collect();
         
@@ -1569,6 +1635,21 @@ Hello {{name}}!"#;
         }
     }
 
+    #[test]
+    fn test_serde_round_trip() {
+        let content = "---\nvariables:\n  greeting: \"Hello\"\n---\n{{greeting}} {{name}}!";
+        let template = PromptTemplate::from_content(content).unwrap().var("name", "Ada");
+
+        let json = serde_json::to_value(&template).unwrap();
+        assert_eq!(json, json!({"source": content, "locale": "en", "variables": {"name": "Ada"}}));
+
+        let restored: PromptTemplate = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.render_with_vars().unwrap().trim(), "Hello Ada!");
+
+        let invalid = serde_json::from_value::<PromptTemplate>(json!({"source": "---\nvariables: [\n---\nHi"}));
+        assert!(invalid.is_err());
+    }
+
     #[test]
     fn test_nested_variables() {
         let content = "Contact {{user.name}} at {{user.email}}";
@@ -2182,7 +2263,19 @@ English content
         assert!(result.contains("$29.99") || result.contains("29.99") || result.contains("USD"));
     }
 
+    #[test]
+    fn test_backslash_before_expression_is_text() {
+        let template = PromptTemplate::from_content(r"Path: C:\{{dir}}").unwrap();
+        assert_eq!(template.render(&json!({"dir": "temp"})).unwrap(), r"Path: C:\temp");
+
+        // Transcripts are literal
+        let transcript = PromptTemplate::from_content("---\ntranscript: true\n---\n{{dir}} {{#if}}").unwrap();
+        assert!(transcript.is_transcript());
+        assert_eq!(transcript.render(&json!({"dir": "temp"})).unwrap(), "\n{{dir}} {{#if}}");
+    }
+
     #[test]  
+    #[allow(clippy::approx_constant)]
     fn test_format_number_helper_decimal() {
         let content = r#"Value: {{format_number value style="decimal" precision="2"}}"#;
         
@@ -2357,6 +2450,7 @@ Status: Inactive
         use schemars::JsonSchema;
         
         #[derive(Clone, Debug, JsonSchema, Deserialize)]
+        #[allow(dead_code)]
         struct TestResponse {
             message: String,
         }
@@ -1,4 +1,4 @@
-use crate::{error::Result, types::{CreateResponse, Output}};
+use crate::{error::Result, stream::{ResponseStream, StreamEvent}, types::{CreateResponse, Output}};
 use std::future::Future;
 
 pub trait Provider: Send + Sync {
@@ -6,6 +6,17 @@ pub trait Provider: Send + Sync {
     
     fn create_response(&self, request: &CreateResponse) -> impl Future<Output = Result<Vec<Output>>> + Send;
     
+    /// Create a response delivered as a stream of events.
+    /// 
+    /// The default implementation waits for the full response and yields it as a
+    /// single `StreamEvent::Completed`, so providers without streaming support still work.
+    fn create_response_stream(&self, request: &CreateResponse) -> impl Future<Output = Result<ResponseStream>> + Send {
+        async move {
+            let output = self.create_response(request).await?;
+            Ok(ResponseStream::from_events([StreamEvent::Completed(output)]))
+        }
+    }
+    
     fn name(&self) -> &'static str;
 }
 
@@ -2,6 +2,7 @@ use std::env;
 use crate::{
     error::{Error, Result},
     provider::{Provider, ProviderBuilder},
+    stream::ResponseStream,
     types::{CreateResponse, Output, Response as ApiResponse},
 };
 use reqwest::Client as HttpClient;
@@ -71,6 +72,17 @@ impl Provider for OpenAIProvider {
         }
     }
     
+    async fn create_response_stream(&self, create_response: &CreateResponse) -> Result<ResponseStream> {
+        let response = self.http_client
+            .post(self.url.clone())
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .json(&create_response)
+            .send()
+            .await?;
+        
+        ResponseStream::from_http(response).await
+    }
+    
     fn name(&self) -> &'static str {
         "openai"
     }
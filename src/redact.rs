@@ -0,0 +1,548 @@
+//! Masking personal data before prompts leave the process.
+//!
+//! A [`Redactor`] runs [`Detector`]s over text and replaces every match with a
+//! placeholder such as `[EMAIL_1]`. The same value always gets the same placeholder,
+//! so the model can still refer to it. The [`RedactionMap`] records the originals
+//! and restores them in response text and tool-call arguments. Text that already
+//! looks like a placeholder is masked as well, so it is never mistaken for one.
+//!
+//! Use it on a conversation with [`Messages::redact`](crate::Messages::redact), or
+//! wrap a provider in a [`RedactingProvider`] to redact every request and restore
+//! every response, including the calls of a tool loop.
+//!
+//! ```
+//! use responses::redact::Redactor;
+//! use responses::Messages;
+//!
+//! let redactor = Redactor::standard()
+//!     .pattern("employee_id", r"\bEMP-\d{6}\b")
+//!     .unwrap();
+//!
+//! let conversation = Messages::new()
+//!     .user("I'm jane@example.com, employee EMP-004211. Call me on +1 (555) 123-4567.");
+//!
+//! let (redacted, map) = conversation.redact(&redactor).unwrap();
+//! let sent = redacted.to_chat_completions().unwrap();
+//! assert_eq!(
+//!     sent[0]["content"],
+//!     "I'm [EMAIL_1], employee [EMPLOYEE_ID_1]. Call me on [PHONE_1]."
+//! );
+//!
+//! assert_eq!(map.restore("Thanks, [EMAIL_1]!"), "Thanks, jane@example.com!");
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    provider::Provider,
+    refusal::Refusal,
+    response::Response,
+    types::{
+        CreateResponse, FunctionOutput, FunctionOutputContent, Input, InputMessage, Output, OutputMessageContent,
+    },
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap());
+
+static PHONE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{2,4}\)|\d{2,4})[ .-]?\d{3,4}[ .-]?\d{3,4}\b").unwrap()
+});
+
+static CARD_NUMBER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap());
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[[A-Z][A-Z0-9_]*_\d+\]").unwrap());
+
+/// Finds personal data in text.
+pub trait Detector: Send + Sync {
+    /// The kind of data found, used in placeholders: `email` becomes `[EMAIL_1]`.
+    fn kind(&self) -> &str;
+
+    /// The byte ranges of every match in `text`. Overlapping matches are allowed;
+    /// the redactor keeps the longest.
+    fn detect(&self, text: &str) -> Vec<Range<usize>>;
+}
+
+/// Email addresses.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmailDetector;
+
+impl Detector for EmailDetector {
+    fn kind(&self) -> &str {
+        "email"
+    }
+
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        EMAIL.find_iter(text).map(|m| m.range()).collect()
+    }
+}
+
+/// Phone numbers with 7 to 15 digits, with an optional country code and separators.
+///
+/// Errs on the side of masking, so long order or account numbers may match too.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhoneDetector;
+
+impl Detector for PhoneDetector {
+    fn kind(&self) -> &str {
+        "phone"
+    }
+
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        PHONE
+            .find_iter(text)
+            .filter(|m| {
+                // Not the tail of a longer word or number
+                let preceded = text[..m.start()].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '+');
+                let digits = m.as_str().chars().filter(char::is_ascii_digit).count();
+                !preceded && (7..=15).contains(&digits)
+            })
+            .map(|m| m.range())
+            .collect()
+    }
+}
+
+/// Payment card numbers of 13 to 19 digits that pass the Luhn check.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CardNumberDetector;
+
+impl Detector for CardNumberDetector {
+    fn kind(&self) -> &str {
+        "card_number"
+    }
+
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        CARD_NUMBER
+            .find_iter(text)
+            .filter(|m| luhn(m.as_str()))
+            .map(|m| m.range())
+            .collect()
+    }
+}
+
+/// Matches of a regular expression, for custom entities such as employee or patient ids.
+#[derive(Clone, Debug)]
+pub struct RegexDetector {
+    kind: String,
+    regex: Regex,
+}
+
+impl RegexDetector {
+    pub fn new<S: Into<String>>(kind: S, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|source| Error::InvalidRedactionPattern {
+            pattern: pattern.to_string(),
+            source,
+        })?;
+        Ok(Self { kind: kind.into(), regex })
+    }
+}
+
+impl Detector for RegexDetector {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex.find_iter(text).map(|m| m.range()).collect()
+    }
+}
+
+/// Replaces personal data with reversible placeholders.
+#[derive(Clone, Default)]
+pub struct Redactor {
+    detectors: Vec<Arc<dyn Detector>>,
+}
+
+impl std::fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kinds: Vec<_> = self.detectors.iter().map(|detector| detector.kind()).collect();
+        f.debug_struct("Redactor").field("detectors", &kinds).finish()
+    }
+}
+
+impl Redactor {
+    /// A redactor without detectors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A redactor for emails, phone numbers and card numbers.
+    pub fn standard() -> Self {
+        Self::new().detector(EmailDetector).detector(CardNumberDetector).detector(PhoneDetector)
+    }
+
+    /// Add a detector. When matches of the same length overlap, earlier detectors win.
+    pub fn detector<D: Detector + 'static>(mut self, detector: D) -> Self {
+        self.detectors.push(Arc::new(detector));
+        self
+    }
+
+    /// Add a detector for a custom entity matching `pattern`.
+    pub fn pattern<S: Into<String>>(self, kind: S, pattern: &str) -> Result<Self> {
+        Ok(self.detector(RegexDetector::new(kind, pattern)?))
+    }
+
+    /// Replace the matches in `text` with placeholders recorded in `map`.
+    ///
+    /// Text shaped like a placeholder, such as a literal `[EMAIL_1]`, gets a
+    /// `[PLACEHOLDER_n]` of its own so it is restored as written.
+    pub fn redact(&self, text: &str, map: &mut RedactionMap) -> String {
+        // `None` marks existing placeholder text, which wins ties with the detectors
+        let mut matches: Vec<(Range<usize>, Option<usize>)> = PLACEHOLDER
+            .find_iter(text)
+            .map(|m| (m.range(), None))
+            .chain(self.detectors.iter().enumerate().flat_map(|(index, detector)| {
+                detector.detect(text).into_iter().map(move |range| (range, Some(index)))
+            }))
+            .filter(|(range, _)| !range.is_empty())
+            .collect();
+
+        // Longest match first at each position, then detector order
+        matches.sort_by_key(|(range, index)| (range.start, std::cmp::Reverse(range.end), *index));
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut position = 0;
+        for (range, index) in matches {
+            if range.start < position {
+                continue;
+            }
+            redacted.push_str(&text[position..range.start]);
+            let kind = index.map_or("placeholder", |index| self.detectors[index].kind());
+            redacted.push_str(&map.placeholder(kind, &text[range.clone()]));
+            position = range.end;
+        }
+        redacted.push_str(&text[position..]);
+        redacted
+    }
+
+    /// Redact the text of every input: messages, function call arguments and outputs.
+    ///
+    /// Templates are kept as they are; render them first, as [`Messages::redact`](crate::Messages::redact) does.
+    pub fn redact_inputs(&self, inputs: &[Input], map: &mut RedactionMap) -> Vec<Input> {
+        inputs
+            .iter()
+            .map(|input| {
+                let mut input = input.clone();
+                match &mut input {
+                    Input::Message(message) => message.content = self.redact(&message.content, map),
+                    Input::FunctionCall(call) => call.arguments = self.redact(&call.arguments, map),
+                    Input::FunctionCallOutput(output) => match &mut output.output {
+                        FunctionOutput::Text(text) => *text = self.redact(text, map),
+                        FunctionOutput::Content(items) => {
+                            for item in items {
+                                if let FunctionOutputContent::InputText { text } = item {
+                                    *text = self.redact(text, map);
+                                }
+                            }
+                        }
+                    },
+                    Input::Template(_) => {}
+                }
+                input
+            })
+            .collect()
+    }
+}
+
+/// The original values behind the placeholders of a redaction.
+#[derive(Clone, Debug, Default)]
+pub struct RedactionMap {
+    originals: HashMap<String, String>,
+    placeholders: HashMap<(String, String), String>,
+    counts: HashMap<String, usize>,
+}
+
+impl RedactionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of values redacted.
+    pub fn len(&self) -> usize {
+        self.originals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    /// The original value behind `placeholder`, such as `[EMAIL_1]`.
+    pub fn original(&self, placeholder: &str) -> Option<&str> {
+        self.originals.get(placeholder).map(String::as_str)
+    }
+
+    /// The placeholder for `value`, reusing the one given to the same value before.
+    fn placeholder(&mut self, kind: &str, value: &str) -> String {
+        let key = (kind.to_string(), value.to_string());
+        if let Some(placeholder) = self.placeholders.get(&key) {
+            return placeholder.clone();
+        }
+
+        let mut label: String = kind
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+
+        // Placeholders must start with a letter to be found again
+        if !label.starts_with(|c: char| c.is_ascii_uppercase()) {
+            label.insert_str(0, "PII_");
+        }
+        let count = self.counts.entry(label.clone()).or_default();
+        *count += 1;
+        let placeholder = format!("[{}_{}]", label, count);
+
+        self.originals.insert(placeholder.clone(), value.to_string());
+        self.placeholders.insert(key, placeholder.clone());
+        placeholder
+    }
+
+    /// Put the original values back into `text`. Unknown placeholders are left as they are.
+    pub fn restore(&self, text: &str) -> String {
+        self.replace(text, |original| original.to_string())
+    }
+
+    /// Put the original values back into JSON, escaping them inside strings.
+    ///
+    /// Text that is not valid JSON is restored as plain text.
+    pub fn restore_json(&self, json: &str) -> String {
+        if serde_json::from_str::<Value>(json).is_err() {
+            return self.restore(json);
+        }
+        self.replace(json, |original| {
+            let quoted = Value::String(original.to_string()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        })
+    }
+
+    /// Put the original values back into a response: its text, structured value,
+    /// refusal and function call arguments.
+    pub fn restore_response<T: Serialize + DeserializeOwned>(&self, response: Response<T>) -> Result<Response<T>> {
+        let message = match response.message {
+            Some(Ok(message)) => {
+                let mut value = serde_json::to_value(message)?;
+                self.restore_value(&mut value);
+                Some(Ok(serde_json::from_value(value)?))
+            }
+            Some(Err(refusal)) => Some(Err(Refusal::from(self.restore(&String::from(refusal))))),
+            None => None,
+        };
+
+        let function_calls = response
+            .function_calls
+            .into_iter()
+            .map(|mut call| {
+                call.arguments = self.restore_json(&call.arguments);
+                call
+            })
+            .collect();
+
+        Ok(Response { message, function_calls })
+    }
+
+    /// Put the original values back into provider output.
+    pub fn restore_outputs(&self, outputs: Vec<Output>) -> Vec<Output> {
+        outputs
+            .into_iter()
+            .map(|mut output| {
+                match &mut output {
+                    Output::Message(message) => {
+                        for content in &mut message.content {
+                            match content {
+                                // Structured output is JSON, and must stay valid
+                                OutputMessageContent::OutputText(text) => text.text = self.restore_json(&text.text),
+                                OutputMessageContent::Refusal(refusal) => refusal.refusal = self.restore(&refusal.refusal),
+                            }
+                        }
+                    }
+                    Output::FunctionCall(call) => call.arguments = self.restore_json(&call.arguments),
+                }
+                output
+            })
+            .collect()
+    }
+
+    fn restore_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.restore(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.restore_value(item)),
+            Value::Object(fields) => fields.values_mut().for_each(|field| self.restore_value(field)),
+            _ => {}
+        }
+    }
+
+    fn replace(&self, text: &str, encode: impl Fn(&str) -> String) -> String {
+        if self.originals.is_empty() {
+            return text.to_string();
+        }
+        PLACEHOLDER
+            .replace_all(text, |captures: &regex::Captures| match self.originals.get(&captures[0]) {
+                Some(original) => encode(original),
+                None => captures[0].to_string(),
+            })
+            .into_owned()
+    }
+}
+
+/// A provider that redacts every request and restores every response.
+///
+/// Each request gets its own [`RedactionMap`], so placeholders are consistent within
+/// the request. Streaming requests are answered in one piece, since a placeholder
+/// could be split across deltas. Templates are rendered before redaction, and a template
+/// that fails to render fails the request instead of being sent.
+///
+/// ```no_run
+/// # use responses::{azure, Client};
+/// # use responses::provider::ProviderBuilder;
+/// use responses::redact::{RedactingProvider, Redactor};
+///
+/// # fn main() -> responses::Result<()> {
+/// let provider = azure().from_env()?.build()?;
+/// let client = Client::new(RedactingProvider::new(provider, Redactor::standard()));
+/// # Ok(())
+/// # }
+/// ```
+pub struct RedactingProvider<P: Provider> {
+    inner: P,
+    redactor: Redactor,
+}
+
+impl<P: Provider> RedactingProvider<P> {
+    pub fn new(inner: P, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+
+    /// The wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
+}
+
+impl<P: Provider> Provider for RedactingProvider<P> {
+    type Config = P::Config;
+
+    async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+        let mut map = RedactionMap::new();
+        let mut request = request.clone();
+        if let Some(inputs) = &request.input {
+            request.input = Some(self.redactor.redact_inputs(&render_templates(inputs)?, &mut map));
+        }
+
+        let outputs = self.inner.create_response(&request).await?;
+        Ok(map.restore_outputs(outputs))
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// The inputs with every template rendered, so no unredacted template text is sent.
+fn render_templates(inputs: &[Input]) -> Result<Vec<Input>> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| match input {
+            Input::Template(template_input) => {
+                let content = template_input
+                    .template
+                    .render_with_vars()
+                    .map_err(|e| crate::messages::rendering_error(index, &template_input.template, e))?;
+                Ok(Input::Message(InputMessage {
+                    role: template_input.role,
+                    content,
+                }))
+            }
+            input => Ok(input.clone()),
+        })
+        .collect()
+}
+
+/// Whether the digits of `number` pass the Luhn checksum.
+fn luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_detectors() {
+        let redactor = Redactor::standard();
+        let mut map = RedactionMap::new();
+
+        let text = "Card 4111 1111 1111 1111, invoice 1234567890123. \
+                    Phones +44 20 7946 0958 and 555-123-4567. Born 2024-01-15, order 42. \
+                    Mail a.b+tag@mail.example.co.uk or a.b+tag@mail.example.co.uk.";
+        let redacted = redactor.redact(text, &mut map);
+
+        assert_eq!(
+            redacted,
+            "Card [CARD_NUMBER_1], invoice 1234567890123. \
+             Phones [PHONE_1] and [PHONE_2]. Born 2024-01-15, order 42. \
+             Mail [EMAIL_1] or [EMAIL_1]."
+        );
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.restore(&redacted), text);
+    }
+
+    #[test]
+    fn test_restoring_json_escapes_values() {
+        let redactor = Redactor::new().pattern("name", r#"Jane "JJ" Doe"#).unwrap();
+        let mut map = RedactionMap::new();
+        assert_eq!(redactor.redact(r#"Jane "JJ" Doe"#, &mut map), "[NAME_1]");
+
+        assert_eq!(map.restore_json(r#"{"to": "[NAME_1]"}"#), r#"{"to": "Jane \"JJ\" Doe"}"#);
+        assert_eq!(map.restore_json("Hi [NAME_1] [NAME_2]"), r#"Hi Jane "JJ" Doe [NAME_2]"#);
+        assert!(matches!(Redactor::new().pattern("bad", "("), Err(Error::InvalidRedactionPattern { .. })));
+    }
+
+    #[test]
+    fn test_every_kind_gets_a_restorable_placeholder() {
+        let redactor = Redactor::new()
+            .pattern("1099_id", r"\b\d{2}-\d{7}\b")
+            .unwrap()
+            .pattern("名前", "Jane")
+            .unwrap()
+            .pattern("", "Doe")
+            .unwrap();
+        let mut map = RedactionMap::new();
+
+        let text = "Jane Doe, form 12-3456789";
+        let redacted = redactor.redact(text, &mut map);
+        assert_eq!(redacted, "[PII____1] [PII__1], form [PII_1099_ID_1]");
+        assert_eq!(map.restore(&redacted), text);
+    }
+
+    #[test]
+    fn test_existing_placeholder_text_is_masked() {
+        let redactor = Redactor::standard();
+        let mut map = RedactionMap::new();
+
+        let text = "Write [EMAIL_1] as a template slot for jane@example.com";
+        let redacted = redactor.redact(text, &mut map);
+        assert_eq!(redacted, "Write [PLACEHOLDER_1] as a template slot for [EMAIL_1]");
+        assert_eq!(map.restore(&redacted), text);
+        assert_eq!(map.restore("[EMAIL_1] [PLACEHOLDER_1]"), "jane@example.com [EMAIL_1]");
+    }
+}
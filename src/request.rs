@@ -1,8 +1,13 @@
 use crate::{
+    cancel::CancellationToken,
+    context::ContextPolicy,
     error::Result,
     messages::Messages,
     provider::Provider,
     response::Response,
+    stream::StructuredStream,
+    tool_loop::ToolLoop,
+    toolbox::Toolbox,
     types::{Input, InputMessage, Role, Tool, ToolChoice},
     Options,
 };
@@ -17,6 +22,8 @@ pub struct TextRequestBuilder<'a, P: Provider> {
     accumulated_variables: HashMap<String, serde_json::Value>,
     current_locale: Option<String>,
     locale_paths: Vec<String>,
+    /// A template of `.messages()` that failed to render, reported on send.
+    rendering_error: Option<crate::error::Error>,
 }
 
 impl<'a, P: Provider> TextRequestBuilder<'a, P> {
@@ -27,6 +34,7 @@ impl<'a, P: Provider> TextRequestBuilder<'a, P> {
             accumulated_variables: HashMap::new(),
             current_locale: None,
             locale_paths: Vec::new(),
+            rendering_error: None,
         }
     }
     
@@ -118,10 +126,41 @@ impl<'a, P: Provider> TextRequestBuilder<'a, P> {
         self
     }
     
+    /// Abort the request with `Error::Timeout` if it has not completed within `timeout`.
+    /// 
+    /// The deadline covers the whole request, including every event of a stream.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+    
+    /// Abort the request with `Error::Cancelled` when `token` is cancelled.
+    /// 
+    /// Aborting closes the connection to the provider, so generation stops
+    /// once the caller no longer needs the result.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.options.cancel_token = Some(token);
+        self
+    }
+    
+    /// Trim the oldest turns of the input before sending when it exceeds the context budget.
+    /// 
+    /// Applied to every request of a tool loop, so long loops keep fitting too.
+    pub fn context_policy(mut self, policy: ContextPolicy) -> Self {
+        self.options.context_policy = Some(policy);
+        self
+    }
+    
     /// Use the provided messages as the conversation history.
     /// This replaces any existing messages.
+    ///
+    /// Templates are rendered with the variables of `messages`; one that fails is
+    /// reported as `Error::MessageRendering` when the request is sent.
     pub fn messages(mut self, messages: Messages) -> Self {
-        self.options.input = Some(messages.into_inputs());
+        match messages.try_render_inputs() {
+            Ok(inputs) => self.options.input = Some(inputs),
+            Err(e) => self.rendering_error = Some(e),
+        }
         self
     }
     
@@ -241,16 +280,39 @@ impl<'a, P: Provider> TextRequestBuilder<'a, P> {
     
 
     pub async fn send(self) -> Result<Response<String>> {
-        let Self { client, options, accumulated_variables, current_locale, locale_paths: _ } = self;
+        let (client, options) = self.into_options()?;
+        
+        client.text_with_options(options).await
+    }
+    
+    /// Send the request with the tools of `toolbox` and keep running the calls the model
+    /// makes until it answers, or until a call needs approval.
+    /// 
+    /// See [`ToolLoop`] for resuming after approval.
+    pub async fn run_tools(self, toolbox: &Toolbox) -> Result<ToolLoop> {
+        let (client, mut options) = self.into_options()?;
+        options.tools.get_or_insert_default().extend(toolbox.tools());
+        
+        crate::tool_loop::run(client, toolbox.clone(), options, 0).await
+    }
+    
+    /// The options to send, with templates rendered.
+    fn into_options(self) -> Result<(&'a crate::Client<P>, Options)> {
+        let Self { client, options, accumulated_variables, current_locale, locale_paths: _, rendering_error } = self;
+        if let Some(e) = rendering_error {
+            return Err(e);
+        }
         let mut rendered_options = options;
         
         // Render templates if any exist
         if let Some(ref inputs) = rendered_options.input {
             let mut rendered_inputs = Vec::new();
             
-            for input in inputs {
+            for (index, input) in inputs.iter().enumerate() {
                 match input {
-                    Input::Message(_msg) => rendered_inputs.push(input.clone()),
+                    Input::Message(_) | Input::FunctionCall(_) | Input::FunctionCallOutput(_) => {
+                        rendered_inputs.push(input.clone())
+                    }
                     Input::Template(template_input) => {
                         // Apply accumulated variables and locale to template
                         let mut template = template_input.template.clone();
@@ -262,15 +324,17 @@ impl<'a, P: Provider> TextRequestBuilder<'a, P> {
                         
                         // Apply current locale if set
                         if let Some(ref locale) = current_locale {
-                            template = template.with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>())?;
+                            template = template.with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+                                .map_err(|e| crate::messages::rendering_error(index, &template_input.template, e))?;
                         }
                         
                         // Render template
-                        let content = template.render_with_vars()?;
+                        let content = template.render_with_vars()
+                            .map_err(|e| crate::messages::rendering_error(index, &template_input.template, e))?;
                         
                         // Create message from rendered content
                         rendered_inputs.push(Input::Message(InputMessage {
-                            role: template_input.role.clone(),
+                            role: template_input.role,
                             content,
                         }));
                     }
@@ -280,19 +344,22 @@ impl<'a, P: Provider> TextRequestBuilder<'a, P> {
             rendered_options.input = Some(rendered_inputs);
         }
         
-        client.text_with_options(rendered_options).await
+        Ok((client, rendered_options))
     }
 }
 
 pub struct StructuredRequestBuilder<'a, P: Provider, T> {
     client: &'a crate::Client<P>,
     name: String,
+    schema: Option<schemars::Schema>,
     options: Options,
     _phantom: std::marker::PhantomData<T>,
     // Fluent API support
     accumulated_variables: HashMap<String, serde_json::Value>,
     current_locale: Option<String>,
     locale_paths: Vec<String>,
+    /// A template of `.messages()` that failed to render, reported on send.
+    rendering_error: Option<crate::error::Error>,
 }
 
 impl<'a, P: Provider, T> StructuredRequestBuilder<'a, P, T> 
@@ -303,11 +370,13 @@ where
         Self {
             client,
             name,
+            schema: None,
             options: Options::default(),
             _phantom: std::marker::PhantomData,
             accumulated_variables: HashMap::new(),
             current_locale: None,
             locale_paths: Vec::new(),
+            rendering_error: None,
         }
     }
     
@@ -316,6 +385,12 @@ where
         self
     }
     
+    /// Use the given schema instead of the one derived from `T`.
+    pub fn with_schema(mut self, schema: schemars::Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+    
     /// Add a system message. Supports multiline strings seamlessly.
     pub fn system<S: Into<String>>(self, content: S) -> Self {
         let message = InputMessage {
@@ -378,10 +453,74 @@ where
         self
     }
     
+    /// Abort the request with `Error::Timeout` if it has not completed within `timeout`.
+    /// 
+    /// The deadline covers the whole request, including every event of a stream.
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+    
+    /// Abort the request with `Error::Cancelled` when `token` is cancelled.
+    /// 
+    /// Aborting closes the connection to the provider, so generation stops
+    /// once the caller no longer needs the result.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.options.cancel_token = Some(token);
+        self
+    }
+    
+    /// Trim the oldest turns of the input before sending when it exceeds the context budget.
+    /// 
+    /// Applied to every request of a tool loop, so long loops keep fitting too.
+    pub fn context_policy(mut self, policy: ContextPolicy) -> Self {
+        self.options.context_policy = Some(policy);
+        self
+    }
+    
+    /// Validate the returned JSON against the schema of `T` before deserializing.
+    /// 
+    /// When enabled, every violation is reported through `Error::SchemaValidation`
+    /// with the JSON pointer of the offending value, instead of serde's first error.
+    pub fn validate_schema(mut self, enabled: bool) -> Self {
+        self.options.validate_schema = enabled;
+        self
+    }
+    
+    /// Enable or disable strict schema adherence (enabled by default).
+    /// 
+    /// Disable strict mode for types whose schemas cannot satisfy the strict-mode
+    /// restrictions, such as maps or optional fields left out of `required`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = Some(strict);
+        self
+    }
+    
+    /// Set a description of the response format, used by the model to decide how to respond.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.options.description = Some(description.into());
+        self
+    }
+    
+    /// Request free-form JSON output (`json_object`) instead of a JSON schema.
+    /// 
+    /// Useful with `serde_json::Value` for exploratory prompts. The model must be
+    /// instructed to produce JSON somewhere in the conversation.
+    pub fn json_object(mut self) -> Self {
+        self.options.json_object = true;
+        self
+    }
+    
     /// Use the provided messages as the conversation history.
     /// This replaces any existing messages.
+    ///
+    /// Templates are rendered with the variables of `messages`; one that fails is
+    /// reported as `Error::MessageRendering` when the request is sent.
     pub fn messages(mut self, messages: Messages) -> Self {
-        self.options.input = Some(messages.into_inputs());
+        match messages.try_render_inputs() {
+            Ok(inputs) => self.options.input = Some(inputs),
+            Err(e) => self.rendering_error = Some(e),
+        }
         self
     }
     
@@ -501,16 +640,36 @@ where
     
 
     pub async fn send(self) -> Result<Response<T>> {
-        let Self { client, name, options, accumulated_variables, current_locale, .. } = self;
+        let (client, name, schema, options) = self.into_parts()?;
+        client.structure_with_schema_and_options(name, schema, options).await
+    }
+    
+    /// Send the request and stream the output.
+    /// 
+    /// The returned stream yields `PartialEvent::Partial` snapshots of the JSON
+    /// received so far, followed by `PartialEvent::Completed` with the final response.
+    pub async fn send_stream(self) -> Result<StructuredStream<T>> {
+        let (client, name, schema, options) = self.into_parts()?;
+        client.stream_structure_with_schema_and_options(name, schema, options).await
+    }
+    
+    /// Render templates and resolve the schema, returning everything needed to send.
+    fn into_parts(self) -> Result<(&'a crate::Client<P>, String, schemars::Schema, Options)> {
+        let Self { client, name, schema, options, accumulated_variables, current_locale, rendering_error, .. } = self;
+        if let Some(e) = rendering_error {
+            return Err(e);
+        }
         let mut rendered_options = options;
         
         // Render templates if any exist
         if let Some(ref inputs) = rendered_options.input {
             let mut rendered_inputs = Vec::new();
             
-            for input in inputs {
+            for (index, input) in inputs.iter().enumerate() {
                 match input {
-                    Input::Message(_msg) => rendered_inputs.push(input.clone()),
+                    Input::Message(_) | Input::FunctionCall(_) | Input::FunctionCallOutput(_) => {
+                        rendered_inputs.push(input.clone())
+                    }
                     Input::Template(template_input) => {
                         // Apply accumulated variables and locale to template
                         let mut template = template_input.template.clone();
@@ -522,15 +681,17 @@ where
                         
                         // Apply current locale if set
                         if let Some(ref locale) = current_locale {
-                            template = template.with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>())?;
+                            template = template.with_locale(locale, &self.locale_paths.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+                                .map_err(|e| crate::messages::rendering_error(index, &template_input.template, e))?;
                         }
                         
                         // Render template
-                        let content = template.render_with_vars()?;
+                        let content = template.render_with_vars()
+                            .map_err(|e| crate::messages::rendering_error(index, &template_input.template, e))?;
                         
                         // Create message from rendered content
                         rendered_inputs.push(Input::Message(InputMessage {
-                            role: template_input.role.clone(),
+                            role: template_input.role,
                             content,
                         }));
                     }
@@ -540,7 +701,8 @@ where
             rendered_options.input = Some(rendered_inputs);
         }
         
-        client.structure_with_name_and_options(name, rendered_options).await
+        let schema = schema.unwrap_or_else(crate::schema::from::<T>);
+        Ok((client, name, schema, rendered_options))
     }
 }
 
@@ -1,8 +1,11 @@
+use crate::error::{Error, Result};
 use schemars::{
     JsonSchema, Schema,
     generate::SchemaSettings,
-    transform::{RecursiveTransform, ReplaceConstValue},
+    transform::{RecursiveTransform, ReplaceConstValue, Transform},
 };
+use serde_json::{Value, json};
+use std::path::Path;
 
 pub fn from<T: ?Sized + JsonSchema>() -> Schema {
     SchemaSettings::default()
@@ -13,17 +16,120 @@ pub fn from<T: ?Sized + JsonSchema>() -> Schema {
         //
         .with_transform(ReplaceConstValue::default())
         //
-        .with_transform(RecursiveTransform(|schema: &mut Schema| {
-            if schema.get("properties").is_some() {
-                schema.insert("additionalProperties".to_owned(), false.into());
-            }
-
-            if let Some(one_of) = schema.remove("oneOf") {
-                schema.insert("anyOf".to_owned(), one_of);
-            }
-        }))
+        .with_transform(RecursiveTransform(strict))
         //
         .into_generator()
         //
         .into_root_schema_for::<T>()
 }
+
+/// Build a schema from a runtime JSON value, applying the same transforms as [`from`].
+///
+/// The root must be an object schema, as required by the Responses API.
+pub fn from_value(value: Value) -> Result<Schema> {
+    if !value.is_object() {
+        return Err(Error::InvalidSchema(
+            "Schema root must be a JSON object".to_string()
+        ));
+    }
+
+    let mut schema = Schema::try_from(value)
+        .map_err(|e| Error::InvalidSchema(e.to_string()))?;
+
+    schema.remove("$schema");
+
+    ReplaceConstValue::default().transform(&mut schema);
+
+    RecursiveTransform(strict).transform(&mut schema);
+
+    Ok(schema)
+}
+
+/// Load a schema from a `.json` file, applying the same transforms as [`from`].
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Schema> {
+    let path = path.as_ref();
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| Error::SchemaFileRead {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+
+    let value = serde_json::from_str(&content)
+        .map_err(|e| Error::InvalidSchema(format!("{}: {}", path.display(), e)))?;
+
+    from_value(value)
+}
+
+/// Make every property required, typing properties that were optional as nullable.
+///
+/// Strict mode rejects schemas with optional properties, e.g. fields with
+/// `#[serde(default)]` or `skip_serializing_if`.
+pub fn make_strict(schema: &mut Schema) {
+    RecursiveTransform(require_all).transform(schema);
+}
+
+fn require_all(schema: &mut Schema) {
+    let Some(Value::Object(properties)) = schema.get_mut("properties") else {
+        return;
+    };
+
+    let required: Vec<Value> = properties.keys().cloned().map(Value::String).collect();
+
+    let previously_required = match schema.get("required") {
+        Some(Value::Array(names)) => names.clone(),
+        _ => Vec::new(),
+    };
+
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            if !previously_required.contains(&Value::String(name.clone())) {
+                make_nullable(property);
+            }
+        }
+    }
+
+    schema.insert("required".to_owned(), Value::Array(required));
+}
+
+fn make_nullable(property: &mut Value) {
+    let Value::Object(object) = property else {
+        return;
+    };
+
+    let null = Value::String("null".to_owned());
+
+    match object.get_mut("type") {
+        Some(Value::String(kind)) if kind == "null" => {}
+        Some(Value::String(kind)) => {
+            let kind = Value::String(std::mem::take(kind));
+            object.insert("type".to_owned(), Value::Array(vec![kind, null]));
+        }
+        Some(Value::Array(kinds)) => {
+            if !kinds.contains(&null) {
+                kinds.push(null);
+            }
+        }
+        _ => {
+            let inner = std::mem::take(property);
+            *property = json!({ "anyOf": [inner, { "type": "null" }] });
+            return;
+        }
+    }
+
+    if let Some(Value::Array(values)) = object.get_mut("enum")
+        && !values.contains(&Value::Null)
+    {
+        values.push(Value::Null);
+    }
+}
+
+fn strict(schema: &mut Schema) {
+    if schema.get("properties").is_some() {
+        schema.insert("additionalProperties".to_owned(), false.into());
+    }
+
+    if let Some(one_of) = schema.remove("oneOf") {
+        schema.insert("anyOf".to_owned(), one_of);
+    }
+}
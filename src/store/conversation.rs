@@ -0,0 +1,73 @@
+//! Conversations that write through to a store.
+
+use super::ConversationStore;
+use crate::{error::Result, messages::Messages};
+use std::sync::Arc;
+
+/// A [`Messages`] history for one session, kept in sync with a [`ConversationStore`].
+///
+/// Every change is written to the store before it is applied locally, so the
+/// local history never holds inputs the store failed to save.
+#[derive(Debug)]
+pub struct Conversation<S: ConversationStore> {
+    store: Arc<S>,
+    session_id: String,
+    messages: Messages,
+}
+
+impl<S: ConversationStore> Conversation<S> {
+    /// Open the session, loading its stored history or starting an empty one.
+    pub async fn open<I: Into<String>>(store: Arc<S>, session_id: I) -> Result<Self> {
+        let session_id = session_id.into();
+        let messages = store.get(&session_id).await?.unwrap_or_default();
+
+        Ok(Self {
+            store,
+            session_id,
+            messages,
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// The history, for use with `continue_conversation()`.
+    pub fn messages(&self) -> &Messages {
+        &self.messages
+    }
+
+    pub fn into_messages(self) -> Messages {
+        self.messages
+    }
+
+    /// Append the inputs of `messages` and store them, such as the new messages of a turn.
+    pub async fn extend(&mut self, messages: Messages) -> Result<()> {
+        let inputs = messages.inputs().to_vec();
+        self.store.append(&self.session_id, &inputs).await?;
+        self.messages = std::mem::take(&mut self.messages).extend_inputs(inputs);
+        Ok(())
+    }
+
+    /// Append and store a user message.
+    pub async fn user<T: Into<String>>(&mut self, content: T) -> Result<()> {
+        self.extend(Messages::new().user(content)).await
+    }
+
+    /// Append and store an assistant message.
+    pub async fn assistant<T: Into<String>>(&mut self, content: T) -> Result<()> {
+        self.extend(Messages::new().assistant(content)).await
+    }
+
+    /// Replace the whole history, e.g. after trimming it.
+    pub async fn replace(&mut self, messages: Messages) -> Result<()> {
+        self.store.put(&self.session_id, &messages).await?;
+        self.messages = messages;
+        Ok(())
+    }
+
+    /// Delete the session from the store.
+    pub async fn delete(self) -> Result<bool> {
+        self.store.delete(&self.session_id).await
+    }
+}
@@ -0,0 +1,170 @@
+//! Filesystem conversation store.
+
+use super::ConversationStore;
+use crate::{
+    error::{Error, Result},
+    messages::{Messages, file_error, inputs_to_jsonl},
+    types::Input,
+};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::OwnedMutexGuard;
+
+/// A [`ConversationStore`] keeping each session in `<dir>/<session id>.jsonl`.
+///
+/// The files are the JSONL written by [`Messages::save_jsonl`], one input per line,
+/// so sessions can be inspected by hand. Appends only add lines to the file.
+///
+/// Writes to a session through the same store, or its clones, happen one at a time.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    /// Per-session locks, so an append never lands in a file that a save is replacing.
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl FileStore {
+    /// Store sessions in `dir`, which is created on the first write.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            locks: Arc::default(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file for a session. Ids are restricted so they cannot escape the directory.
+    fn path(&self, session_id: &str) -> Result<PathBuf> {
+        let valid = !session_id.is_empty()
+            && !session_id.starts_with('.')
+            && session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        if !valid {
+            return Err(Error::InvalidSessionId {
+                id: session_id.to_string(),
+            });
+        }
+
+        Ok(self.dir.join(format!("{}.jsonl", session_id)))
+    }
+
+    /// Wait for the other writes to a session to finish.
+    async fn lock(&self, session_id: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            // Forget the locks of sessions nobody is writing to
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(session_id.to_string()).or_default().clone()
+        };
+
+        lock.lock_owned().await
+    }
+
+    async fn create_dir(&self) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| file_error(&self.dir, e))
+    }
+}
+
+impl ConversationStore for FileStore {
+    async fn get(&self, session_id: &str) -> Result<Option<Messages>> {
+        let path = self.path(session_id)?;
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(jsonl) => Messages::from_jsonl(&jsonl).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(file_error(&path, e)),
+        }
+    }
+
+    async fn put(&self, session_id: &str, messages: &Messages) -> Result<()> {
+        let path = self.path(session_id)?;
+        let jsonl = messages.to_jsonl()?;
+        let _guard = self.lock(session_id).await;
+        self.create_dir().await?;
+
+        // Write a uniquely named temporary file and rename it, so readers never see
+        // a partial session and concurrent saves don't write to the same file
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut temp = NamedTempFile::new_in(&dir).map_err(|e| file_error(&dir, e))?;
+            temp.write_all(jsonl.as_bytes()).map_err(|e| file_error(temp.path(), e))?;
+            temp.persist(&path).map_err(|e| file_error(&path, e.error))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| file_error(&self.dir, std::io::Error::other(e)))?
+    }
+
+    async fn append(&self, session_id: &str, inputs: &[Input]) -> Result<()> {
+        let path = self.path(session_id)?;
+        let jsonl = inputs_to_jsonl(inputs)?;
+        let _guard = self.lock(session_id).await;
+        self.create_dir().await?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| file_error(&path, e))?;
+
+        file.write_all(jsonl.as_bytes()).await.map_err(|e| file_error(&path, e))?;
+        file.flush().await.map_err(|e| file_error(&path, e))
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(file_error(&self.dir, e)),
+        };
+
+        let mut ids = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| file_error(&self.dir, e))? {
+            let file_name = entry.file_name();
+            if let Some(id) = file_name.to_str().and_then(|name| name.strip_suffix(".jsonl")) {
+                ids.push(id.to_string());
+            }
+        }
+
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn delete(&self, session_id: &str) -> Result<bool> {
+        let path = self.path(session_id)?;
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(file_error(&path, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_ids_cannot_escape_the_directory() {
+        let store = FileStore::new("sessions");
+
+        assert_eq!(store.path("user-42_v1.0").unwrap(), Path::new("sessions/user-42_v1.0.jsonl"));
+
+        for id in ["", "../secrets", "a/b", ".hidden", "a\\b"] {
+            assert!(matches!(store.path(id), Err(Error::InvalidSessionId { .. })), "{:?}", id);
+        }
+    }
+}
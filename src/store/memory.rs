@@ -0,0 +1,52 @@
+//! In-memory conversation store.
+
+use super::ConversationStore;
+use crate::{error::Result, messages::Messages, types::Input};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A [`ConversationStore`] in process memory, for tests and single-instance services.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    sessions: Mutex<HashMap<String, Vec<Input>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<Input>>> {
+        // A panic while holding the lock cannot leave a session half-written
+        self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ConversationStore for MemoryStore {
+    async fn get(&self, session_id: &str) -> Result<Option<Messages>> {
+        Ok(self.sessions().get(session_id).cloned().map(Messages::from_inputs))
+    }
+
+    async fn put(&self, session_id: &str, messages: &Messages) -> Result<()> {
+        self.sessions().insert(session_id.to_string(), messages.inputs().to_vec());
+        Ok(())
+    }
+
+    async fn append(&self, session_id: &str, inputs: &[Input]) -> Result<()> {
+        self.sessions()
+            .entry(session_id.to_string())
+            .or_default()
+            .extend_from_slice(inputs);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = self.sessions().keys().cloned().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn delete(&self, session_id: &str) -> Result<bool> {
+        Ok(self.sessions().remove(session_id).is_some())
+    }
+}
@@ -0,0 +1,64 @@
+//! Conversation storage.
+//!
+//! A [`ConversationStore`] keeps the inputs of conversations by session id, so chat
+//! services can resume sessions across restarts and instances. A [`Conversation`]
+//! wraps [`Messages`] and writes every change through to its store:
+//!
+//! ```
+//! use responses::store::{Conversation, MemoryStore};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> responses::Result<()> {
+//! let store = Arc::new(MemoryStore::new());
+//!
+//! let mut conversation = Conversation::open(store.clone(), "user-42").await?;
+//! conversation.user("What's the weather in Paris?").await?;
+//!
+//! // Later, possibly on another instance sharing the store
+//! let conversation = Conversation::open(store, "user-42").await?;
+//! assert_eq!(conversation.messages().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MemoryStore`] is always available. The `file-store` feature adds [`FileStore`],
+//! which keeps one JSONL file per session, and the `sqlite-store` feature adds
+//! [`SqliteStore`], backed by an embedded SQLite database.
+
+mod conversation;
+#[cfg(feature = "file-store")]
+mod file;
+mod memory;
+#[cfg(feature = "sqlite-store")]
+mod sqlite;
+
+pub use conversation::Conversation;
+#[cfg(feature = "file-store")]
+pub use file::FileStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "sqlite-store")]
+pub use sqlite::SqliteStore;
+
+use crate::{error::Result, messages::Messages, types::Input};
+use std::future::Future;
+
+/// Storage for conversations, keyed by session id.
+///
+/// Stores keep the inputs of a conversation, including unrendered templates.
+/// Variables and locale settings on the [`Messages`] are not stored.
+pub trait ConversationStore: Send + Sync {
+    /// The conversation stored for `session_id`, or `None` if there is none.
+    fn get(&self, session_id: &str) -> impl Future<Output = Result<Option<Messages>>> + Send;
+
+    /// Store `messages` for `session_id`, replacing any stored conversation.
+    fn put(&self, session_id: &str, messages: &Messages) -> impl Future<Output = Result<()>> + Send;
+
+    /// Append `inputs` to the conversation for `session_id`, creating it if needed.
+    fn append(&self, session_id: &str, inputs: &[Input]) -> impl Future<Output = Result<()>> + Send;
+
+    /// The ids of all stored sessions, sorted.
+    fn list(&self) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    /// Delete the conversation for `session_id`. Returns whether one was stored.
+    fn delete(&self, session_id: &str) -> impl Future<Output = Result<bool>> + Send;
+}
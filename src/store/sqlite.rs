@@ -0,0 +1,210 @@
+//! Embedded SQLite conversation store.
+
+use super::ConversationStore;
+use crate::{
+    error::{Error, Result},
+    messages::Messages,
+    types::Input,
+};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS conversations (
+        session_id TEXT PRIMARY KEY
+    );
+    CREATE TABLE IF NOT EXISTS conversation_inputs (
+        session_id TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        input TEXT NOT NULL,
+        PRIMARY KEY (session_id, position)
+    );
+";
+
+/// A [`ConversationStore`] in an SQLite database, one row per input.
+///
+/// Inputs are stored as JSON, like the lines of [`Messages::save_jsonl`]. Several
+/// processes can share a database file: appends run in immediate transactions,
+/// and a locked database is retried for a few seconds.
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Open or create the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_connection(Connection::open(path).map_err(sqlite_error)?)
+    }
+
+    /// A private in-memory database, dropped with the store.
+    pub fn in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(sqlite_error)?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self> {
+        connection.busy_timeout(Duration::from_secs(5)).map_err(sqlite_error)?;
+        connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run `f` on the connection without blocking the async runtime.
+    async fn with_connection<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&mut connection)
+        })
+        .await?
+        .map_err(sqlite_error)
+    }
+}
+
+impl ConversationStore for SqliteStore {
+    async fn get(&self, session_id: &str) -> Result<Option<Messages>> {
+        let session_id = session_id.to_string();
+
+        let rows = self
+            .with_connection(move |connection| {
+                let exists = connection
+                    .query_row(
+                        "SELECT 1 FROM conversations WHERE session_id = ?1",
+                        params![session_id],
+                        |_| Ok(()),
+                    )
+                    .optional()?;
+
+                if exists.is_none() {
+                    return Ok(None);
+                }
+
+                let mut statement = connection
+                    .prepare("SELECT input FROM conversation_inputs WHERE session_id = ?1 ORDER BY position")?;
+                let rows = statement
+                    .query_map(params![session_id], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+                Ok(Some(rows))
+            })
+            .await?;
+
+        let Some(rows) = rows else {
+            return Ok(None);
+        };
+
+        let inputs = rows
+            .iter()
+            .map(|row| serde_json::from_str(row))
+            .collect::<serde_json::Result<Vec<Input>>>()?;
+
+        Ok(Some(Messages::from_inputs(inputs)))
+    }
+
+    async fn put(&self, session_id: &str, messages: &Messages) -> Result<()> {
+        let session_id = session_id.to_string();
+        let rows = serialize_inputs(messages.inputs())?;
+
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+            transaction.execute(
+                "INSERT OR IGNORE INTO conversations (session_id) VALUES (?1)",
+                params![session_id],
+            )?;
+            transaction.execute(
+                "DELETE FROM conversation_inputs WHERE session_id = ?1",
+                params![session_id],
+            )?;
+            insert_inputs(&transaction, &session_id, 0, &rows)?;
+
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn append(&self, session_id: &str, inputs: &[Input]) -> Result<()> {
+        let session_id = session_id.to_string();
+        let rows = serialize_inputs(inputs)?;
+
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+            transaction.execute(
+                "INSERT OR IGNORE INTO conversations (session_id) VALUES (?1)",
+                params![session_id],
+            )?;
+            let next: i64 = transaction.query_row(
+                "SELECT COALESCE(MAX(position) + 1, 0) FROM conversation_inputs WHERE session_id = ?1",
+                params![session_id],
+                |row| row.get(0),
+            )?;
+            insert_inputs(&transaction, &session_id, next, &rows)?;
+
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        self.with_connection(|connection| {
+            let mut statement = connection.prepare("SELECT session_id FROM conversations ORDER BY session_id")?;
+            statement
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })
+        .await
+    }
+
+    async fn delete(&self, session_id: &str) -> Result<bool> {
+        let session_id = session_id.to_string();
+
+        self.with_connection(move |connection| {
+            let transaction = connection.transaction()?;
+
+            transaction.execute(
+                "DELETE FROM conversation_inputs WHERE session_id = ?1",
+                params![session_id],
+            )?;
+            let deleted = transaction.execute(
+                "DELETE FROM conversations WHERE session_id = ?1",
+                params![session_id],
+            )?;
+
+            transaction.commit()?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+}
+
+fn serialize_inputs(inputs: &[Input]) -> Result<Vec<String>> {
+    Ok(inputs
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<serde_json::Result<Vec<_>>>()?)
+}
+
+fn insert_inputs(connection: &Connection, session_id: &str, first: i64, rows: &[String]) -> rusqlite::Result<()> {
+    let mut statement = connection
+        .prepare("INSERT INTO conversation_inputs (session_id, position, input) VALUES (?1, ?2, ?3)")?;
+
+    for (position, row) in (first..).zip(rows) {
+        statement.execute(params![session_id, position, row])?;
+    }
+
+    Ok(())
+}
+
+fn sqlite_error(e: rusqlite::Error) -> Error {
+    Error::ConversationStore(format!("SQLite: {}", e))
+}
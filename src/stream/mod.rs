@@ -0,0 +1,228 @@
+//! Streaming responses.
+//!
+//! Providers deliver streamed responses as a [`ResponseStream`] of [`StreamEvent`]s.
+//! [`StructuredStream`] builds on it to yield partial snapshots of structured output
+//! while it is still being generated.
+
+pub mod partial;
+pub mod structured;
+
+pub(crate) mod sse;
+
+pub use partial::parse_partial;
+pub use structured::{PartialEvent, StructuredStream};
+
+use crate::{
+    error::{Error, Result},
+    types::{Output, Response as ApiResponse},
+};
+use serde::Deserialize;
+use sse::SseDecoder;
+use std::collections::VecDeque;
+
+/// An event received while a response is being streamed.
+#[derive(Clone, Debug)]
+pub enum StreamEvent {
+    /// A fragment of the output text.
+    TextDelta(String),
+
+    /// A fragment of a refusal.
+    RefusalDelta(String),
+
+    /// The response finished with the given output items.
+    Completed(Vec<Output>),
+}
+
+/// A stream of events for a single response.
+///
+/// Call [`next`](Self::next) until it returns `None`.
+pub struct ResponseStream {
+    source: Source,
+}
+
+enum Source {
+    Http {
+        response: reqwest::Response,
+        decoder: SseDecoder,
+        pending: VecDeque<String>,
+        finished: bool,
+    },
+
+    Events(VecDeque<Result<StreamEvent>>),
+}
+
+impl ResponseStream {
+    /// Create a stream that yields the given events, e.g. from a custom provider or in tests.
+    pub fn from_events<I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = StreamEvent>,
+    {
+        Self {
+            source: Source::Events(events.into_iter().map(Ok).collect()),
+        }
+    }
+
+    /// Create a stream from an HTTP response carrying server-sent events.
+    ///
+    /// Error statuses are turned into `Error::Provider` using the error body.
+    pub async fn from_http(response: reqwest::Response) -> Result<Self> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await?;
+
+            return Err(match serde_json::from_str::<ApiResponse>(&body) {
+                Ok(ApiResponse { error: Some(e), .. }) => Error::Provider {
+                    code: e.code,
+                    message: e.message,
+                },
+                _ => Error::Provider {
+                    code: status.as_u16().to_string(),
+                    message: body,
+                },
+            });
+        }
+
+        Ok(Self {
+            source: Source::Http {
+                response,
+                decoder: SseDecoder::new(),
+                pending: VecDeque::new(),
+                finished: false,
+            },
+        })
+    }
+
+    /// Receive the next event, or `None` once the stream is exhausted.
+    pub async fn next(&mut self) -> Option<Result<StreamEvent>> {
+        match &mut self.source {
+            Source::Events(events) => events.pop_front(),
+
+            Source::Http { response, decoder, pending, finished } => loop {
+                while let Some(data) = pending.pop_front() {
+                    match parse_event(&data) {
+                        Ok(Some(event)) => return Some(Ok(event)),
+                        Ok(None) => continue,
+                        Err(e) => {
+                            *finished = true;
+                            pending.clear();
+                            return Some(Err(e));
+                        }
+                    }
+                }
+
+                if *finished {
+                    return None;
+                }
+
+                match response.chunk().await {
+                    Ok(Some(chunk)) => pending.extend(decoder.push(&chunk)),
+                    Ok(None) => {
+                        *finished = true;
+                        pending.extend(decoder.finish());
+                    }
+                    Err(e) => {
+                        *finished = true;
+                        return Some(Err(Error::Http(e)));
+                    }
+                }
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(rename = "type")]
+    kind: String,
+
+    #[serde(default)]
+    delta: Option<String>,
+
+    #[serde(default)]
+    response: Option<ApiResponse>,
+
+    #[serde(default)]
+    code: Option<String>,
+
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Convert the data of one server-sent event into a stream event.
+///
+/// Returns `Ok(None)` for events that carry nothing of interest.
+fn parse_event(data: &str) -> Result<Option<StreamEvent>> {
+    if data == "[DONE]" {
+        return Ok(None);
+    }
+
+    let event: RawEvent = serde_json::from_str(data)?;
+
+    match event.kind.as_str() {
+        "response.output_text.delta" => Ok(event.delta.map(StreamEvent::TextDelta)),
+
+        "response.refusal.delta" => Ok(event.delta.map(StreamEvent::RefusalDelta)),
+
+        "response.completed" | "response.incomplete" | "response.failed" => {
+            let response = event.response.ok_or_else(|| {
+                Error::InvalidResponse(format!("Event '{}' is missing the response", event.kind))
+            })?;
+
+            match (response.error, response.output) {
+                (Some(e), _) => Err(Error::Provider {
+                    code: e.code,
+                    message: e.message,
+                }),
+                (None, output) => Ok(Some(StreamEvent::Completed(output.unwrap_or_default()))),
+            }
+        }
+
+        "error" => Err(Error::Provider {
+            code: event.code.unwrap_or_else(|| "stream_error".to_string()),
+            message: event.message.unwrap_or_default(),
+        }),
+
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delta_events() {
+        match parse_event(r#"{"type":"response.output_text.delta","delta":"Hel"}"#) {
+            Ok(Some(StreamEvent::TextDelta(delta))) => assert_eq!(delta, "Hel"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert!(matches!(
+            parse_event(r#"{"type":"response.created","response":{"output":[]}}"#),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn test_parse_completed_event() {
+        let data = r#"{"type":"response.completed","response":{"output":[{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Hi"}]}]}}"#;
+
+        match parse_event(data) {
+            Ok(Some(StreamEvent::Completed(output))) => assert_eq!(output.len(), 1),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_events() {
+        assert!(matches!(
+            parse_event(r#"{"type":"error","code":"rate_limit_exceeded","message":"Slow down"}"#),
+            Err(Error::Provider { code, .. }) if code == "rate_limit_exceeded"
+        ));
+
+        assert!(matches!(
+            parse_event(r#"{"type":"response.failed","response":{"error":{"code":"server_error","message":"Oops"}}}"#),
+            Err(Error::Provider { code, .. }) if code == "server_error"
+        ));
+    }
+}
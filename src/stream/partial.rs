@@ -0,0 +1,478 @@
+//! Tolerant parser for truncated JSON documents.
+//!
+//! Used to build snapshots of structured output while it is still streaming.
+//! Open strings, arrays and objects are closed; keys without a value,
+//! unfinished literals and numbers that may still grow are dropped.
+
+use serde_json::{Map, Number, Value};
+
+/// Parse a possibly truncated JSON document into the most complete value it describes.
+///
+/// Returns `None` if nothing usable has arrived yet or the input is not a prefix of valid JSON.
+pub fn parse_partial(input: &str) -> Option<Value> {
+    let mut parser = PartialJson::default();
+    parser.push(input);
+    parser.snapshot()
+}
+
+/// Incremental form of [`parse_partial`] for a document that arrives in pieces.
+///
+/// Each character is read once, so feeding a long document delta by delta
+/// costs about as much as parsing it whole. Building a snapshot copies the
+/// document, so take one only when [`push`](Self::push) says a value began or ended.
+#[derive(Default)]
+pub(crate) struct PartialJson {
+    /// Containers that are still open, innermost last.
+    stack: Vec<Frame>,
+    /// The string, number or literal being read.
+    scalar: Option<Scalar>,
+    /// The fully received document; anything after it is ignored.
+    root: Option<Value>,
+    invalid: bool,
+    /// Whether a value began or ended since the last `push`.
+    changed: bool,
+}
+
+enum Frame {
+    Object {
+        map: Map<String, Value>,
+        key: Option<String>,
+        expect: ObjectExpect,
+    },
+    Array {
+        items: Vec<Value>,
+        expect: ArrayExpect,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ObjectExpect {
+    KeyOrEnd,
+    Colon,
+    Value,
+    CommaOrEnd,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ArrayExpect {
+    ValueOrEnd,
+    CommaOrEnd,
+}
+
+enum Scalar {
+    String {
+        text: String,
+        escape: Escape,
+        is_key: bool,
+    },
+    Number(String),
+    Literal {
+        literal: &'static str,
+        matched: usize,
+        value: Value,
+    },
+}
+
+/// Progress through an escape sequence inside a string.
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    Backslash,
+    Unicode { code: u32, digits: u32 },
+    /// A high surrogate was read; `backslash` is set once the `\` of its pair has arrived.
+    Surrogate { high: u32, backslash: bool },
+    Low { high: u32, code: u32, digits: u32 },
+}
+
+/// Marker for input that cannot be the prefix of a valid JSON document.
+struct Invalid;
+
+/// Whether a character was used up, or must be read again in the new state.
+enum Step {
+    Consumed,
+    Reprocess,
+}
+
+impl PartialJson {
+    /// Append the next piece of the document.
+    ///
+    /// Returns whether a value began or ended, the points where a new snapshot is
+    /// worth building. Text added to an open string alone does not count.
+    pub(crate) fn push(&mut self, text: &str) -> bool {
+        self.changed = false;
+
+        for c in text.chars() {
+            if self.invalid || self.root.is_some() {
+                break;
+            }
+
+            loop {
+                match self.step(c) {
+                    Ok(Step::Consumed) => break,
+                    Ok(Step::Reprocess) => {}
+                    Err(Invalid) => {
+                        self.invalid = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.changed
+    }
+
+    /// The most complete value described by the text pushed so far, like [`parse_partial`].
+    pub(crate) fn snapshot(&self) -> Option<Value> {
+        if self.invalid {
+            return None;
+        }
+
+        if let Some(root) = &self.root {
+            return Some(root.clone());
+        }
+
+        let mut current = self.scalar.as_ref().and_then(Scalar::partial);
+
+        for frame in self.stack.iter().rev() {
+            current = Some(match frame {
+                Frame::Object { map, key, .. } => {
+                    let mut map = map.clone();
+                    if let (Some(key), Some(value)) = (key, current) {
+                        map.insert(key.clone(), value);
+                    }
+                    Value::Object(map)
+                }
+                Frame::Array { items, .. } => {
+                    let mut items = items.clone();
+                    items.extend(current);
+                    Value::Array(items)
+                }
+            });
+        }
+
+        current
+    }
+
+    fn step(&mut self, c: char) -> Result<Step, Invalid> {
+        if self.root.is_some() {
+            return Ok(Step::Consumed);
+        }
+
+        if let Some(scalar) = self.scalar.take() {
+            return self.step_scalar(scalar, c);
+        }
+
+        if c.is_whitespace() {
+            return Ok(Step::Consumed);
+        }
+
+        match self.stack.last_mut() {
+            None => self.begin_value(c)?,
+
+            Some(Frame::Object { key, expect, .. }) => match (*expect, c) {
+                (ObjectExpect::KeyOrEnd | ObjectExpect::CommaOrEnd, '}') => self.close()?,
+                (ObjectExpect::KeyOrEnd, '"') => {
+                    self.scalar = Some(Scalar::String {
+                        text: String::new(),
+                        escape: Escape::None,
+                        is_key: true,
+                    });
+                }
+                (ObjectExpect::Colon, ':') => *expect = ObjectExpect::Value,
+                (ObjectExpect::Value, c) => self.begin_value(c)?,
+                (ObjectExpect::CommaOrEnd, ',') => {
+                    *key = None;
+                    *expect = ObjectExpect::KeyOrEnd;
+                }
+                _ => return Err(Invalid),
+            },
+
+            Some(Frame::Array { expect, .. }) => match (*expect, c) {
+                (_, ']') => self.close()?,
+                (ArrayExpect::ValueOrEnd, c) => self.begin_value(c)?,
+                (ArrayExpect::CommaOrEnd, ',') => *expect = ArrayExpect::ValueOrEnd,
+                _ => return Err(Invalid),
+            },
+        }
+
+        Ok(Step::Consumed)
+    }
+
+    fn begin_value(&mut self, c: char) -> Result<(), Invalid> {
+        // Numbers and literals only show up in snapshots once complete
+        self.changed |= matches!(c, '{' | '[' | '"');
+
+        let literal = |literal, value| Scalar::Literal { literal, matched: 1, value };
+
+        match c {
+            '{' => self.stack.push(Frame::Object {
+                map: Map::new(),
+                key: None,
+                expect: ObjectExpect::KeyOrEnd,
+            }),
+            '[' => self.stack.push(Frame::Array {
+                items: Vec::new(),
+                expect: ArrayExpect::ValueOrEnd,
+            }),
+            '"' => {
+                self.scalar = Some(Scalar::String {
+                    text: String::new(),
+                    escape: Escape::None,
+                    is_key: false,
+                });
+            }
+            't' => self.scalar = Some(literal("true", Value::Bool(true))),
+            'f' => self.scalar = Some(literal("false", Value::Bool(false))),
+            'n' => self.scalar = Some(literal("null", Value::Null)),
+            c if c == '-' || c.is_ascii_digit() => self.scalar = Some(Scalar::Number(c.to_string())),
+            _ => return Err(Invalid),
+        }
+
+        Ok(())
+    }
+
+    fn step_scalar(&mut self, scalar: Scalar, c: char) -> Result<Step, Invalid> {
+        match scalar {
+            Scalar::String { mut text, mut escape, is_key } => {
+                let step = step_string(&mut text, &mut escape, c)?;
+
+                match step {
+                    Some(true) if is_key => {
+                        if let Some(Frame::Object { key, expect, .. }) = self.stack.last_mut() {
+                            *key = Some(text);
+                            *expect = ObjectExpect::Colon;
+                        }
+                    }
+                    Some(true) => self.complete(Value::String(text)),
+                    _ => self.scalar = Some(Scalar::String { text, escape, is_key }),
+                }
+
+                Ok(if step.is_none() { Step::Reprocess } else { Step::Consumed })
+            }
+
+            Scalar::Number(mut text) => {
+                if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                    text.push(c);
+                    self.scalar = Some(Scalar::Number(text));
+                    return Ok(Step::Consumed);
+                }
+
+                // The number ended with the character before this one
+                let number = serde_json::from_str::<Number>(&text).map_err(|_| Invalid)?;
+                self.complete(Value::Number(number));
+
+                Ok(Step::Reprocess)
+            }
+
+            Scalar::Literal { literal, matched, value } => {
+                if !literal[matched..].starts_with(c) {
+                    return Err(Invalid);
+                }
+
+                let matched = matched + c.len_utf8();
+
+                if matched == literal.len() {
+                    self.complete(value);
+                } else {
+                    self.scalar = Some(Scalar::Literal { literal, matched, value });
+                }
+
+                Ok(Step::Consumed)
+            }
+        }
+    }
+
+    /// Close the innermost container.
+    fn close(&mut self) -> Result<(), Invalid> {
+        let value = match self.stack.pop() {
+            Some(Frame::Object { map, .. }) => Value::Object(map),
+            Some(Frame::Array { items, .. }) => Value::Array(items),
+            None => return Err(Invalid),
+        };
+
+        self.complete(value);
+
+        Ok(())
+    }
+
+    /// Place a fully received value in its container, or make it the document.
+    fn complete(&mut self, value: Value) {
+        self.changed = true;
+
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(Frame::Object { map, key, expect }) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, value);
+                }
+                *expect = ObjectExpect::CommaOrEnd;
+            }
+            Some(Frame::Array { items, expect }) => {
+                items.push(value);
+                *expect = ArrayExpect::CommaOrEnd;
+            }
+        }
+    }
+}
+
+impl Scalar {
+    /// The value so far, if it is already usable.
+    fn partial(&self) -> Option<Value> {
+        match self {
+            Scalar::String { text, is_key: false, .. } => Some(Value::String(text.clone())),
+            // `12` may still become `1234`, so numbers wait for the character after them
+            Scalar::String { .. } | Scalar::Number(_) | Scalar::Literal { .. } => None,
+        }
+    }
+}
+
+/// Read one character of a string.
+///
+/// Returns `Some(true)` at the closing quote, `Some(false)` for any other consumed
+/// character, and `None` when the character must be read again as plain text.
+fn step_string(text: &mut String, escape: &mut Escape, c: char) -> Result<Option<bool>, Invalid> {
+    match *escape {
+        Escape::None => match c {
+            '"' => return Ok(Some(true)),
+            '\\' => *escape = Escape::Backslash,
+            c => text.push(c),
+        },
+
+        Escape::Backslash => {
+            *escape = Escape::None;
+
+            match c {
+                '"' => text.push('"'),
+                '\\' => text.push('\\'),
+                '/' => text.push('/'),
+                'b' => text.push('\u{0008}'),
+                'f' => text.push('\u{000C}'),
+                'n' => text.push('\n'),
+                'r' => text.push('\r'),
+                't' => text.push('\t'),
+                'u' => *escape = Escape::Unicode { code: 0, digits: 0 },
+                _ => return Err(Invalid),
+            }
+        }
+
+        Escape::Unicode { code, digits } => {
+            let code = code * 16 + c.to_digit(16).ok_or(Invalid)?;
+
+            *escape = match digits + 1 {
+                4 if (0xD800..0xDC00).contains(&code) => Escape::Surrogate { high: code, backslash: false },
+                4 => {
+                    text.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    Escape::None
+                }
+                digits => Escape::Unicode { code, digits },
+            };
+        }
+
+        // A high surrogate not followed by `\u` stands alone
+        Escape::Surrogate { high, backslash } => match (backslash, c) {
+            (false, '\\') => *escape = Escape::Surrogate { high, backslash: true },
+            (true, 'u') => *escape = Escape::Low { high, code: 0, digits: 0 },
+            _ => {
+                text.push(char::REPLACEMENT_CHARACTER);
+                *escape = Escape::None;
+                return Ok(None);
+            }
+        },
+
+        Escape::Low { high, code, digits } => {
+            let low = code * 16 + c.to_digit(16).ok_or(Invalid)?;
+
+            *escape = if digits + 1 == 4 {
+                let combined = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                text.push(char::from_u32(combined).unwrap_or(char::REPLACEMENT_CHARACTER));
+                Escape::None
+            } else {
+                Escape::Low { high, code: low, digits: digits + 1 }
+            };
+        }
+    }
+
+    Ok(Some(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_complete_document() {
+        assert_eq!(parse_partial(r#"{"a": [1, 2], "b": "x"}"#), Some(json!({"a": [1, 2], "b": "x"})));
+    }
+
+    #[test]
+    fn test_truncated_containers_are_closed() {
+        assert_eq!(parse_partial(r#"{"items": [{"name": "one"}, {"name": "tw"#), Some(json!({"items": [{"name": "one"}, {"name": "tw"}]})));
+        assert_eq!(parse_partial(r#"{"items": [1, 2,"#), Some(json!({"items": [1, 2]})));
+    }
+
+    #[test]
+    fn test_dangling_keys_and_literals_are_dropped() {
+        assert_eq!(parse_partial(r#"{"done": true, "next"#), Some(json!({"done": true})));
+        assert_eq!(parse_partial(r#"{"done": true, "next":"#), Some(json!({"done": true})));
+        assert_eq!(parse_partial(r#"{"done": tr"#), Some(json!({})));
+        assert_eq!(parse_partial(r#"{"count": -"#), Some(json!({})));
+    }
+
+    #[test]
+    fn test_numbers_wait_for_a_delimiter() {
+        assert_eq!(parse_partial(r#"{"count": 12"#), Some(json!({})));
+        assert_eq!(parse_partial(r#"{"count": 12,"#), Some(json!({"count": 12})));
+        assert_eq!(parse_partial("[1, 23"), Some(json!([1])));
+        assert_eq!(parse_partial("12"), None);
+    }
+
+    #[test]
+    fn test_push_reports_values_beginning_and_ending() {
+        let mut parser = PartialJson::default();
+
+        assert!(parser.push(r#"{"text": "a"#));
+        assert!(!parser.push("bc"));
+        assert_eq!(parser.snapshot(), Some(json!({"text": "abc"})));
+        assert!(parser.push(r#"", "n"#));
+        assert!(!parser.push(r#"": 4"#));
+        assert!(parser.push("2}"));
+        assert_eq!(parser.snapshot(), Some(json!({"text": "abc", "n": 42})));
+    }
+
+    #[test]
+    fn test_truncated_escapes() {
+        assert_eq!(parse_partial(r#"{"text": "line\"#), Some(json!({"text": "line"})));
+        assert_eq!(parse_partial(r#"{"text": "caf\u00"#), Some(json!({"text": "caf"})));
+        assert_eq!(parse_partial(r#"{"text": "café"#), Some(json!({"text": "café"})));
+        assert_eq!(parse_partial(r#"["\ud83d\ude00"]"#), Some(json!(["😀"])));
+    }
+
+    #[test]
+    fn test_empty_and_invalid_input() {
+        assert_eq!(parse_partial(""), None);
+        assert_eq!(parse_partial("   "), None);
+        assert_eq!(parse_partial(r#"{"a" 1}"#), None);
+        assert_eq!(parse_partial("<html>"), None);
+    }
+
+    #[test]
+    fn test_pieces_match_whole_parse() {
+        let document = r#"{"items": [{"name": "caf\u00e9 \ud83d\ude00", "price": 12.5e1}, {"ok": true, "note": null}], "n": -3}"#;
+        let chars: Vec<char> = document.chars().collect();
+        let mut parser = PartialJson::default();
+
+        for (i, c) in chars.iter().enumerate() {
+            parser.push(&c.to_string());
+            let prefix: String = chars[..=i].iter().collect();
+            assert_eq!(parser.snapshot(), parse_partial(&prefix), "after {:?}", prefix);
+        }
+
+        assert_eq!(parser.snapshot(), serde_json::from_str(document).ok());
+
+        // Whitespace between tokens cannot change the snapshot, and text after the document is ignored
+        assert!(!parser.push("  "));
+        assert!(!parser.push("trailing"));
+        assert_eq!(parse_partial("12x"), Some(json!(12)));
+    }
+}
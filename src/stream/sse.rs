@@ -0,0 +1,115 @@
+//! Incremental decoder for `text/event-stream` bodies.
+
+/// Splits a server-sent event byte stream into the `data` payloads of complete events.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    /// How much of the buffer is known to hold no separator.
+    scanned: usize,
+}
+
+impl SseDecoder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of bytes and return the data of every event completed by it.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+
+        while let Some((end, separator_len)) = find_event_end(&self.buffer, self.scanned) {
+            let block: Vec<u8> = self.buffer.drain(..end + separator_len).take(end).collect();
+            self.scanned = 0;
+
+            if let Some(data) = parse_block(&String::from_utf8_lossy(&block)) {
+                events.push(data);
+            }
+        }
+
+        // A separator can straddle chunks, so its first three bytes are scanned again
+        self.scanned = self.buffer.len().saturating_sub(3);
+
+        events
+    }
+
+    /// Return the data of a trailing event that was not terminated by a blank line.
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let block = std::mem::take(&mut self.buffer);
+        self.scanned = 0;
+        parse_block(&String::from_utf8_lossy(&block))
+    }
+}
+
+/// Find the end of the first complete event at or after `from`, returning its length and the separator length.
+fn find_event_end(buffer: &[u8], from: usize) -> Option<(usize, usize)> {
+    for index in from..buffer.len() {
+        if buffer[index..].starts_with(b"\r\n\r\n") {
+            return Some((index, 4));
+        }
+
+        if buffer[index..].starts_with(b"\n\n") || buffer[index..].starts_with(b"\r\r") {
+            return Some((index, 2));
+        }
+    }
+
+    None
+}
+
+/// Join the `data:` lines of an event block, ignoring comments and other fields.
+fn parse_block(block: &str) -> Option<String> {
+    let data: Vec<&str> = block
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+
+    if data.is_empty() {
+        None
+    } else {
+        Some(data.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_split_across_chunks() {
+        let mut decoder = SseDecoder::new();
+
+        assert!(decoder.push(b"event: response.output_text.delta\ndata: {\"del").is_empty());
+
+        let events = decoder.push(b"ta\":\"a\"}\n\ndata: second\n\n: comment\n\n");
+        assert_eq!(events, vec!["{\"delta\":\"a\"}".to_string(), "second".to_string()]);
+    }
+
+    #[test]
+    fn test_multiline_data_and_crlf() {
+        let mut decoder = SseDecoder::new();
+
+        let events = decoder.push(b"data: one\r\ndata: two\r\n\r\n");
+        assert_eq!(events, vec!["one\ntwo".to_string()]);
+    }
+
+    #[test]
+    fn test_separator_split_across_chunks() {
+        let mut decoder = SseDecoder::new();
+
+        assert!(decoder.push(b"data: one\r\n").is_empty());
+        assert!(decoder.push(b"\r").is_empty());
+        assert_eq!(decoder.push(b"\ndata: two\n"), vec!["one".to_string()]);
+        assert_eq!(decoder.push(b"\n"), vec!["two".to_string()]);
+    }
+
+    #[test]
+    fn test_unterminated_trailing_event() {
+        let mut decoder = SseDecoder::new();
+
+        assert!(decoder.push(b"data: last").is_empty());
+        assert_eq!(decoder.finish(), Some("last".to_string()));
+        assert_eq!(decoder.finish(), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Streaming of structured output with partial snapshots.
+
+use super::{ResponseStream, StreamEvent, partial::PartialJson};
+use crate::{
+    cancel::RequestGuard,
+    error::{Error, Result},
+    response::Response,
+    util::{output_to_response, parse_structured},
+};
+use schemars::Schema;
+use serde::Deserialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+/// An update from a [`StructuredStream`].
+#[derive(Clone, Debug)]
+pub enum PartialEvent<T> {
+    /// Snapshot of the output received so far, with open strings, arrays and objects closed.
+    ///
+    /// A new snapshot is taken when a string, array or object opens or any value ends,
+    /// so numbers only appear once complete.
+    ///
+    /// Snapshots can be deserialized into types whose fields tolerate absence,
+    /// or inspected directly to show progress.
+    Partial(Value),
+
+    /// The final response, parsed (and validated, if enabled) like a non-streaming request.
+    Completed(Response<T>),
+}
+
+/// A stream of partial snapshots followed by the final structured response.
+///
+/// Created by `StructuredRequestBuilder::send_stream`.
+pub struct StructuredStream<T> {
+    stream: ResponseStream,
+    schema: Schema,
+    validate_schema: bool,
+    guard: RequestGuard,
+    text: String,
+    partial: PartialJson,
+    last_snapshot: Option<Value>,
+    finished: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> StructuredStream<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    pub(crate) fn new(stream: ResponseStream, schema: Schema, validate_schema: bool, guard: RequestGuard) -> Self {
+        Self {
+            stream,
+            schema,
+            validate_schema,
+            guard,
+            text: String::new(),
+            partial: PartialJson::default(),
+            last_snapshot: None,
+            finished: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Receive the next update, or `None` after the final response has been returned.
+    ///
+    /// A snapshot is only yielded when it differs from the previous one. The request's
+    /// timeout and cancellation token apply while waiting for each event.
+    pub async fn next(&mut self) -> Option<Result<PartialEvent<T>>> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            let event = match self.guard.run(async { Ok(self.stream.next().await) }).await {
+                Ok(Some(event)) => event,
+                Ok(None) => break,
+                Err(e) => Err(e),
+            };
+            
+            match event {
+                Ok(StreamEvent::TextDelta(delta)) => {
+                    self.text.push_str(&delta);
+
+                    // Only the delta is parsed; a snapshot is built when a value began or ended
+                    if self.partial.push(&delta)
+                        && let Some(snapshot) = self.partial.snapshot()
+                        && self.last_snapshot.as_ref() != Some(&snapshot)
+                    {
+                        self.last_snapshot = Some(snapshot.clone());
+                        return Some(Ok(PartialEvent::Partial(snapshot)));
+                    }
+                }
+
+                Ok(StreamEvent::RefusalDelta(_)) => {}
+
+                Ok(StreamEvent::Completed(output)) => {
+                    self.finished = true;
+
+                    let response = output_to_response(output)
+                        .and_then(|response| parse_structured(response, &self.schema, self.validate_schema));
+
+                    return Some(response.map(PartialEvent::Completed));
+                }
+
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.finished = true;
+
+        Some(Err(Error::InvalidResponse(
+            "Stream ended before the response was completed".to_string()
+        )))
+    }
+
+    /// The output text received so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Skip the remaining snapshots and wait for the final response.
+    pub async fn into_response(mut self) -> Result<Response<T>> {
+        while let Some(event) = self.next().await {
+            if let PartialEvent::Completed(response) = event? {
+                return Ok(response);
+            }
+        }
+
+        Err(Error::InvalidResponse(
+            "Stream ended before the response was completed".to_string()
+        ))
+    }
+}
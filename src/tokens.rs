@@ -0,0 +1,199 @@
+//! Counting the tokens of prompts before they are sent.
+//!
+//! With the `tokenizer` feature, enabled by default, text is encoded with the
+//! o200k or cl100k BPE encoding embedded in the crate, so counting works offline.
+//! Without it, tokens are estimated at about four characters per token.
+//!
+//! Counts include the formatting added around each input and the size of tool
+//! definitions. They match the provider's usage closely, but not exactly.
+//!
+//! ```
+//! use responses::Messages;
+//! use responses::tokens::Encoding;
+//!
+//! let conversation = Messages::new()
+//!     .system("You are a helpful assistant")
+//!     .user("What's the weather in Paris?");
+//!
+//! assert_eq!(Encoding::for_model("gpt-4o"), Encoding::O200kBase);
+//! assert!(conversation.estimate_tokens("gpt-4o").unwrap() > 10);
+//! ```
+
+use crate::types::{
+    CreateResponse, FunctionOutput, FunctionOutputContent, Input, Text, TextFormat, Tool,
+};
+
+/// Tokens added around each input, for its role and delimiters.
+const INPUT_OVERHEAD: usize = 4;
+
+/// Tokens priming the model's reply.
+const REPLY_OVERHEAD: usize = 3;
+
+/// Tokens added around each tool definition.
+const TOOL_OVERHEAD: usize = 8;
+
+/// Estimated tokens for an image, whatever its size.
+const IMAGE_TOKENS: usize = 765;
+
+/// A BPE encoding used by OpenAI models.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// GPT-4o, GPT-4.1, GPT-5 and the o-series.
+    O200kBase,
+
+    /// GPT-4, GPT-3.5 Turbo and the text-embedding models.
+    Cl100kBase,
+}
+
+impl Encoding {
+    /// The encoding of `model`.
+    ///
+    /// Unknown models, such as Azure deployments with custom names, use o200k.
+    pub fn for_model(model: &str) -> Self {
+        // `gpt-4` and its `gpt-4-turbo`, `gpt-4-32k`, ... versions, but not `gpt-4o` or `gpt-4.5`
+        let cl100k = model == "gpt-4"
+            || ["gpt-4-", "gpt-3.5", "gpt-35", "text-embedding"]
+                .iter()
+                .any(|prefix| model.starts_with(prefix));
+
+        if cl100k {
+            Encoding::Cl100kBase
+        } else {
+            Encoding::O200kBase
+        }
+    }
+
+    /// The number of tokens in `text`.
+    #[cfg(feature = "tokenizer")]
+    pub fn count(&self, text: &str) -> usize {
+        let bpe = match self {
+            Encoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        };
+        bpe.encode_ordinary(text).len()
+    }
+
+    /// The number of tokens in `text`, estimated from its length.
+    #[cfg(not(feature = "tokenizer"))]
+    pub fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// The tokens of `input`, including the formatting around it.
+///
+/// Templates are counted by their source; render them first for an exact count.
+pub fn input_tokens(input: &Input, encoding: Encoding) -> usize {
+    let content = match input {
+        Input::Message(message) => encoding.count(&message.content),
+        Input::Template(template) => encoding.count(template.template.source()),
+        Input::FunctionCall(call) => encoding.count(&call.name) + encoding.count(&call.arguments),
+        Input::FunctionCallOutput(output) => match &output.output {
+            FunctionOutput::Text(text) => encoding.count(text),
+            FunctionOutput::Content(items) => items
+                .iter()
+                .map(|item| match item {
+                    FunctionOutputContent::InputText { text } => encoding.count(text),
+                    FunctionOutputContent::InputImage { .. } => IMAGE_TOKENS,
+                })
+                .sum(),
+        },
+    };
+
+    content + INPUT_OVERHEAD
+}
+
+/// The tokens of a tool definition, including its parameter schema.
+pub fn tool_tokens(tool: &Tool, encoding: Encoding) -> usize {
+    let Tool::Function(function) = tool;
+
+    let description = function.description.as_deref().map_or(0, |d| encoding.count(d));
+    let parameters = encoding.count(&function.parameters.as_value().to_string());
+
+    encoding.count(&function.name) + description + parameters + TOOL_OVERHEAD
+}
+
+/// The tokens of a request: its input, tools and output schema.
+pub fn request_tokens(request: &CreateResponse) -> usize {
+    let encoding = Encoding::for_model(request.model.as_deref().unwrap_or_default());
+
+    let input: usize = request.input.iter().flatten().map(|input| input_tokens(input, encoding)).sum();
+    let tools: usize = request.tools.iter().flatten().map(|tool| tool_tokens(tool, encoding)).sum();
+    let format = request.text.as_ref().map_or(0, |text| format_tokens(text, encoding));
+
+    input + tools + format + REPLY_OVERHEAD
+}
+
+fn format_tokens(text: &Text, encoding: Encoding) -> usize {
+    match &text.format {
+        TextFormat::JsonSchema(format) => {
+            let description = format.description.as_deref().map_or(0, |d| encoding.count(d));
+            encoding.count(&format.name) + description + encoding.count(&format.schema.as_value().to_string())
+        }
+        TextFormat::Text | TextFormat::JsonObject => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{InputMessage, Role, ToolFunction};
+    use serde_json::json;
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(Encoding::for_model("gpt-4o-mini"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("gpt-4.1"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("o3-mini"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("gpt-4-turbo"), Encoding::Cl100kBase);
+        assert_eq!(Encoding::for_model("gpt-35-turbo"), Encoding::Cl100kBase);
+        assert_eq!(Encoding::for_model("gpt-4"), Encoding::Cl100kBase);
+        assert_eq!(Encoding::for_model("gpt-4.5-preview"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("gpt-4o"), Encoding::O200kBase);
+        assert_eq!(Encoding::for_model("my-deployment"), Encoding::O200kBase);
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_counts_with_the_embedded_encodings() {
+        assert_eq!(Encoding::O200kBase.count("Hello, world!"), 4);
+        assert_eq!(Encoding::Cl100kBase.count("Hello, world!"), 4);
+        assert_eq!(Encoding::O200kBase.count(""), 0);
+
+        // The encodings differ on less common text
+        let text = "Привет, как дела? 東京の天気はどうですか";
+        assert!(Encoding::O200kBase.count(text) < Encoding::Cl100kBase.count(text));
+    }
+
+    #[test]
+    fn test_request_tokens_include_tools_and_schema() {
+        let message = Input::Message(InputMessage {
+            role: Role::User,
+            content: "What's the weather in Paris?".to_string(),
+        });
+
+        let mut request = CreateResponse {
+            model: Some("gpt-4o".to_string()),
+            input: Some(vec![message.clone()]),
+            ..Default::default()
+        };
+        let input_only = request_tokens(&request);
+        assert_eq!(input_only, input_tokens(&message, Encoding::O200kBase) + REPLY_OVERHEAD);
+
+        let tool = Tool::Function(ToolFunction {
+            name: "get_weather".to_string(),
+            description: Some("Get weather information for a city".to_string()),
+            parameters: crate::schema::from_value(json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            }))
+            .unwrap(),
+            strict: None,
+        });
+        request.tools = Some(vec![tool.clone()]);
+
+        assert_eq!(request_tokens(&request), input_only + tool_tokens(&tool, Encoding::O200kBase));
+        assert!(tool_tokens(&tool, Encoding::O200kBase) > 20);
+    }
+}
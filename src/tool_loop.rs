@@ -0,0 +1,290 @@
+//! Automatic tool loop: send a request, run the calls the model makes, and repeat
+//! until the model answers.
+//!
+//! Calls to tools marked `#[tool(requires_approval)]` never run unattended. The loop
+//! pauses with [`ToolLoop::PendingApproval`] before running any call of that turn, and
+//! continues once the caller has approved, denied or edited the calls.
+//!
+//! ```no_run
+//! use responses::{tool, Client, Result, ToolLoop, Toolbox};
+//! # use responses::provider::ProviderBuilder;
+//!
+//! #[tool(requires_approval)]
+//! /// Refund an order
+//! async fn refund(order_id: String) -> Result<String> {
+//!     Ok(format!("Refunded {}", order_id))
+//! }
+//!
+//! # #[tokio::main] async fn main() -> Result<()> {
+//! # let client = Client::new(responses::azure().from_env()?.build()?);
+//! let toolbox = Toolbox::new().register(refund_handler());
+//!
+//! let mut outcome = client.text().user("Refund order 42").run_tools(&toolbox).await?;
+//!
+//! while let ToolLoop::PendingApproval(pending) = outcome {
+//!     for call in pending.awaiting_approval() {
+//!         println!("{} wants to run with {}", call.name(), call.arguments);
+//!     }
+//!     outcome = pending.approve_all().resume(&client).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    cancel::RequestGuard,
+    client::Client,
+    error::{Error, Result},
+    messages::Messages,
+    options::Options,
+    provider::Provider,
+    response::Response,
+    toolbox::{Toolbox, call_inputs},
+    types::{FunctionOutput, OutputFunctionCall},
+};
+use serde_json::Value;
+use std::time::Duration;
+
+/// The state of the tool loop after it stopped.
+pub enum ToolLoop {
+    /// The model answered without calling more tools.
+    Completed {
+        response: Response<String>,
+        /// The whole conversation, ending with the model's answer.
+        messages: Messages,
+    },
+
+    /// The model called tools that require approval. No call of this turn has run yet.
+    PendingApproval(PendingApproval),
+}
+
+/// What to do with a call waiting for approval.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision {
+    /// Run the call as requested.
+    Approve,
+
+    /// Don't run the call; the model receives the reason as an error output.
+    Deny(String),
+
+    /// Run the call with these arguments instead.
+    Edit(Value),
+}
+
+/// A function call of a paused turn.
+#[derive(Clone, Debug)]
+pub struct PendingCall {
+    pub call: OutputFunctionCall,
+
+    /// The parsed arguments, or the raw string when they are not valid JSON.
+    pub arguments: Value,
+
+    pub requires_approval: bool,
+
+    decision: Option<Decision>,
+}
+
+impl PendingCall {
+    fn new(call: OutputFunctionCall, requires_approval: bool) -> Self {
+        let arguments = serde_json::from_str(&call.arguments)
+            .unwrap_or_else(|_| Value::String(call.arguments.clone()));
+
+        Self {
+            call,
+            arguments,
+            requires_approval,
+            decision: None,
+        }
+    }
+
+    pub fn call_id(&self) -> &str {
+        &self.call.call_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.call.name
+    }
+
+    pub fn decision(&self) -> Option<&Decision> {
+        self.decision.as_ref()
+    }
+}
+
+/// A turn paused until its calls requiring approval are decided.
+///
+/// Calls requiring approval that are left undecided are denied on [`resume`](Self::resume).
+/// Other calls of the turn run as usual unless a decision is made for them too.
+///
+/// The loop's timeout is paused while waiting, so time spent deciding doesn't count.
+pub struct PendingApproval {
+    options: Options,
+    toolbox: Toolbox,
+    calls: Vec<PendingCall>,
+    turns: usize,
+    guard: RequestGuard,
+    remaining: Option<Duration>,
+}
+
+impl PendingApproval {
+    /// All calls of the paused turn, in the order the model made them.
+    pub fn calls(&self) -> &[PendingCall] {
+        &self.calls
+    }
+
+    /// The calls that require approval.
+    pub fn awaiting_approval(&self) -> impl Iterator<Item = &PendingCall> {
+        self.calls.iter().filter(|call| call.requires_approval)
+    }
+
+    /// The conversation up to the paused turn.
+    pub fn messages(&self) -> Messages {
+        Messages::from_inputs(self.options.input.clone().unwrap_or_default())
+    }
+
+    /// Record the decision for the call with `call_id`. Unknown ids are ignored.
+    pub fn decide(mut self, call_id: &str, decision: Decision) -> Self {
+        if let Some(call) = self.calls.iter_mut().find(|call| call.call_id() == call_id) {
+            call.decision = Some(decision);
+        }
+        self
+    }
+
+    pub fn approve(self, call_id: &str) -> Self {
+        self.decide(call_id, Decision::Approve)
+    }
+
+    pub fn deny<S: Into<String>>(self, call_id: &str, reason: S) -> Self {
+        self.decide(call_id, Decision::Deny(reason.into()))
+    }
+
+    /// Approve the call with `call_id`, running it with `arguments` instead of the model's.
+    pub fn edit(self, call_id: &str, arguments: Value) -> Self {
+        self.decide(call_id, Decision::Edit(arguments))
+    }
+
+    /// Approve every call that requires approval.
+    pub fn approve_all(mut self) -> Self {
+        for call in self.calls.iter_mut().filter(|call| call.requires_approval) {
+            call.decision = Some(Decision::Approve);
+        }
+        self
+    }
+
+    /// Deny every call that requires approval.
+    pub fn deny_all<S: Into<String>>(mut self, reason: S) -> Self {
+        let reason = reason.into();
+        for call in self.calls.iter_mut().filter(|call| call.requires_approval) {
+            call.decision = Some(Decision::Deny(reason.clone()));
+        }
+        self
+    }
+
+    /// Run the calls as decided and continue the loop.
+    ///
+    /// Edited calls appear in the conversation with the edited arguments.
+    pub async fn resume<P: Provider>(self, client: &Client<P>) -> Result<ToolLoop> {
+        let Self { mut options, toolbox, calls, turns, guard, remaining } = self;
+        let guard = guard.resumed(remaining);
+
+        let mut executed = Vec::with_capacity(calls.len());
+        let mut outputs: Vec<Option<FunctionOutput>> = Vec::with_capacity(calls.len());
+        let mut approved = Vec::new();
+
+        for pending in calls {
+            let mut call = pending.call;
+
+            let denied = match pending.decision {
+                Some(Decision::Deny(reason)) => Some(format!("The call was denied: {}", reason)),
+                None if pending.requires_approval => Some("The call was not approved".to_string()),
+                Some(Decision::Edit(arguments)) => {
+                    call.arguments = arguments.to_string();
+                    None
+                }
+                Some(Decision::Approve) | None => None,
+            };
+
+            match denied {
+                Some(reason) => outputs.push(Some(FunctionOutput::error(reason))),
+                None => {
+                    approved.push(call.clone());
+                    outputs.push(None);
+                }
+            }
+            executed.push(call);
+        }
+
+        let mut results = guard.run(toolbox.outputs(&approved)).await?.into_iter();
+        let outputs = outputs
+            .into_iter()
+            .map(|output| output.unwrap_or_else(|| results.next().expect("one output per approved call")))
+            .collect();
+
+        options.input.get_or_insert_default().extend(call_inputs(&executed, outputs));
+
+        run_guarded(client, toolbox, options, turns, guard).await
+    }
+}
+
+/// Send requests and run the calls until the model answers or a call requires approval.
+///
+/// The timeout and cancellation token of `options` apply to the whole loop: every
+/// model turn and every tool call.
+pub(crate) async fn run<P: Provider>(
+    client: &Client<P>,
+    toolbox: Toolbox,
+    options: Options,
+    turns: usize,
+) -> Result<ToolLoop> {
+    let guard = RequestGuard::new(options.timeout, options.cancel_token.clone());
+    run_guarded(client, toolbox, options, turns, guard).await
+}
+
+async fn run_guarded<P: Provider>(
+    client: &Client<P>,
+    toolbox: Toolbox,
+    mut options: Options,
+    mut turns: usize,
+    guard: RequestGuard,
+) -> Result<ToolLoop> {
+    loop {
+        if turns >= toolbox.turn_limit() {
+            return Err(Error::ToolLoopLimit { turns });
+        }
+
+        let response = client.text_with_guard(options.clone(), &guard).await?;
+        turns += 1;
+
+        if !response.has_function_calls() {
+            let mut messages = Messages::from_inputs(options.input.unwrap_or_default());
+            if let Some(text) = response.text_message() {
+                messages = messages.assistant(text.clone());
+            }
+
+            return Ok(ToolLoop::Completed { response, messages });
+        }
+
+        let calls = response.function_calls;
+
+        if calls.iter().any(|call| toolbox.requires_approval(&call.name)) {
+            let calls = calls
+                .into_iter()
+                .map(|call| {
+                    let requires_approval = toolbox.requires_approval(&call.name);
+                    PendingCall::new(call, requires_approval)
+                })
+                .collect();
+
+            return Ok(ToolLoop::PendingApproval(PendingApproval {
+                options,
+                toolbox,
+                calls,
+                turns,
+                remaining: guard.remaining(),
+                guard,
+            }));
+        }
+
+        let outputs = guard.run(toolbox.outputs(&calls)).await?;
+        options.input.get_or_insert_default().extend(call_inputs(&calls, outputs));
+    }
+}
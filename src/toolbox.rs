@@ -0,0 +1,363 @@
+//! Registry of tool handlers with dispatch by name.
+//!
+//! A [`Toolbox`] holds handlers generated by `#[tool]` behind the object-safe
+//! [`ToolboxHandler`] trait, so tools with different parameters, outputs and
+//! contexts can be offered to the model and called together. Outputs are
+//! converted with [`ToolOutput`].
+
+use crate::{
+    error::{Error, Result},
+    functions::{CallHandler, ContextCallHandler, ToolOutput},
+    types::{FunctionOutput, Input, InputFunctionCallOutput, OutputFunctionCall, Tool},
+};
+use std::{
+    future::{Future, poll_fn},
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+};
+
+const DEFAULT_MAX_TURNS: usize = 10;
+
+/// The future returned by [`ToolboxHandler::call`].
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<FunctionOutput>> + Send + 'a>>;
+
+/// An object-safe tool handler returning the output for the model.
+///
+/// Implemented for every `#[tool]` handler without a context parameter whose output
+/// implements [`ToolOutput`]. Handlers with a context are added with [`Toolbox::register_with_context`].
+pub trait ToolboxHandler: Send + Sync {
+    /// The name of the function this handler manages
+    fn name(&self) -> &str;
+
+    /// Get the tool definition for this function
+    fn tool(&self) -> Tool;
+
+    /// Whether calls must be approved before they run.
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    /// Call the function with the arguments of `call`.
+    fn call<'a>(&'a self, call: &'a OutputFunctionCall) -> ToolFuture<'a>;
+}
+
+impl<H> ToolboxHandler for H
+where
+    H: CallHandler,
+    H::Output: ToolOutput,
+{
+    fn name(&self) -> &str {
+        crate::functions::FunctionHandler::name(self)
+    }
+
+    fn tool(&self) -> Tool {
+        crate::functions::FunctionHandler::tool(self)
+    }
+
+    fn requires_approval(&self) -> bool {
+        crate::functions::FunctionHandler::requires_approval(self)
+    }
+
+    fn call<'a>(&'a self, call: &'a OutputFunctionCall) -> ToolFuture<'a> {
+        Box::pin(async move {
+            CallHandler::call(self, call).await?.into_output()
+        })
+    }
+}
+
+/// A handler bound to its context.
+struct WithContext<H: ContextCallHandler> {
+    handler: H,
+    context: Arc<H::Context>,
+}
+
+impl<H> ToolboxHandler for WithContext<H>
+where
+    H: ContextCallHandler,
+    H::Context: Send + Sync,
+    H::Output: ToolOutput,
+{
+    fn name(&self) -> &str {
+        self.handler.name()
+    }
+
+    fn tool(&self) -> Tool {
+        self.handler.tool()
+    }
+
+    fn requires_approval(&self) -> bool {
+        self.handler.requires_approval()
+    }
+
+    fn call<'a>(&'a self, call: &'a OutputFunctionCall) -> ToolFuture<'a> {
+        Box::pin(async move {
+            self.handler.call_with_context(call, &self.context).await?.into_output()
+        })
+    }
+}
+
+/// A set of tool handlers that can be offered to the model and dispatched by name.
+///
+/// ```no_run
+/// use responses::{tool, Result, Toolbox};
+///
+/// #[tool]
+/// /// Get weather information for a city
+/// async fn get_weather(city: String) -> Result<String> {
+///     Ok(format!("Weather in {}: Sunny", city))
+/// }
+///
+/// # async fn example(calls: Vec<responses::types::OutputFunctionCall>) -> Result<()> {
+/// let toolbox = Toolbox::new().register(get_weather_handler()).parallelism(4);
+///
+/// // Pass `toolbox.tools()` to the request, then run the calls from the response
+/// for (call, result) in toolbox.dispatch(&calls).await {
+///     println!("{}: {:?}", call.call_id, result);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Toolbox {
+    handlers: Vec<Arc<dyn ToolboxHandler>>,
+    parallelism: Option<usize>,
+    report_errors: bool,
+    max_turns: Option<usize>,
+}
+
+impl Toolbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a handler. A handler with the same name replaces the previous one.
+    pub fn register<H>(mut self, handler: H) -> Self
+    where
+        H: ToolboxHandler + 'static,
+    {
+        self.insert(Arc::new(handler));
+        self
+    }
+
+    /// Add several handlers of the same type, such as the tools of an MCP server.
+    pub fn register_all<I>(mut self, handlers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToolboxHandler + 'static,
+    {
+        for handler in handlers {
+            self.insert(Arc::new(handler));
+        }
+        self
+    }
+
+    /// Add a handler for a function with a context parameter, which is called with `context`.
+    pub fn register_with_context<H>(mut self, handler: H, context: Arc<H::Context>) -> Self
+    where
+        H: ContextCallHandler + 'static,
+        H::Context: Send + Sync,
+        H::Output: ToolOutput,
+    {
+        self.insert(Arc::new(WithContext { handler, context }));
+        self
+    }
+
+    /// Limit how many calls [`dispatch`](Self::dispatch) runs at the same time (unlimited by default).
+    pub fn parallelism(mut self, limit: usize) -> Self {
+        self.parallelism = Some(limit.max(1));
+        self
+    }
+
+    /// Report failed calls to the model as `{"error": "..."}` outputs in [`run`](Self::run),
+    /// instead of failing the whole turn.
+    pub fn report_errors(mut self, report_errors: bool) -> Self {
+        self.report_errors = report_errors;
+        self
+    }
+
+    /// Limit how many requests the [tool loop](crate::tool_loop) sends before failing
+    /// with `Error::ToolLoopLimit` (10 by default).
+    pub fn max_turns(mut self, turns: usize) -> Self {
+        self.max_turns = Some(turns.max(1));
+        self
+    }
+
+    pub(crate) fn turn_limit(&self) -> usize {
+        self.max_turns.unwrap_or(DEFAULT_MAX_TURNS)
+    }
+
+    fn insert(&mut self, handler: Arc<dyn ToolboxHandler>) {
+        self.handlers.retain(|existing| existing.name() != handler.name());
+        self.handlers.push(handler);
+    }
+
+    /// The tool definitions to send with a request.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.handlers.iter().map(|handler| handler.tool()).collect()
+    }
+
+    /// The names of the registered tools.
+    pub fn names(&self) -> Vec<&str> {
+        self.handlers.iter().map(|handler| handler.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ToolboxHandler> {
+        self.handlers
+            .iter()
+            .find(|handler| handler.name() == name)
+            .map(|handler| handler.as_ref())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Whether the tool registered under `name` requires approval before running.
+    pub fn requires_approval(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|handler| handler.requires_approval())
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Run a single call with the handler registered under its name.
+    pub async fn call(&self, call: &OutputFunctionCall) -> Result<FunctionOutput> {
+        match self.get(&call.name) {
+            Some(handler) => handler.call(call).await,
+            None => Err(Error::FunctionNotFound {
+                name: call.name.clone(),
+            }),
+        }
+    }
+
+    /// Run all calls concurrently, up to the [`parallelism`](Self::parallelism) limit.
+    ///
+    /// Each call is returned with its result, in the order of `calls`, so calls with
+    /// the same or an empty `call_id` keep their own results. A failing call does not
+    /// affect the others.
+    pub async fn dispatch(&self, calls: &[OutputFunctionCall]) -> Vec<(OutputFunctionCall, Result<FunctionOutput>)> {
+        calls.iter().cloned().zip(self.call_all(calls).await).collect()
+    }
+
+    /// Run all calls and return the inputs continuing the conversation: each call
+    /// followed by its `function_call_output`.
+    ///
+    /// Fails with the first error unless [`report_errors`](Self::report_errors) is enabled.
+    pub async fn run(&self, calls: &[OutputFunctionCall]) -> Result<Vec<Input>> {
+        let outputs = self.outputs(calls).await?;
+
+        Ok(call_inputs(calls, outputs))
+    }
+
+    /// Run all calls, turning errors into outputs when [`report_errors`](Self::report_errors) is enabled.
+    pub(crate) async fn outputs(&self, calls: &[OutputFunctionCall]) -> Result<Vec<FunctionOutput>> {
+        self.call_all(calls)
+            .await
+            .into_iter()
+            .map(|result| match result {
+                Err(e) if self.report_errors => Ok(FunctionOutput::error(e.to_string())),
+                result => result,
+            })
+            .collect()
+    }
+
+    async fn call_all(&self, calls: &[OutputFunctionCall]) -> Vec<Result<FunctionOutput>> {
+        let limit = self.parallelism.unwrap_or(calls.len());
+
+        run_concurrently(calls.iter().map(|call| self.call(call)), limit).await
+    }
+}
+
+/// Each call followed by its `function_call_output`.
+pub(crate) fn call_inputs(calls: &[OutputFunctionCall], outputs: Vec<FunctionOutput>) -> Vec<Input> {
+    calls
+        .iter()
+        .zip(outputs)
+        .flat_map(|(call, output)| {
+            [
+                Input::FunctionCall(call.clone()),
+                Input::FunctionCallOutput(InputFunctionCallOutput {
+                    call_id: call.call_id.clone(),
+                    output,
+                }),
+            ]
+        })
+        .collect()
+}
+
+/// Poll the futures concurrently with at most `limit` in flight, returning outputs in order.
+async fn run_concurrently<I, F>(futures: I, limit: usize) -> Vec<F::Output>
+where
+    I: IntoIterator<Item = F>,
+    F: Future,
+{
+    let mut queued = futures.into_iter().enumerate();
+    let mut running: Vec<(usize, Pin<Box<F>>)> = Vec::new();
+    let mut outputs: Vec<Option<F::Output>> = Vec::new();
+
+    poll_fn(|cx| {
+        loop {
+            while running.len() < limit.max(1) {
+                let Some((index, future)) = queued.next() else {
+                    break;
+                };
+
+                outputs.push(None);
+                running.push((index, Box::pin(future)));
+            }
+
+            if running.is_empty() {
+                return Poll::Ready(());
+            }
+
+            let before = running.len();
+
+            running.retain_mut(|(index, future)| match future.as_mut().poll(cx) {
+                Poll::Ready(output) => {
+                    outputs[*index] = Some(output);
+                    false
+                }
+                Poll::Pending => true,
+            });
+
+            // Start queued futures in place of finished ones before waiting
+            if running.len() == before {
+                return Poll::Pending;
+            }
+        }
+    })
+    .await;
+
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_concurrently_keeps_order() {
+        let delays = [30, 10, 20];
+
+        let futures = delays.iter().map(|&ms| async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            ms
+        });
+
+        assert_eq!(run_concurrently(futures, 2).await, vec![30, 10, 20]);
+    }
+
+    #[tokio::test]
+    async fn test_run_concurrently_with_no_futures() {
+        let futures = std::iter::empty::<std::future::Ready<()>>();
+
+        assert!(run_concurrently(futures, 4).await.is_empty());
+    }
+}
@@ -24,6 +24,16 @@ pub struct CreateResponse {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub store: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+impl CreateResponse {
+    /// The tokens of the input, tools and output schema, counted before sending.
+    pub fn estimate_tokens(&self) -> usize {
+        crate::tokens::request_tokens(self)
+    }
 }
 
 #[derive(Copy, Clone, Debug, Serialize)]
@@ -55,6 +65,9 @@ pub enum TextFormat {
 
     #[serde(rename = "json_schema")]
     JsonSchema(TextFormatJsonSchema),
+
+    #[serde(rename = "json_object")]
+    JsonObject,
 }
 
 #[derive(Clone, Debug, Default, Serialize)]
@@ -116,29 +129,107 @@ pub enum Role {
     Developer,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum Input {
     #[serde(rename = "message")]
     Message(InputMessage),
     
-    #[serde(skip)]
+    /// A function call made by the model, sent back together with its output.
+    #[serde(rename = "function_call")]
+    FunctionCall(OutputFunctionCall),
+    
+    #[serde(rename = "function_call_output")]
+    FunctionCallOutput(InputFunctionCallOutput),
+    
+    /// A template rendered when the request is sent. Serialized with its source and
+    /// variables for persistence, never sent to the API as is.
+    #[serde(rename = "template")]
     Template(TemplateInput),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TemplateInput {
     pub role: Role,
     pub template: crate::prompt::PromptTemplate,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InputMessage {
     pub role: Role,
 
     pub content: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InputFunctionCallOutput {
+    pub call_id: String,
+
+    #[serde(serialize_with = "FunctionOutput::serialize", deserialize_with = "FunctionOutput::deserialize")]
+    pub output: FunctionOutput,
+}
+
+/// The result of a function call as sent back to the model.
+///
+/// Deliberately not `Serialize`, so it can implement `ToolOutput` alongside
+/// the implementation for serializable types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FunctionOutput {
+    Text(String),
+
+    Content(Vec<FunctionOutputContent>),
+}
+
+impl FunctionOutput {
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        FunctionOutput::Text(text.into())
+    }
+
+    /// An image by URL, which may be a `data:` URL.
+    pub fn image<S: Into<String>>(image_url: S) -> Self {
+        FunctionOutput::Content(vec![FunctionOutputContent::InputImage {
+            image_url: image_url.into(),
+        }])
+    }
+
+    /// An error reported to the model as `{"error": "..."}`, so it can recover.
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        FunctionOutput::Text(serde_json::json!({ "error": message.into() }).to_string())
+    }
+
+    fn serialize<S: serde::Serializer>(output: &FunctionOutput, serializer: S) -> Result<S::Ok, S::Error> {
+        match output {
+            FunctionOutput::Text(text) => serializer.serialize_str(text),
+            FunctionOutput::Content(content) => content.serialize(serializer),
+        }
+    }
+
+    fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<FunctionOutput, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Text(String),
+            Content(Vec<FunctionOutputContent>),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => FunctionOutput::Text(text),
+            Repr::Content(content) => FunctionOutput::Content(content),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum FunctionOutputContent {
+    #[serde(rename = "input_text")]
+    InputText { text: String },
+
+    #[serde(rename = "input_image")]
+    InputImage { image_url: String },
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum Output {
@@ -176,10 +267,14 @@ pub struct OutputMessageContentRefusal {
     pub refusal: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OutputFunctionCall {
     pub arguments: String,
 
+    /// Identifies the call when its result is sent back to the model.
+    #[serde(default)]
+    pub call_id: String,
+
     pub name: String,
 }
 
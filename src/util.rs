@@ -3,6 +3,8 @@ use crate::{
     Refusal, Response,
     types::{Output, OutputMessage, OutputMessageContent, Role},
 };
+use schemars::Schema;
+use serde::Deserialize;
 
 pub fn output_to_response(output: Vec<Output>) -> Result<Response<String>> {
     let mut text: Option<String> = None;
@@ -83,3 +85,32 @@ pub fn output_to_response(output: Vec<Output>) -> Result<Response<String>> {
 
     Ok(response)
 }
+
+pub fn parse_structured<T>(response: Response<String>, schema: &Schema, validate_schema: bool) -> Result<Response<T>>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    let Response { message, function_calls } = response;
+
+    let message = match message {
+        Some(result) => match result {
+            Ok(string) => {
+                let parsed = if validate_schema {
+                    let value = serde_json::from_str::<serde_json::Value>(&string)
+                        .map_err(Error::Json)?;
+                    crate::validation::validate(schema, &value)?;
+                    serde_json::from_value::<T>(value)
+                        .map_err(Error::Json)?
+                } else {
+                    serde_json::from_str::<T>(&string)
+                        .map_err(Error::Json)?
+                };
+                Some(Ok(parsed))
+            }
+            Err(refusal) => Some(Err(refusal)),
+        },
+        None => None,
+    };
+
+    Ok(Response { message, function_calls })
+}
@@ -0,0 +1,463 @@
+//! JSON Schema validation for structured model output.
+//!
+//! Validates a `serde_json::Value` against the subset of JSON Schema that
+//! [`schema::from`](crate::schema::from) produces, collecting every violation
+//! instead of stopping at the first one.
+
+use crate::error::{Error, Result};
+use regex::Regex;
+use schemars::Schema;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+/// A single schema violation found in a JSON document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value (empty for the document root).
+    pub path: String,
+
+    /// Human-readable description of the violation.
+    pub message: String,
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Validate `value` against `schema`, returning every violation found.
+pub fn violations(schema: &Schema, value: &Value) -> Vec<SchemaViolation> {
+    let root = schema.as_value();
+    let mut validator = Validator {
+        root,
+        violations: Vec::new(),
+        patterns: HashMap::new(),
+        entered: HashSet::new(),
+    };
+    validator.validate(root, value, String::new());
+    validator.violations
+}
+
+/// Validate `value` against `schema`.
+///
+/// Returns [`Error::SchemaValidation`] listing all violations if the value does not conform.
+pub fn validate(schema: &Schema, value: &Value) -> Result<()> {
+    let violations = violations(schema, value);
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::SchemaValidation { violations })
+    }
+}
+
+struct Validator<'s> {
+    root: &'s Value,
+    violations: Vec<SchemaViolation>,
+    /// Compiled `pattern` keywords, or `None` for invalid ones, so each is compiled once.
+    patterns: HashMap<&'s str, Option<Regex>>,
+    /// The `$ref`s being followed, with the instance path they were entered at.
+    entered: HashSet<(&'s str, String)>,
+}
+
+impl<'s> Validator<'s> {
+    fn report<S: Into<String>>(&mut self, path: &str, message: S) {
+        self.violations.push(SchemaViolation {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn validate(&mut self, schema: &'s Value, value: &Value, path: String) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.report(&path, "no value is allowed here");
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = schema.get("$ref") {
+            match self.resolve(reference) {
+                // A reference reached again without descending into the value would never end
+                Some(_) if self.entered.contains(&(reference.as_str(), path.clone())) => {
+                    self.report(&path, format!("schema reference '{}' refers back to itself", reference));
+                }
+                Some(target) => {
+                    let key = (reference.as_str(), path.clone());
+                    self.entered.insert(key.clone());
+                    self.validate(target, value, path.clone());
+                    self.entered.remove(&key);
+                }
+                None => self.report(&path, format!("unresolvable schema reference '{}'", reference)),
+            }
+        }
+
+        if let Some(expected) = schema.get("type")
+            && !type_matches(expected, value)
+        {
+            self.report(&path, format!("expected {}, found {}", describe_type(expected), json_type(value)));
+            return;
+        }
+
+        if let Some(Value::Array(allowed)) = schema.get("enum")
+            && !allowed.contains(value)
+        {
+            self.report(&path, format!("value {} is not one of {}", value, Value::Array(allowed.clone())));
+        }
+
+        if let Some(constant) = schema.get("const")
+            && constant != value
+        {
+            self.report(&path, format!("expected constant {}, found {}", constant, value));
+        }
+
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = schema.get(keyword) {
+                let matching = branches.iter().filter(|branch| self.matches(branch, value)).count();
+
+                if matching == 0 {
+                    self.report(&path, "value does not match any of the allowed schemas");
+                } else if keyword == "oneOf" && matching > 1 {
+                    self.report(&path, format!("value matches {} schemas but exactly one is allowed", matching));
+                }
+            }
+        }
+
+        if let Some(Value::Array(branches)) = schema.get("allOf") {
+            for branch in branches {
+                self.validate(branch, value, path.clone());
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.validate_object(schema, object, &path),
+            Value::Array(items) => self.validate_array(schema, items, &path),
+            Value::String(string) => self.validate_string(schema, string, &path),
+            Value::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    self.validate_number(schema, number, &path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_object(&mut self, schema: &'s Map<String, Value>, object: &Map<String, Value>, path: &str) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.report(&pointer(path, name), "missing required property");
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        for (name, child) in object {
+            let child_path = pointer(path, name);
+
+            if let Some(property) = properties.and_then(|properties| properties.get(name)) {
+                self.validate(property, child, child_path);
+                continue;
+            }
+
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => self.report(&child_path, "unexpected property"),
+                Some(additional @ Value::Object(_)) => self.validate(additional, child, child_path),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_array(&mut self, schema: &'s Map<String, Value>, items: &[Value], path: &str) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            self.report(path, format!("expected at least {} items, found {}", min, items.len()));
+        }
+
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && (items.len() as u64) > max
+        {
+            self.report(path, format!("expected at most {} items, found {}", max, items.len()));
+        }
+
+        let prefix = match schema.get("prefixItems") {
+            Some(Value::Array(prefix)) => prefix.as_slice(),
+            _ => &[],
+        };
+
+        for (index, item) in items.iter().enumerate() {
+            let item_path = pointer(path, &index.to_string());
+
+            if let Some(item_schema) = prefix.get(index) {
+                self.validate(item_schema, item, item_path);
+            } else if let Some(item_schema) = schema.get("items") {
+                self.validate(item_schema, item, item_path);
+            }
+        }
+    }
+
+    fn validate_string(&mut self, schema: &'s Map<String, Value>, string: &str, path: &str) {
+        let length = string.chars().count() as u64;
+
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && length < min
+        {
+            self.report(path, format!("expected at least {} characters, found {}", min, length));
+        }
+
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && length > max
+        {
+            self.report(path, format!("expected at most {} characters, found {}", max, length));
+        }
+
+        if let Some(Value::String(pattern)) = schema.get("pattern") {
+            let regex = self
+                .patterns
+                .entry(pattern.as_str())
+                .or_insert_with(|| Regex::new(pattern).ok());
+
+            match regex {
+                Some(regex) if !regex.is_match(string) => {
+                    self.report(path, format!("value does not match pattern '{}'", pattern));
+                }
+                Some(_) => {}
+                None => self.report(path, format!("schema pattern '{}' is not a valid regular expression", pattern)),
+            }
+        }
+    }
+
+    fn validate_number(&mut self, schema: &'s Map<String, Value>, number: f64, path: &str) {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64)
+            && number < minimum
+        {
+            self.report(path, format!("value {} is less than minimum {}", number, minimum));
+        }
+
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64)
+            && number > maximum
+        {
+            self.report(path, format!("value {} is greater than maximum {}", number, maximum));
+        }
+
+        if let Some(minimum) = schema.get("exclusiveMinimum").and_then(Value::as_f64)
+            && number <= minimum
+        {
+            self.report(path, format!("value {} must be greater than {}", number, minimum));
+        }
+
+        if let Some(maximum) = schema.get("exclusiveMaximum").and_then(Value::as_f64)
+            && number >= maximum
+        {
+            self.report(path, format!("value {} must be less than {}", number, maximum));
+        }
+    }
+
+    /// Check whether `value` matches `schema` without recording violations.
+    fn matches(&mut self, schema: &'s Value, value: &Value) -> bool {
+        let outer = std::mem::take(&mut self.violations);
+        self.validate(schema, value, String::new());
+        let matched = self.violations.is_empty();
+        self.violations = outer;
+        matched
+    }
+
+    /// Resolve a local `#/...` reference against the root schema.
+    fn resolve(&self, reference: &str) -> Option<&'s Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+}
+
+fn type_matches(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(name) => type_name_matches(name, value),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| type_name_matches(name, value)),
+        _ => true,
+    }
+}
+
+fn type_name_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(number) => {
+                number.is_i64() || number.is_u64() || number.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::String(name) => name.clone(),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.to_string(),
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Append a reference token to a JSON pointer, escaping `~` and `/`.
+fn pointer(base: &str, token: &str) -> String {
+    format!("{}/{}", base, token.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(JsonSchema, Deserialize)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[derive(JsonSchema, Deserialize)]
+    #[allow(dead_code)]
+    enum Status {
+        Active,
+        Inactive,
+    }
+
+    #[derive(JsonSchema, Deserialize)]
+    #[allow(dead_code)]
+    struct Person {
+        name: String,
+        age: u32,
+        status: Status,
+        addresses: Vec<Address>,
+    }
+
+    #[test]
+    fn test_valid_value_has_no_violations() {
+        let schema = crate::schema::from::<Person>();
+        let value = json!({
+            "name": "Ada",
+            "age": 36,
+            "status": "Active",
+            "addresses": [{"city": "London", "zip": null}]
+        });
+
+        assert!(validate(&schema, &value).is_ok());
+    }
+
+    #[test]
+    fn test_collects_every_violation_with_paths() {
+        let schema = crate::schema::from::<Person>();
+        let value = json!({
+            "age": "thirty",
+            "status": "Retired",
+            "addresses": [{"zip": 12345}, {"city": "Paris", "country": "FR"}]
+        });
+
+        let paths: Vec<String> = violations(&schema, &value)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+
+        assert!(paths.contains(&"/name".to_string()));
+        assert!(paths.contains(&"/age".to_string()));
+        assert!(paths.contains(&"/status".to_string()));
+        assert!(paths.contains(&"/addresses/0/city".to_string()));
+        assert!(paths.contains(&"/addresses/0/zip".to_string()));
+        assert!(paths.contains(&"/addresses/1/country".to_string()));
+    }
+
+    #[test]
+    fn test_validation_error_lists_violations() {
+        let schema = crate::schema::from::<Address>();
+
+        match validate(&schema, &json!({"zip": "12345"})) {
+            Err(Error::SchemaValidation { violations }) => {
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].path, "/city");
+                assert_eq!(violations[0].to_string(), "/city: missing required property");
+            }
+            other => panic!("expected schema validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        let schema: Schema = serde_json::from_value(json!({
+            "type": "array",
+            "items": {"type": "string", "pattern": "^[A-Z]{3}-\\d+$"}
+        }))
+        .unwrap();
+
+        let value = json!(["ABC-1", "abc-2", "DEF-3", "GHI"]);
+        let paths: Vec<String> = violations(&schema, &value).into_iter().map(|violation| violation.path).collect();
+        assert_eq!(paths, vec!["/1", "/3"]);
+
+        let invalid: Schema = serde_json::from_value(json!({"type": "string", "pattern": "("})).unwrap();
+        for _ in 0..2 {
+            let found = violations(&invalid, &json!("x"));
+            assert_eq!(found[0].message, "schema pattern '(' is not a valid regular expression");
+        }
+    }
+
+    #[test]
+    fn test_reference_cycles_are_reported() {
+        let tree: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {"children": {"type": "array", "items": {"$ref": "#"}}}
+        }))
+        .unwrap();
+        assert!(validate(&tree, &json!({"children": [{"children": [{}]}]})).is_ok());
+
+        let itself: Schema = serde_json::from_value(json!({"$ref": "#"})).unwrap();
+        let found = violations(&itself, &json!({"a": 1}));
+        assert_eq!(found[0].message, "schema reference '#' refers back to itself");
+
+        let cycle: Schema = serde_json::from_value(json!({
+            "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"anyOf": [{"$ref": "#/$defs/a"}]}},
+            "$ref": "#/$defs/a"
+        }))
+        .unwrap();
+        assert!(!violations(&cycle, &json!(1)).is_empty());
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        assert_eq!(pointer("", "a/b~c"), "/a~1b~0c");
+    }
+}
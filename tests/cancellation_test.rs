@@ -0,0 +1,135 @@
+//! Tests for request timeouts and cancellation.
+
+#[cfg(test)]
+mod cancellation_tests {
+    use responses::{CancellationToken, Client, Error, Provider, Result};
+    use responses::stream::{ResponseStream, StreamEvent};
+    use responses::types::{CreateResponse, Output};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    /// Provider that answers after a delay, recording whether the answer was delivered.
+    struct SlowProvider {
+        delay: Duration,
+        finished: AtomicBool,
+    }
+
+    impl SlowProvider {
+        fn new(delay: Duration) -> Self {
+            Self {
+                delay,
+                finished: AtomicBool::new(false),
+            }
+        }
+
+        fn output() -> Result<Vec<Output>> {
+            Ok(serde_json::from_value(json!([{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": "{}"}]
+            }]))?)
+        }
+    }
+
+    impl Provider for SlowProvider {
+        type Config = ();
+
+        async fn create_response(&self, _request: &CreateResponse) -> Result<Vec<Output>> {
+            tokio::time::sleep(self.delay).await;
+            self.finished.store(true, Ordering::SeqCst);
+            Self::output()
+        }
+
+        async fn create_response_stream(&self, _request: &CreateResponse) -> Result<ResponseStream> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ResponseStream::from_events([StreamEvent::Completed(Self::output()?)]))
+        }
+
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_text_request_times_out() {
+        let client = Client::new(SlowProvider::new(Duration::from_secs(30)));
+
+        let result = client
+            .text()
+            .user("Hello")
+            .timeout(Duration::from_millis(20))
+            .send()
+            .await;
+
+        assert!(matches!(result, Err(Error::Timeout(timeout)) if timeout == Duration::from_millis(20)));
+        assert!(!client.provider().finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_request_completes_within_timeout() {
+        let client = Client::new(SlowProvider::new(Duration::from_millis(1)));
+
+        let response = client
+            .text()
+            .user("Hello")
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text_message().unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_structured_request_cancelled_in_flight() {
+        let client = Client::new(SlowProvider::new(Duration::from_secs(30)));
+        let token = CancellationToken::new();
+
+        let canceller = token.clone();
+        let cancel = async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        };
+
+        let request = client
+            .structured::<Value>()
+            .user("Hello")
+            .json_object()
+            .cancel_token(token.clone())
+            .send();
+
+        let (result, ()) = tokio::join!(request, cancel);
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(token.is_cancelled());
+        assert!(!client.provider().finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_already_cancelled_token_skips_request() {
+        let client = Client::new(SlowProvider::new(Duration::from_millis(1)));
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = client.text().user("Hello").cancel_token(token).send().await;
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(!client.provider().finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_stream_respects_timeout() {
+        let client = Client::new(SlowProvider::new(Duration::from_secs(30)));
+
+        let result = client
+            .structured::<Value>()
+            .user("Hello")
+            .json_object()
+            .timeout(Duration::from_millis(20))
+            .send_stream()
+            .await;
+
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+}
@@ -0,0 +1,173 @@
+//! Tests for compacting conversations into summaries.
+
+#[cfg(test)]
+mod compaction_tests {
+    use responses::context::CompactionPolicy;
+    use responses::types::{CreateResponse, FunctionOutput, Input, OutputFunctionCall, Output, Role};
+    use responses::{CancellationToken, Client, Error, Messages, Options, Provider, Result};
+    use serde_json::{json, Value};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Provider answering with scripted summaries and recording every request.
+    struct SummaryProvider {
+        summaries: Mutex<VecDeque<&'static str>>,
+        requests: Mutex<Vec<Value>>,
+    }
+
+    impl SummaryProvider {
+        fn new(summaries: Vec<&'static str>) -> Self {
+            Self {
+                summaries: Mutex::new(summaries.into()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Provider for SummaryProvider {
+        type Config = ();
+
+        async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+            self.requests.lock().unwrap().push(serde_json::to_value(request)?);
+            let summary = self.summaries.lock().unwrap().pop_front().expect("no summary left");
+
+            Ok(serde_json::from_value(json!([{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": summary}]
+            }]))?)
+        }
+
+        fn name(&self) -> &'static str {
+            "summary"
+        }
+    }
+
+    fn support_session() -> Messages {
+        Messages::new()
+            .system("You are a support agent")
+            .user("My order 1234 has not arrived")
+            .function_call(OutputFunctionCall {
+                call_id: "call_1".to_string(),
+                name: "track_order".to_string(),
+                arguments: r#"{"order_id":"1234"}"#.to_string(),
+            })
+            .function_output("call_1", FunctionOutput::text("In transit, delayed"))
+            .assistant("It is delayed in transit")
+            .user("Can you refund the shipping?")
+            .assistant("I refunded the shipping")
+            .user("Thanks, when will it arrive?")
+            .assistant("By Friday")
+    }
+
+    fn contents(messages: &Messages) -> Vec<String> {
+        messages
+            .inputs()
+            .iter()
+            .map(|input| match input {
+                Input::Message(message) => message.content.clone(),
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_older_turns_are_folded_into_a_summary() {
+        let client = Client::new(SummaryProvider::new(vec!["Order 1234 is delayed; shipping was refunded."]));
+        let policy = CompactionPolicy::new("gpt-4o-mini").keep_turns(1);
+
+        let compaction = support_session().compact(&client, &policy).await.unwrap();
+
+        assert_eq!(compaction.summary.as_deref(), Some("Order 1234 is delayed; shipping was refunded."));
+        assert_eq!(compaction.folded, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            contents(&compaction.messages),
+            vec![
+                "You are a support agent",
+                "Summary of the earlier conversation:\n\nOrder 1234 is delayed; shipping was refunded.",
+                "Thanks, when will it arrive?",
+                "By Friday",
+            ]
+        );
+        assert!(matches!(&compaction.messages.inputs()[1], Input::Message(m) if m.role == Role::Developer));
+
+        let requests = client.provider().requests.lock().unwrap().clone();
+        assert_eq!(requests[0]["model"], "gpt-4o-mini");
+
+        let transcript = requests[0]["input"][1]["content"].as_str().unwrap();
+        assert!(transcript.starts_with("User: My order 1234 has not arrived\n\n"));
+        assert!(transcript.contains("Function call: track_order({\"order_id\":\"1234\"})"));
+        assert!(transcript.contains("Function output: In transit, delayed"));
+        assert!(!transcript.contains("You are a support agent"));
+        assert!(!transcript.contains("By Friday"));
+    }
+
+    #[tokio::test]
+    async fn test_earlier_summaries_are_merged() {
+        let client = Client::new(SummaryProvider::new(vec!["First summary", "Merged summary"]));
+        let policy = CompactionPolicy::new("gpt-4o-mini").keep_turns(1);
+
+        let first = support_session().compact(&client, &policy).await.unwrap();
+        let session = first.messages.user("Can I change the address?").assistant("Yes, done");
+
+        let second = session.compact(&client, &policy).await.unwrap();
+
+        assert_eq!(second.folded, vec![1, 2, 3]);
+        assert_eq!(
+            contents(&second.messages),
+            vec![
+                "You are a support agent",
+                "Summary of the earlier conversation:\n\nMerged summary",
+                "Can I change the address?",
+                "Yes, done",
+            ]
+        );
+
+        let requests = client.provider().requests.lock().unwrap().clone();
+        let transcript = requests[1]["input"][1]["content"].as_str().unwrap();
+        assert!(transcript.starts_with("Earlier summary: First summary\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_short_conversations_are_left_alone() {
+        let client = Client::new(SummaryProvider::new(vec![]));
+        let policy = CompactionPolicy::new("gpt-4o-mini");
+
+        let compaction = support_session().compact(&client, &policy).await.unwrap();
+
+        assert!(compaction.summary.is_none());
+        assert!(compaction.folded.is_empty());
+        assert_eq!(compaction.messages.len(), support_session().len());
+        assert!(client.provider().requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_options_reach_the_summary_request() {
+        let client = Client::new(SummaryProvider::new(vec!["Order 1234 is delayed"]));
+        let policy = CompactionPolicy::new("gpt-4o-mini").keep_turns(1);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = Options {
+            cancel_token: Some(token),
+            ..Default::default()
+        };
+        let result = support_session().compact_with_options(&client, &policy, cancelled).await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(client.provider().requests.lock().unwrap().is_empty());
+
+        // The model and input come from the policy, everything else from the options
+        let options = Options {
+            model: Some("ignored".to_string()),
+            safety_identifier: Some("user-42".to_string()),
+            timeout: Some(std::time::Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let compaction = support_session().compact_with_options(&client, &policy, options).await.unwrap();
+        assert_eq!(compaction.summary.as_deref(), Some("Order 1234 is delayed"));
+
+        let requests = client.provider().requests.lock().unwrap().clone();
+        assert_eq!(requests[0]["model"], "gpt-4o-mini");
+        assert_eq!(requests[0]["safety_identifier"], "user-42");
+    }
+}
@@ -0,0 +1,100 @@
+//! Tests for trimming requests to the context window.
+
+#[cfg(test)]
+mod context_policy_tests {
+    use responses::context::ContextPolicy;
+    use responses::types::{CreateResponse, Output};
+    use responses::{Client, Messages, Provider, Result};
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Provider answering every request with the same text and recording the inputs.
+    #[derive(Default)]
+    struct RecordingProvider {
+        inputs: Mutex<Vec<Value>>,
+    }
+
+    impl Provider for RecordingProvider {
+        type Config = ();
+
+        async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+            self.inputs.lock().unwrap().push(serde_json::to_value(&request.input)?);
+            Ok(serde_json::from_value(json!([{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": "{\"answer\": \"ok\"}"}]
+            }]))?)
+        }
+
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct Answer {
+        #[allow(dead_code)]
+        answer: String,
+    }
+
+    fn long_chat() -> Messages {
+        (0..50).fold(Messages::new().system("You are a helpful assistant"), |chat, i| {
+            chat.user(format!("Question {} {}", i, "with plenty of detail ".repeat(20)))
+                .assistant(format!("Answer {} {}", i, "with an equally long explanation ".repeat(20)))
+        })
+    }
+
+    fn contents(input: &Value) -> Vec<String> {
+        input
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["content"].as_str().unwrap().split(' ').take(2).collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_trimmed_before_sending() {
+        let client = Client::new(RecordingProvider::default());
+        let policy = ContextPolicy::new().max_tokens(1_000);
+
+        client
+            .text()
+            .model("gpt-4o")
+            .context_policy(policy.clone())
+            .messages(long_chat().user("Final question"))
+            .send()
+            .await
+            .unwrap();
+
+        client
+            .structured::<Answer>()
+            .model("gpt-4o")
+            .context_policy(policy)
+            .messages(long_chat().user("Final question"))
+            .send()
+            .await
+            .unwrap();
+
+        // Without a policy the whole history is sent
+        client.text().model("gpt-4o").messages(long_chat()).send().await.unwrap();
+
+        let inputs = client.provider().inputs.lock().unwrap().clone();
+
+        // The system prompt, the last whole turns that fit and the final question.
+        // The output schema of the structured request takes room from its history.
+        let kept = if cfg!(feature = "tokenizer") { [12, 10] } else { [8, 8] };
+
+        for (input, kept) in inputs[..2].iter().zip(kept) {
+            let contents = contents(input);
+            assert_eq!(contents.first().unwrap(), "You are");
+            assert_eq!(contents.last().unwrap(), "Final question");
+            assert_eq!(contents.len(), kept);
+
+            // Whole turns are dropped, so the history still starts with a question
+            assert!(contents[1].starts_with("Question"));
+        }
+
+        assert_eq!(inputs[2].as_array().unwrap().len(), 101);
+    }
+}
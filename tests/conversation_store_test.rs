@@ -0,0 +1,176 @@
+//! Tests for conversation stores and write-through conversations
+
+#[cfg(test)]
+mod conversation_store_tests {
+    use responses::store::{Conversation, ConversationStore, MemoryStore};
+    use responses::types::{FunctionOutput, Input, OutputFunctionCall};
+    use responses::Messages;
+    use std::sync::Arc;
+
+    fn contents(messages: &Messages) -> Vec<String> {
+        messages
+            .inputs()
+            .iter()
+            .map(|input| match input {
+                Input::Message(message) => message.content.clone(),
+                Input::FunctionCall(call) => call.name.clone(),
+                Input::FunctionCallOutput(output) => format!("output of {}", output.call_id),
+                Input::Template(_) => "template".to_string(),
+            })
+            .collect()
+    }
+
+    /// The behavior every store must share.
+    async fn check_store<S: ConversationStore>(store: S) {
+        assert!(store.get("alice").await.unwrap().is_none());
+        assert!(store.list().await.unwrap().is_empty());
+
+        store.put("alice", &Messages::new().system("Be brief").user("Hi")).await.unwrap();
+        store.append("alice", Messages::new().assistant("Hello!").inputs()).await.unwrap();
+
+        let call = OutputFunctionCall {
+            call_id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: "{}".to_string(),
+        };
+        let turn = Messages::new().function_call(call).function_output("call_1", FunctionOutput::text("Sunny"));
+        store.append("bob", turn.inputs()).await.unwrap();
+
+        let alice = store.get("alice").await.unwrap().unwrap();
+        assert_eq!(contents(&alice), vec!["Be brief", "Hi", "Hello!"]);
+
+        let bob = store.get("bob").await.unwrap().unwrap();
+        assert_eq!(contents(&bob), vec!["get_weather", "output of call_1"]);
+
+        assert_eq!(store.list().await.unwrap(), vec!["alice", "bob"]);
+
+        // Putting replaces the whole conversation
+        store.put("alice", &Messages::new().user("Start over")).await.unwrap();
+        let alice = store.get("alice").await.unwrap().unwrap();
+        assert_eq!(contents(&alice), vec!["Start over"]);
+
+        // An empty conversation is still stored
+        store.put("carol", &Messages::new()).await.unwrap();
+        assert!(store.get("carol").await.unwrap().unwrap().is_empty());
+
+        assert!(store.delete("alice").await.unwrap());
+        assert!(!store.delete("alice").await.unwrap());
+        assert!(store.get("alice").await.unwrap().is_none());
+        assert_eq!(store.list().await.unwrap(), vec!["bob", "carol"]);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        check_store(MemoryStore::new()).await;
+    }
+
+    #[cfg(feature = "file-store")]
+    #[tokio::test]
+    async fn test_file_store() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let store = responses::store::FileStore::new(temp_dir.path().join("sessions"));
+
+        check_store(store.clone()).await;
+
+        let bob = std::fs::read_to_string(temp_dir.path().join("sessions/bob.jsonl")).unwrap();
+        assert_eq!(bob.lines().count(), 2);
+
+        let invalid = store.get("../bob").await;
+        assert!(matches!(invalid, Err(responses::Error::InvalidSessionId { .. })));
+    }
+
+    #[cfg(feature = "file-store")]
+    #[tokio::test]
+    async fn test_file_store_concurrent_puts() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let store = responses::store::FileStore::new(temp_dir.path());
+
+        let saves = (0..16).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                let messages = Messages::new().user(format!("Message {} ", i).repeat(500));
+                store.put("alice", &messages).await
+            })
+        });
+        for save in saves.collect::<Vec<_>>() {
+            save.await.unwrap().unwrap();
+        }
+
+        // One save wins whole, and no temporary files are left behind
+        let alice = store.get("alice").await.unwrap().unwrap();
+        assert_eq!(alice.len(), 1);
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(feature = "file-store")]
+    #[tokio::test]
+    async fn test_file_store_append_waits_for_put() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let store = responses::store::FileStore::new(temp_dir.path());
+        let appended = Messages::new().assistant("Appended").into_inputs();
+
+        for round in 0..20 {
+            let saved = Messages::new().user(format!("Round {} ", round).repeat(50_000));
+
+            // The save starts first, so the append must land in the file it writes
+            let (put, append) = tokio::join!(store.put("alice", &saved), store.append("alice", &appended));
+            put.unwrap();
+            append.unwrap();
+
+            let alice = store.get("alice").await.unwrap().unwrap();
+            assert_eq!(alice.len(), 2, "round {}", round);
+        }
+    }
+
+    #[cfg(feature = "sqlite-store")]
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        check_store(responses::store::SqliteStore::in_memory().unwrap()).await;
+    }
+
+    #[cfg(feature = "sqlite-store")]
+    #[tokio::test]
+    async fn test_sqlite_sessions_resume_across_instances() {
+        use responses::store::SqliteStore;
+
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("sessions.db");
+
+        let first = Arc::new(SqliteStore::open(&path).unwrap());
+        let mut conversation = Conversation::open(first, "alice").await.unwrap();
+        conversation.user("Remember the number 7").await.unwrap();
+
+        let second = Arc::new(SqliteStore::open(&path).unwrap());
+        let mut resumed = Conversation::open(second, "alice").await.unwrap();
+        resumed.assistant("I'll remember 7.").await.unwrap();
+
+        conversation.user("Which number?").await.unwrap();
+
+        let third = SqliteStore::open(&path).unwrap();
+        let stored = third.get("alice").await.unwrap().unwrap();
+        assert_eq!(contents(&stored), vec!["Remember the number 7", "I'll remember 7.", "Which number?"]);
+    }
+
+    #[tokio::test]
+    async fn test_conversation_writes_through() {
+        let store = Arc::new(MemoryStore::new());
+
+        let mut conversation = Conversation::open(store.clone(), "alice").await.unwrap();
+        assert_eq!(conversation.session_id(), "alice");
+        assert!(conversation.messages().is_empty());
+
+        conversation.user("What's 2+2?").await.unwrap();
+        conversation.extend(Messages::new().assistant("4")).await.unwrap();
+        assert_eq!(contents(conversation.messages()), vec!["What's 2+2?", "4"]);
+
+        let resumed = Conversation::open(store.clone(), "alice").await.unwrap();
+        assert_eq!(contents(resumed.messages()), vec!["What's 2+2?", "4"]);
+
+        conversation.replace(conversation.messages().take_last(1)).await.unwrap();
+        let stored = store.get("alice").await.unwrap().unwrap();
+        assert_eq!(contents(&stored), vec!["4"]);
+
+        assert!(conversation.delete().await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
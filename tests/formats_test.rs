@@ -0,0 +1,134 @@
+//! Tests for converting conversations to and from transcript formats.
+
+#[cfg(test)]
+mod formats_tests {
+    use responses::formats::{self, FineTuningExample};
+    use responses::types::{FunctionOutput, Input, OutputFunctionCall, Tool, ToolFunction};
+    use responses::{Messages, Refusal, Response};
+    use serde_json::{json, Value};
+
+    fn weather_tool() -> Tool {
+        Tool::Function(ToolFunction {
+            name: "get_weather".to_string(),
+            description: Some("Get weather information for a city".to_string()),
+            parameters: responses::schema::from_value(json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            }))
+            .unwrap(),
+            strict: None,
+        })
+    }
+
+    fn weather_chat() -> Messages {
+        Messages::new()
+            .system("You are a helpful assistant")
+            .user("What's the weather in Paris and Rome?")
+            .function_call(OutputFunctionCall {
+                call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: r#"{"city":"Paris"}"#.to_string(),
+            })
+            .function_call(OutputFunctionCall {
+                call_id: "call_2".to_string(),
+                name: "get_weather".to_string(),
+                arguments: r#"{"city":"Rome"}"#.to_string(),
+            })
+            .function_output("call_1", FunctionOutput::text("Sunny"))
+            .function_output("call_2", FunctionOutput::text("Rainy"))
+            .assistant("Paris is sunny and Rome is rainy.")
+    }
+
+    #[test]
+    fn test_chat_completions_round_trip() {
+        let chat = weather_chat().to_chat_completions().unwrap();
+        let messages = chat.as_array().unwrap();
+
+        // Both calls belong to one assistant message
+        assert_eq!(messages.len(), 6);
+        assert_eq!(messages[2]["content"], Value::Null);
+        assert_eq!(messages[2]["tool_calls"].as_array().unwrap().len(), 2);
+        assert_eq!(messages[3], json!({"role": "tool", "tool_call_id": "call_1", "content": "Sunny"}));
+
+        let restored = Messages::from_chat_completions(&chat).unwrap();
+        assert_eq!(serde_json::to_value(restored.inputs()).unwrap(), serde_json::to_value(weather_chat().inputs()).unwrap());
+
+        // ShareGPT keeps the same conversation, with fresh call ids
+        let sharegpt = weather_chat().to_sharegpt().unwrap();
+        assert_eq!(sharegpt["conversations"][0], json!({"from": "system", "value": "You are a helpful assistant"}));
+        assert_eq!(Messages::from_sharegpt(&sharegpt).unwrap().to_chat_completions().unwrap(), chat);
+    }
+
+    #[test]
+    fn test_fine_tuning_jsonl_round_trip() {
+        let examples = vec![
+            FineTuningExample::new(weather_chat()).tools(vec![weather_tool()]),
+            FineTuningExample::new(Messages::new().user("Hi").assistant("Hello!")),
+        ];
+
+        let jsonl = formats::to_fine_tuning_jsonl(&examples).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+
+        let first: Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(first["tools"][0]["type"], "function");
+        assert_eq!(first["tools"][0]["function"]["name"], "get_weather");
+        assert!(serde_json::from_str::<Value>(jsonl.lines().nth(1).unwrap()).unwrap().get("tools").is_none());
+
+        let restored = formats::from_fine_tuning_jsonl(&format!("{}\n\n", jsonl)).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].messages.len(), 7);
+        assert_eq!(restored[0].tools.len(), 1);
+        assert_eq!(formats::to_fine_tuning_jsonl(&restored).unwrap(), jsonl);
+
+        // Tool schemas come back exactly as written in the dataset
+        let loose = r#"{"messages": [{"role": "user", "content": "Hi"}], "tools": [{"type": "function", "function": {"name": "lookup", "parameters": {"type": "object", "properties": {"id": {"type": "string"}}}}}]}"#;
+        let restored = formats::from_fine_tuning_jsonl(loose).unwrap();
+        let row: Value = serde_json::from_str(&formats::to_fine_tuning_jsonl(&restored).unwrap()).unwrap();
+        assert_eq!(row["tools"][0]["function"]["parameters"], json!({"type": "object", "properties": {"id": {"type": "string"}}}));
+
+        let invalid = formats::from_fine_tuning_jsonl("{\"messages\": []}\nnot json");
+        assert!(matches!(invalid, Err(responses::Error::InvalidTranscript(message)) if message.starts_with("line 2")));
+    }
+
+    #[derive(serde::Serialize)]
+    struct Forecast {
+        city: String,
+        sunny: bool,
+    }
+
+    #[test]
+    fn test_response_rows() {
+        let prompt = Messages::new().user("Is it sunny in Paris?");
+
+        let structured = Response {
+            message: Some(Ok(Forecast { city: "Paris".to_string(), sunny: true })),
+            function_calls: vec![],
+        };
+        let row: Value = serde_json::from_str(&prompt.to_fine_tuning_row(&structured, &[]).unwrap()).unwrap();
+        assert_eq!(row["messages"][1], json!({"role": "assistant", "content": "{\"city\":\"Paris\",\"sunny\":true}"}));
+
+        let calls = Response::<String> {
+            message: None,
+            function_calls: vec![OutputFunctionCall {
+                call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: r#"{"city":"Paris"}"#.to_string(),
+            }],
+        };
+        let row: Value = serde_json::from_str(&prompt.to_fine_tuning_row(&calls, &[weather_tool()]).unwrap()).unwrap();
+        assert_eq!(row["messages"][1]["tool_calls"][0]["function"]["name"], "get_weather");
+        assert_eq!(row["tools"].as_array().unwrap().len(), 1);
+
+        let refusal = Response::<String> {
+            message: Some(Err(Refusal::from("I can't help with that".to_string()))),
+            function_calls: vec![],
+        };
+        let row: Value = serde_json::from_str(&prompt.to_fine_tuning_row(&refusal, &[]).unwrap()).unwrap();
+        assert_eq!(row["messages"][1]["refusal"], "I can't help with that");
+
+        // Rows parse back as examples, with the answer as the last message
+        let examples = formats::from_fine_tuning_jsonl(&prompt.to_fine_tuning_row(&calls, &[]).unwrap()).unwrap();
+        assert!(matches!(examples[0].messages.last(), Some(Input::FunctionCall(call)) if call.call_id == "call_1"));
+    }
+}
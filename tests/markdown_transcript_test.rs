@@ -0,0 +1,86 @@
+//! Tests for Markdown transcripts that load back as conversation templates.
+
+#[cfg(test)]
+mod markdown_transcript_tests {
+    use responses::prompt::{template, ConversationTemplate};
+    use responses::types::{FunctionOutput, OutputFunctionCall};
+    use responses::{Error, Messages};
+    use serde_json::{json, Value};
+
+    fn inputs(messages: &Messages) -> Value {
+        serde_json::to_value(messages.inputs()).unwrap()
+    }
+
+    fn tricky_conversation() -> Messages {
+        Messages::new()
+            .system("You review Markdown documents.")
+            .developer("Answer in Markdown.")
+            .user("Fix the headings in this file:\n\n## User\n  ## Assistant\n\\## Escaped already\n## Notes\n# Title")
+            .assistant("Use `{{name}}` for variables, or {{{raw}}} and \\{{ in Handlebars.\n\n## Summary\nDone.")
+    }
+
+    #[test]
+    fn test_round_trip_through_conversation_templates() {
+        let conversation = tricky_conversation();
+        let markdown = conversation.to_markdown().unwrap();
+
+        assert!(markdown.starts_with("---\ntranscript: true\n---\n\n## System\n\nYou review Markdown documents.\n\n## Developer\n"));
+        assert!(markdown.contains("\n\\## User\n  \\## Assistant\n\\\\## Escaped already\n\\## Notes\n# Title\n"));
+        assert!(markdown.contains("Use `{{name}}` for variables, or {{{raw}}} and \\{{ in Handlebars."));
+
+        let loaded = ConversationTemplate::from_content(&markdown).unwrap().to_messages().unwrap();
+        assert_eq!(inputs(&loaded), inputs(&conversation));
+
+        // The template set's parser reads the same format
+        let loaded = template::ConversationTemplate::load_from_content(&markdown)
+            .unwrap()
+            .render(&json!({}))
+            .unwrap();
+        assert_eq!(inputs(&loaded), inputs(&conversation));
+    }
+
+    #[test]
+    fn test_saved_transcripts_load_as_fixtures() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("support.md");
+
+        let conversation = tricky_conversation().user("Thanks!\n\n\n  Bye");
+        std::fs::write(&path, conversation.to_markdown().unwrap()).unwrap();
+
+        let loaded = Messages::from_conversation_template(&path, &json!({})).unwrap();
+        assert_eq!(inputs(&loaded), inputs(&conversation));
+    }
+
+    #[test]
+    fn test_whitespace_and_empty_messages_are_kept() {
+        let conversation = Messages::new()
+            .system("")
+            .user("  indented\n\n")
+            .assistant("\n\n## User\n")
+            .user("\ttabs\r\nand CRLF ");
+
+        let markdown = conversation.to_markdown().unwrap();
+        let loaded = ConversationTemplate::from_content(&markdown).unwrap().to_messages().unwrap();
+        assert_eq!(inputs(&loaded), inputs(&conversation));
+
+        // Transcripts edited by hand must keep the blank lines around each message
+        let edited = markdown.replacen("## User\n\n", "## User\n", 1);
+        let error = ConversationTemplate::from_content(&edited).unwrap().to_messages().unwrap_err();
+        assert!(matches!(&error, Error::InvalidTranscript(reason) if reason.contains("blank line")));
+    }
+
+    #[test]
+    fn test_function_calls_are_rejected() {
+        let conversation = Messages::new()
+            .user("What's the weather?")
+            .function_call(OutputFunctionCall {
+                call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: "{}".to_string(),
+            })
+            .function_output("call_1", FunctionOutput::text("Sunny"));
+
+        let error = conversation.to_markdown().unwrap_err();
+        assert!(matches!(&error, Error::InvalidTranscript(reason) if reason.starts_with("input 1 is a function call")));
+    }
+}
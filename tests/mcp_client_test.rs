@@ -0,0 +1,226 @@
+//! Tests for connecting to MCP servers and dispatching their tools.
+
+#[cfg(test)]
+mod mcp_client_tests {
+    use responses::mcp::{McpClient, McpServer};
+    use responses::types::{FunctionOutput, OutputFunctionCall, Tool};
+    use responses::{tool, Error, Result, Toolbox};
+    use serde_json::{json, Value};
+    use std::process::Command;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tool]
+    /// Echo the text back
+    fn echo(text: String) -> Result<String> {
+        Ok(text)
+    }
+
+    #[tool]
+    /// Add two numbers
+    fn add(a: f64, b: f64) -> Result<f64> {
+        Ok(a + b)
+    }
+
+    #[tool]
+    /// Divide two numbers
+    fn divide(a: f64, b: f64) -> Result<f64> {
+        if b == 0.0 {
+            return Err(Error::FunctionValidation { reason: "division by zero".to_string() });
+        }
+        Ok(a / b)
+    }
+
+    /// A client talking newline-delimited JSON-RPC to an in-process server, as over stdio.
+    async fn calculator() -> McpClient {
+        let toolbox = Toolbox::new().register(echo_handler()).register(add_handler()).register(divide_handler());
+        let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+
+        let (reader, writer) = tokio::io::split(server_side);
+        tokio::spawn(McpServer::new(toolbox).name("calculator").serve(reader, writer));
+
+        let (reader, writer) = tokio::io::split(client_side);
+        McpClient::streams(reader, writer).await.unwrap()
+    }
+
+    fn call(call_id: &str, name: &str, arguments: Value) -> OutputFunctionCall {
+        serde_json::from_value(json!({
+            "call_id": call_id,
+            "name": name,
+            "arguments": arguments.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_server_tools() {
+        let client = calculator().await;
+        assert_eq!(client.server_name(), "calculator");
+
+        let tools = client.tools().await.unwrap();
+        let names: Vec<&str> = tools.iter().map(|tool| tool.info().name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "add", "divide"]);
+
+        let toolbox = Toolbox::new().register_all(tools);
+        let Tool::Function(add) = &toolbox.tools()[1];
+        assert_eq!(add.description.as_deref(), Some("Add two numbers"));
+        assert_eq!(serde_json::to_value(&add.parameters).unwrap()["required"], json!(["a", "b"]));
+
+        let mut results = toolbox
+            .dispatch(&[
+                call("call_1", "echo", json!({"text": "hello"})),
+                call("call_2", "add", json!({"a": 2, "b": 3.5})),
+                call("call_3", "divide", json!({"a": 1, "b": 0})),
+            ])
+            .await;
+
+        let (_, divided) = results.pop().unwrap();
+        assert_eq!(results[0].1.as_ref().unwrap(), &FunctionOutput::text("hello"));
+        assert_eq!(results[1].1.as_ref().unwrap(), &FunctionOutput::text("5.5"));
+
+        // The server flags the failure with `isError`, which becomes an error output
+        let FunctionOutput::Text(divided) = divided.unwrap() else {
+            panic!("expected a text output");
+        };
+        assert!(divided.starts_with("{\"error\":") && divided.contains("division by zero"), "{}", divided);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_and_failed_start() {
+        let client = calculator().await;
+
+        let result = client.call_tool("echo", json!({"text": "direct"})).await.unwrap();
+        assert_eq!(result, FunctionOutput::text("direct"));
+
+        let mut malformed = call("call_1", "echo", json!({}));
+        malformed.arguments = "{not json".to_string();
+        let result = Toolbox::new()
+            .register_all(client.tools().await.unwrap())
+            .call(&malformed)
+            .await;
+        assert!(matches!(result, Err(Error::FunctionParameterParsing { function_name, .. }) if function_name == "echo"));
+
+        let missing = McpClient::stdio(Command::new("definitely-not-an-mcp-server")).await;
+        assert!(matches!(missing, Err(Error::McpConnection(_))));
+    }
+
+    /// Read one HTTP request, returning its headers and JSON body.
+    async fn read_request(stream: &mut TcpStream) -> (String, Value) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+
+        loop {
+            let read = stream.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..read]);
+
+            let text = String::from_utf8_lossy(&buffer).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let headers = text[..end].to_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().unwrap())
+                    .unwrap_or(0);
+
+                if buffer.len() >= end + 4 + length {
+                    let body = serde_json::from_slice(&buffer[end + 4..end + 4 + length]).unwrap();
+                    return (headers, body);
+                }
+            }
+        }
+    }
+
+    async fn respond(stream: &mut TcpStream, status: &str, headers: &str, body: &str) {
+        let response = format!(
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    }
+
+    /// A streamable HTTP server answering with JSON, or with an event stream for `tools/list`.
+    async fn serve_http(listener: TcpListener) {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (headers, message) = read_request(&mut stream).await;
+
+            let Some(id) = message.get("id").cloned() else {
+                respond(&mut stream, "202 Accepted", "", "").await;
+                continue;
+            };
+
+            let has_session = headers.contains("mcp-session-id: session-1");
+
+            match message["method"].as_str().unwrap() {
+                "initialize" => {
+                    let body = json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {"protocolVersion": "2025-06-18", "capabilities": {}, "serverInfo": {"name": "http-server"}}
+                    });
+                    respond(
+                        &mut stream,
+                        "200 OK",
+                        "Content-Type: application/json\r\nMcp-Session-Id: session-1\r\n",
+                        &body.to_string(),
+                    )
+                    .await;
+                }
+                _ if !has_session => respond(&mut stream, "400 Bad Request", "", "missing session").await,
+                "tools/list" => {
+                    let progress = json!({"jsonrpc": "2.0", "method": "notifications/progress", "params": {}});
+                    let page = match message["params"]["cursor"].as_str() {
+                        None => json!({
+                            "tools": [{"name": "search", "inputSchema": {"type": "object", "properties": {"query": {"type": "string"}}}}],
+                            "nextCursor": "page-2"
+                        }),
+                        Some(_) => json!({"tools": [{"name": "broken", "inputSchema": "not a schema"}]}),
+                    };
+                    let result = json!({"jsonrpc": "2.0", "id": id, "result": page});
+                    let body = format!("event: message\ndata: {}\n\nevent: message\ndata: {}\n\n", progress, result);
+                    respond(&mut stream, "200 OK", "Content-Type: text/event-stream\r\n", &body).await;
+                }
+                "tools/call" => {
+                    let body = json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32602, "message": "Unknown tool"}
+                    });
+                    respond(&mut stream, "200 OK", "Content-Type: application/json\r\n", &body.to_string()).await;
+                }
+                _ => respond(&mut stream, "404 Not Found", "", "").await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamable_http_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(listener));
+
+        let client = McpClient::http(&url).await.unwrap();
+        assert_eq!(client.server_name(), "http-server");
+
+        // Both pages are listed
+        let tools = client.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].name, "search");
+
+        // The broken tool is left out, and the schema of the other is forwarded as given
+        let toolbox = Toolbox::new().register_all(client.tools().await.unwrap());
+        assert_eq!(toolbox.tools().len(), 1);
+        let Tool::Function(search) = &toolbox.tools()[0];
+        assert_eq!(
+            serde_json::to_value(&search.parameters).unwrap(),
+            json!({"type": "object", "properties": {"query": {"type": "string"}}})
+        );
+        assert!(matches!(client.tool(tools[1].clone()), Err(Error::InvalidSchema(reason)) if reason.contains("broken")));
+
+        let result = client.call_tool("missing", json!({})).await;
+        assert!(matches!(result, Err(Error::McpServer { code: -32602, message }) if message == "Unknown tool"));
+    }
+}
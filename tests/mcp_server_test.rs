@@ -0,0 +1,185 @@
+//! Tests for serving `#[tool]` handlers over MCP.
+
+#[cfg(test)]
+mod mcp_server_tests {
+    use responses::mcp::{McpClient, McpServer};
+    use responses::types::FunctionOutput;
+    use responses::{tool, Error, Result, Toolbox};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, ReadHalf, WriteHalf};
+
+    #[tool]
+    /// Add two numbers
+    fn add(a: i64, b: i64) -> Result<i64> {
+        Ok(a + b)
+    }
+
+    #[tool]
+    /// Render a chart of the given values
+    async fn chart(values: Vec<f64>) -> Result<FunctionOutput> {
+        if values.is_empty() {
+            return Err(Error::FunctionValidation { reason: "no values to chart".to_string() });
+        }
+        Ok(FunctionOutput::image("data:image/png;base64,iVBORw0KGgo="))
+    }
+
+    #[tool(requires_approval)]
+    /// Delete a user's account
+    fn delete_account(user: String) -> Result<String> {
+        Ok(format!("Deleted {}", user))
+    }
+
+    /// An in-memory connection to a server over newline-delimited JSON-RPC.
+    struct Connection {
+        lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+        writer: WriteHalf<DuplexStream>,
+    }
+
+    impl Connection {
+        fn start(server: McpServer) -> Self {
+            let (client, server_side) = tokio::io::duplex(64 * 1024);
+            let (server_reader, server_writer) = tokio::io::split(server_side);
+            tokio::spawn(server.serve(server_reader, server_writer));
+
+            let (reader, writer) = tokio::io::split(client);
+            Self {
+                lines: BufReader::new(reader).lines(),
+                writer,
+            }
+        }
+
+        async fn send(&mut self, message: &str) {
+            self.writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+        }
+
+        async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+            let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+            self.send(&request.to_string()).await;
+            self.receive().await
+        }
+
+        async fn receive(&mut self) -> Value {
+            let line = self.lines.next_line().await.unwrap().unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+    }
+
+    fn server() -> McpServer {
+        let toolbox = Toolbox::new().register(add_handler()).register(chart_handler());
+        McpServer::new(toolbox).name("math").version("2.0.0").instructions("Prefer add for sums.")
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools() {
+        let mut connection = Connection::start(server());
+
+        let response = connection
+            .request(1, "initialize", json!({"protocolVersion": "2025-03-26", "capabilities": {}, "clientInfo": {"name": "test"}}))
+            .await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"], json!({"name": "math", "version": "2.0.0"}));
+        assert_eq!(response["result"]["instructions"], "Prefer add for sums.");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+
+        // Notifications are not answered, so the next line is the response to the listing
+        connection.send(r#"{"jsonrpc": "2.0", "method": "notifications/initialized"}"#).await;
+
+        let response = connection.request(2, "tools/list", json!({})).await;
+        assert_eq!(response["id"], 2);
+
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0]["name"], "add");
+        assert_eq!(tools[0]["description"], "Add two numbers");
+        assert_eq!(tools[0]["inputSchema"]["required"], json!(["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn test_tools_call() {
+        let mut connection = Connection::start(server());
+
+        let response = connection.request(1, "tools/call", json!({"name": "add", "arguments": {"a": 2, "b": 3}})).await;
+        assert_eq!(response["result"], json!({"content": [{"type": "text", "text": "5"}], "isError": false}));
+
+        let response = connection.request(2, "tools/call", json!({"name": "chart", "arguments": {"values": [1.0]}})).await;
+        assert_eq!(
+            response["result"]["content"],
+            json!([{"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"}])
+        );
+
+        // Tool failures are results the client's model can see
+        let response = connection.request(3, "tools/call", json!({"name": "chart", "arguments": {"values": []}})).await;
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"].as_str().unwrap().contains("no values to chart"));
+
+        let response = connection.request(4, "tools/call", json!({"name": "add", "arguments": {"a": "two"}})).await;
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let mut connection = Connection::start(server());
+
+        let response = connection.request(1, "tools/call", json!({"name": "missing", "arguments": {}})).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        let response = connection.request(2, "resources/list", json!({})).await;
+        assert_eq!(response["error"]["code"], -32601);
+
+        let response = connection.request(3, "ping", json!({})).await;
+        assert_eq!(response["result"], json!({}));
+
+        connection.send("{not json").await;
+        let response = connection.receive().await;
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], -32700);
+    }
+
+    #[tokio::test]
+    async fn test_approval_tools_are_not_served() {
+        let toolbox = Toolbox::new().register(add_handler()).register(delete_account_handler());
+
+        let mut connection = Connection::start(McpServer::new(toolbox.clone()));
+        let response = connection.request(1, "tools/list", json!({})).await;
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "add");
+
+        let response = connection.request(2, "tools/call", json!({"name": "delete_account", "arguments": {"user": "alice"}})).await;
+        assert_eq!(response["error"]["code"], -32602);
+        assert!(response["error"]["message"].as_str().unwrap().contains("requires approval"));
+        assert!(response["result"].is_null());
+
+        // Opting in serves them, leaving approval to the client
+        let mut connection = Connection::start(McpServer::new(toolbox).allow_unapproved());
+        let response = connection.request(1, "tools/list", json!({})).await;
+        assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 2);
+
+        let response = connection.request(2, "tools/call", json!({"name": "delete_account", "arguments": {"user": "alice"}})).await;
+        assert_eq!(response["result"]["content"][0]["text"], "Deleted alice");
+    }
+
+    #[tokio::test]
+    async fn test_client_round_trip() {
+        let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+        let (reader, writer) = tokio::io::split(server_side);
+        tokio::spawn(server().serve(reader, writer));
+
+        let (reader, writer) = tokio::io::split(client_side);
+        let client = McpClient::streams(reader, writer).await.unwrap();
+        assert_eq!(client.server_name(), "math");
+
+        let toolbox = Toolbox::new().register_all(client.tools().await.unwrap());
+        assert_eq!(toolbox.names(), vec!["add", "chart"]);
+
+        let sum = client.call_tool("add", json!({"a": 1, "b": 4})).await.unwrap();
+        assert_eq!(sum, FunctionOutput::text("5"));
+
+        let FunctionOutput::Text(error) = client.call_tool("chart", json!({"values": []})).await.unwrap() else {
+            panic!("expected a text output");
+        };
+        let error: Value = serde_json::from_str(&error).unwrap();
+        assert!(error["error"].as_str().unwrap().contains("no values to chart"));
+    }
+}
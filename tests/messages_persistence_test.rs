@@ -0,0 +1,90 @@
+//! Tests for saving and loading conversations
+
+#[cfg(test)]
+mod messages_persistence_tests {
+    use responses::error::Error;
+    use responses::types::{FunctionOutput, Input, OutputFunctionCall};
+    use responses::Messages;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn rendered_contents(messages: &Messages) -> Vec<String> {
+        messages
+            .render_inputs()
+            .into_iter()
+            .filter_map(|input| match input {
+                Input::Message(message) => Some(message.content),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_json_round_trip_keeps_templates_unrendered() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let template_file = temp_dir.path().join("system.md");
+        fs::write(&template_file, "---\nrequired_variables: [\"name\"]\n---\nYou are helping {{name}}.")
+            .expect("Failed to write template file");
+
+        let conversation = Messages::new()
+            .system_from_md(&template_file)
+            .unwrap()
+            .var("name", "Ada")
+            .user("What's the weather in Paris?")
+            .function_call(OutputFunctionCall {
+                call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: r#"{"city":"Paris"}"#.to_string(),
+            })
+            .function_output("call_1", FunctionOutput::text("Sunny"))
+            .function_output("call_2", FunctionOutput::image("data:image/png;base64,iVBORw0KGgo="));
+
+        let path = temp_dir.path().join("session.json");
+        conversation.save_json(&path).unwrap();
+
+        // The file is readable without the template on disk
+        fs::remove_file(&template_file).unwrap();
+        let restored = Messages::load_json(&path).unwrap();
+
+        assert_eq!(restored.len(), 5);
+        assert!(matches!(&restored.inputs()[0], Input::Template(input) if input.template.source().contains("{{name}}")));
+        assert_eq!(rendered_contents(&restored), rendered_contents(&conversation));
+        assert_eq!(rendered_contents(&restored)[0].trim(), "You are helping Ada.");
+
+        let Input::FunctionCallOutput(output) = &restored.inputs()[4] else {
+            panic!("expected a function call output");
+        };
+        assert_eq!(output.output, FunctionOutput::image("data:image/png;base64,iVBORw0KGgo="));
+    }
+
+    #[test]
+    fn test_jsonl_append_and_load() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("session.jsonl");
+
+        let conversation = Messages::new().system("You are helpful").user("Hello");
+        conversation.save_jsonl(&path).unwrap();
+
+        let conversation = conversation.assistant("Hi there!");
+        conversation.take_last(1).append_jsonl(&path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+        assert!(contents.ends_with("{\"type\":\"message\",\"role\":\"assistant\",\"content\":\"Hi there!\"}\n"));
+
+        let restored = Messages::load_jsonl(&path).unwrap();
+        assert_eq!(rendered_contents(&restored), vec!["You are helpful", "Hello", "Hi there!"]);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+        let missing = Messages::load_json(temp_dir.path().join("missing.json"));
+        assert!(matches!(missing, Err(Error::ConversationFile { .. })));
+
+        let jsonl = "{\"type\":\"message\",\"role\":\"user\",\"content\":\"Hi\"}\n\n{\"type\":\"message\",\"role\":\"robot\"}\n";
+        let invalid = Messages::from_jsonl(jsonl);
+        assert!(matches!(invalid, Err(Error::ConversationLine { line: 3, .. })));
+    }
+}
@@ -94,6 +94,7 @@ Hello {{role}}!"#).expect("Failed to write template file");
         let function_call = OutputFunctionCall {
             name: "get_weather".to_string(),
             arguments: json!({"city": "Paris"}).to_string(),
+            call_id: "call_1".to_string(),
         };
 
         let response: Response<String> = Response {
@@ -137,6 +138,7 @@ Hello {{role}}!"#).expect("Failed to write template file");
         let function_call = OutputFunctionCall {
             name: "search_docs".to_string(),
             arguments: json!({"query": "rust async"}).to_string(),
+            call_id: "call_1".to_string(),
         };
 
         let response: Response<String> = Response {
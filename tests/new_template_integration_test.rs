@@ -229,6 +229,7 @@ Main content
     }
 
     #[test]
+    #[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
     fn test_template_set_with_base_path() {
         use std::path::PathBuf;
         use responses::prompt::template::TemplateSet;
@@ -359,6 +360,7 @@ i18n_key: "system.title"
     }
 
     #[test]
+    #[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
     fn test_template_set_locale_switching() {
         use std::path::PathBuf;
         use responses::prompt::template::TemplateSet;
@@ -0,0 +1,173 @@
+//! Tests for redacting personal data before it reaches a provider.
+
+#[cfg(test)]
+mod redaction_tests {
+    use responses::redact::{RedactingProvider, Redactor};
+    use responses::prompt::PromptTemplate;
+    use responses::types::{CreateResponse, FunctionOutput, Input, OutputFunctionCall, Output, Role, TemplateInput};
+    use responses::{Client, Messages, Provider, Refusal, Response, Result};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Provider answering with a fixed output and recording every request.
+    struct RecordingProvider {
+        output: Value,
+        requests: Mutex<Vec<Value>>,
+    }
+
+    impl RecordingProvider {
+        fn new(output: Value) -> Self {
+            Self {
+                output,
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Provider for RecordingProvider {
+        type Config = ();
+
+        async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+            self.requests.lock().unwrap().push(serde_json::to_value(request)?);
+            Ok(serde_json::from_value(self.output.clone())?)
+        }
+
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+    }
+
+    fn text_output(text: &str) -> Value {
+        json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "output_text", "text": text}]
+        })
+    }
+
+    fn redactor() -> Redactor {
+        Redactor::standard().pattern("customer", r"\bCUST-\d{4}\b").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_provider_sees_placeholders_and_caller_sees_originals() {
+        let provider = RecordingProvider::new(json!([
+            text_output("I'll email [EMAIL_1] about [CUSTOMER_1]."),
+            {
+                "type": "function_call",
+                "call_id": "call_1",
+                "name": "send_email",
+                "arguments": "{\"to\":\"[EMAIL_1]\",\"card\":\"[CARD_NUMBER_1]\",\"cc\":\"[EMAIL_9]\"}"
+            }
+        ]));
+        let client = Client::new(RedactingProvider::new(provider, redactor()));
+
+        let conversation = Messages::new()
+            .system("You handle billing for customer CUST-0042.")
+            .user("Refund card 4242-4242-4242-4242 and email \"jo\"@example.com? No: jo@example.com")
+            .function_call(OutputFunctionCall {
+                call_id: "call_0".to_string(),
+                name: "lookup".to_string(),
+                arguments: r#"{"email":"jo@example.com"}"#.to_string(),
+            })
+            .function_output("call_0", FunctionOutput::text("Phone on file: 020 7946 0958"));
+
+        let response = client.text().model("gpt-4o").messages(conversation).send().await.unwrap();
+
+        let requests = client.provider().inner().requests.lock().unwrap().clone();
+        let input = &requests[0]["input"];
+        assert_eq!(input[0]["content"], "You handle billing for customer [CUSTOMER_1].");
+        assert_eq!(input[1]["content"], "Refund card [CARD_NUMBER_1] and email \"jo\"@example.com? No: [EMAIL_1]");
+        assert_eq!(input[2]["arguments"], r#"{"email":"[EMAIL_1]"}"#);
+        assert_eq!(input[3]["output"], "Phone on file: [PHONE_1]");
+        assert!(!requests[0].to_string().contains("jo@example.com"));
+
+        assert_eq!(response.message.unwrap().unwrap(), "I'll email jo@example.com about CUST-0042.");
+
+        // Arguments stay valid JSON, and unknown placeholders are left alone
+        let arguments: Value = serde_json::from_str(&response.function_calls[0].arguments).unwrap();
+        assert_eq!(arguments, json!({"to": "jo@example.com", "card": "4242-4242-4242-4242", "cc": "[EMAIL_9]"}));
+        assert_eq!(client.provider().name(), "recording");
+    }
+
+    #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, PartialEq)]
+    struct Contact {
+        name: String,
+        emails: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_is_restored() {
+        let provider = RecordingProvider::new(json!([
+            text_output(r#"{"name":"Jo","emails":["[EMAIL_1]","[EMAIL_2]"]}"#)
+        ]));
+        let client = Client::new(RedactingProvider::new(provider, redactor()));
+
+        let response = client
+            .structured::<Contact>()
+            .user("Jo writes from jo@example.com and jo+work@example.com")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.message.unwrap().unwrap(),
+            Contact {
+                name: "Jo".to_string(),
+                emails: vec!["jo@example.com".to_string(), "jo+work@example.com".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_templates_are_rendered_before_redaction() {
+        let provider = RedactingProvider::new(RecordingProvider::new(json!([text_output("ok")])), redactor());
+        let template = |source: &str| PromptTemplate::from_content(source).unwrap();
+
+        let request = CreateResponse {
+            input: Some(vec![Input::Template(TemplateInput {
+                role: Role::User,
+                template: template("Email {{email}}").var("email", "jo@example.com"),
+            })]),
+            ..Default::default()
+        };
+        provider.create_response(&request).await.unwrap();
+
+        let requests = provider.inner().requests.lock().unwrap().clone();
+        assert_eq!(requests[0]["input"][0]["content"], "Email [EMAIL_1]");
+
+        // A template that cannot be rendered is never sent
+        let request = CreateResponse {
+            input: Some(vec![Input::Template(TemplateInput { role: Role::User, template: template("Email {{email}}") })]),
+            ..Default::default()
+        };
+        let result = provider.create_response(&request).await;
+        assert!(matches!(result, Err(responses::Error::MessageRendering { index: 0, .. })));
+        assert_eq!(provider.inner().requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_redacting_messages_without_middleware() {
+        let template = PromptTemplate::from_content("Reach {{name}} at {{email}} or {{email}}").unwrap();
+        let conversation = Messages::new()
+            .extend_inputs(vec![Input::Template(TemplateInput { role: Role::User, template })])
+            .var("name", "Jo")
+            .var("email", "jo@example.com");
+
+        let (redacted, map) = conversation.redact(&redactor()).unwrap();
+        assert_eq!(
+            redacted.to_chat_completions().unwrap()[0]["content"],
+            "Reach Jo at [EMAIL_1] or [EMAIL_1]"
+        );
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.original("[EMAIL_1]"), Some("jo@example.com"));
+
+        let response = Response::<Contact> {
+            message: Some(Err(Refusal::from("I won't contact [EMAIL_1].".to_string()))),
+            function_calls: vec![],
+        };
+        let restored = map.restore_response(response).unwrap();
+        assert_eq!(String::from(restored.message.unwrap().unwrap_err()), "I won't contact jo@example.com.");
+    }
+}
@@ -0,0 +1,332 @@
+//! Tests for structured output request formats and validation.
+
+#[cfg(test)]
+mod structured_output_tests {
+    use responses::{schema, Client, Error, Provider, Result};
+    use responses::schemars::JsonSchema;
+    use responses::stream::{PartialEvent, ResponseStream, StreamEvent};
+    use responses::types::{CreateResponse, Output};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Provider that records the request body and replies with a fixed output text.
+    struct MockProvider {
+        reply: String,
+        last_request: Mutex<Option<Value>>,
+    }
+
+    impl MockProvider {
+        fn new(reply: Value) -> Self {
+            Self {
+                reply: reply.to_string(),
+                last_request: Mutex::new(None),
+            }
+        }
+
+        fn last_request(&self) -> Value {
+            self.last_request.lock().unwrap().clone().expect("no request was sent")
+        }
+    }
+
+    impl Provider for MockProvider {
+        type Config = ();
+
+        async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+            *self.last_request.lock().unwrap() = Some(serde_json::to_value(request)?);
+
+            let output = json!([{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": self.reply}]
+            }]);
+
+            Ok(serde_json::from_value(output)?)
+        }
+
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+    }
+
+    #[derive(Debug, JsonSchema, Deserialize)]
+    struct Weather {
+        temperature: f64,
+        condition: String,
+    }
+
+    #[tokio::test]
+    async fn test_default_format_is_strict_json_schema() {
+        let client = Client::new(MockProvider::new(json!({"temperature": 21.5, "condition": "Sunny"})));
+
+        let response = client.structured::<Weather>().user("Weather?").send().await.unwrap();
+        let weather = response.text_message().unwrap();
+        assert_eq!(weather.temperature, 21.5);
+        assert_eq!(weather.condition, "Sunny");
+
+        let format = &client.provider().last_request()["text"]["format"];
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["name"], "Weather");
+        assert_eq!(format["strict"], true);
+        assert!(format.get("description").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_non_strict_format_with_description() {
+        let client = Client::new(MockProvider::new(json!({"temperature": 21.5, "condition": "Sunny"})));
+
+        client
+            .structured::<Weather>()
+            .user("Weather?")
+            .strict(false)
+            .description("Current weather conditions")
+            .send()
+            .await
+            .unwrap();
+
+        let format = &client.provider().last_request()["text"]["format"];
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["strict"], false);
+        assert_eq!(format["description"], "Current weather conditions");
+    }
+
+    #[tokio::test]
+    async fn test_json_object_format_with_value() {
+        let client = Client::new(MockProvider::new(json!({"anything": [1, 2, 3]})));
+
+        let response = client
+            .structured::<Value>()
+            .user("Reply with any JSON")
+            .json_object()
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text_message().unwrap()["anything"], json!([1, 2, 3]));
+        assert_eq!(client.provider().last_request()["text"]["format"], json!({"type": "json_object"}));
+    }
+
+    #[tokio::test]
+    async fn test_schema_validation_reports_all_violations() {
+        let client = Client::new(MockProvider::new(json!({"temperature": "warm"})));
+
+        let result = client
+            .structured::<Weather>()
+            .user("Weather?")
+            .validate_schema(true)
+            .send()
+            .await;
+
+        match result {
+            Err(Error::SchemaValidation { violations }) => {
+                let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+                assert_eq!(paths.len(), 2);
+                assert!(paths.contains(&"/temperature"));
+                assert!(paths.contains(&"/condition"));
+            }
+            other => panic!("expected schema validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_without_validation_serde_error_is_returned() {
+        let client = Client::new(MockProvider::new(json!({"temperature": "warm"})));
+
+        let result = client.structured::<Weather>().user("Weather?").send().await;
+
+        assert!(matches!(result, Err(Error::Json(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_schema_applies_strict_transforms() {
+        let client = Client::new(MockProvider::new(json!({"vendor": "ACME", "amount": 12})));
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "vendor": {"type": "string"},
+                "amount": {"type": "integer"}
+            },
+            "required": ["vendor", "amount"]
+        });
+
+        let response = client
+            .structured_dynamic("Extraction", schema)
+            .unwrap()
+            .user("Extract the vendor")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text_message().unwrap()["vendor"], "ACME");
+
+        let format = &client.provider().last_request()["text"]["format"];
+        assert_eq!(format["name"], "Extraction");
+        assert_eq!(format["schema"]["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_dynamic_schema_must_be_object() {
+        let client = Client::new(MockProvider::new(json!({})));
+
+        let result = client.structured_dynamic("Invalid", json!(true));
+
+        assert!(matches!(result, Err(Error::InvalidSchema(_))));
+    }
+
+    #[test]
+    fn test_load_schema_from_file() {
+        let schema = schema::load("tests/fixtures/schemas/invoice.json").unwrap();
+        let schema = schema.as_value();
+
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["properties"]["currency"]["enum"], json!(["EUR"]));
+        assert!(schema["properties"]["payment"].get("oneOf").is_none());
+        assert_eq!(schema["properties"]["payment"]["anyOf"][0]["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_load_missing_schema_file() {
+        let result = schema::load("tests/fixtures/schemas/missing.json");
+
+        assert!(matches!(result, Err(Error::SchemaFileRead { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_loaded_schema_validates_output() {
+        let client = Client::new(MockProvider::new(json!({"number": "INV-1", "total": 10.0, "currency": "USD", "payment": null})));
+        let schema = schema::load("tests/fixtures/schemas/invoice.json").unwrap();
+
+        let result = client
+            .structured_with_schema("Invoice", schema)
+            .user("Extract the invoice")
+            .validate_schema(true)
+            .send()
+            .await;
+
+        match result {
+            Err(Error::SchemaValidation { violations }) => {
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].path, "/currency");
+            }
+            other => panic!("expected schema validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    /// Provider that streams a fixed output text in small fragments.
+    struct StreamingProvider {
+        fragments: Vec<&'static str>,
+    }
+
+    impl Provider for StreamingProvider {
+        type Config = ();
+
+        async fn create_response(&self, _request: &CreateResponse) -> Result<Vec<Output>> {
+            unreachable!("streaming requests must use create_response_stream")
+        }
+
+        async fn create_response_stream(&self, request: &CreateResponse) -> Result<ResponseStream> {
+            assert_eq!(request.stream, Some(true));
+
+            let text: String = self.fragments.concat();
+            let output = serde_json::from_value(json!([{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": text}]
+            }]))?;
+
+            let mut events: Vec<StreamEvent> = self.fragments
+                .iter()
+                .map(|fragment| StreamEvent::TextDelta(fragment.to_string()))
+                .collect();
+            events.push(StreamEvent::Completed(output));
+
+            Ok(ResponseStream::from_events(events))
+        }
+
+        fn name(&self) -> &'static str {
+            "streaming"
+        }
+    }
+
+    #[derive(Debug, JsonSchema, Deserialize)]
+    struct Entities {
+        entities: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_send_stream_yields_partial_snapshots() {
+        let client = Client::new(StreamingProvider {
+            fragments: vec![r#"{"enti"#, r#"ties": ["Al"#, r#"ice", "#, r#""Bob"#, r#""]}"#],
+        });
+
+        let mut stream = client
+            .structured::<Entities>()
+            .user("Extract the people")
+            .send_stream()
+            .await
+            .unwrap();
+
+        let mut snapshots = Vec::new();
+        let mut completed = None;
+
+        while let Some(event) = stream.next().await {
+            match event.unwrap() {
+                PartialEvent::Partial(snapshot) => snapshots.push(snapshot),
+                PartialEvent::Completed(response) => completed = Some(response),
+            }
+        }
+
+        assert_eq!(snapshots, vec![
+            json!({}),
+            json!({"entities": ["Al"]}),
+            json!({"entities": ["Alice"]}),
+            json!({"entities": ["Alice", "Bob"]}),
+        ]);
+
+        let response = completed.expect("stream should complete");
+        assert_eq!(response.text_message().unwrap().entities, vec!["Alice", "Bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_unrendered_templates_are_not_sent() {
+        use responses::prompt::PromptTemplate;
+        use responses::types::{Input, Role, TemplateInput};
+        use responses::Messages;
+
+        let template = PromptTemplate::from_content("Report on {{topic}}").unwrap();
+        let conversation = Messages::new()
+            .system("Be brief")
+            .extend_inputs(vec![Input::Template(TemplateInput { role: Role::User, template })]);
+        let client = Client::new(MockProvider::new(json!({"entities": []})));
+
+        let result = client.text().messages(conversation.clone()).send().await;
+        assert!(matches!(result, Err(Error::MessageRendering { index: 1, .. })));
+
+        let result = client.structured::<Entities>().messages(conversation.clone()).send().await;
+        assert!(matches!(result, Err(Error::MessageRendering { index: 1, .. })));
+        assert!(client.provider().last_request.lock().unwrap().is_none());
+
+        client.text().messages(conversation.var("topic", "rust")).send().await.unwrap();
+        assert_eq!(client.provider().last_request()["input"][1]["content"], "Report on rust");
+    }
+
+    #[tokio::test]
+    async fn test_send_stream_falls_back_for_non_streaming_providers() {
+        let client = Client::new(MockProvider::new(json!({"entities": ["Carol"]})));
+
+        let response = client
+            .structured::<Entities>()
+            .user("Extract the people")
+            .send_stream()
+            .await
+            .unwrap()
+            .into_response()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text_message().unwrap().entities, vec!["Carol"]);
+        assert_eq!(client.provider().last_request()["stream"], true);
+    }
+}
@@ -178,4 +178,43 @@ Hello {{user.name}}, I am your {{role}}."#;
         let rendered = result.unwrap();
         assert_eq!(rendered.trim(), "Hello Bob, I am your assistant.");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_error_names_the_failing_message() {
+        use responses::types::{Input, Role, TemplateInput};
+        use responses::Messages;
+
+        let content = r#"---
+variables:
+  role: "assistant"
+---
+Hello {{name}}, I am your {{role}}."#;
+
+        let template = PromptTemplate::from_content(content).unwrap();
+        let conversation = Messages::new()
+            .system("You are helpful")
+            .user("Hi")
+            .extend_inputs(vec![Input::Template(TemplateInput { role: Role::Assistant, template })]);
+
+        match conversation.try_render_inputs().unwrap_err() {
+            Error::MessageRendering { index, template, source } => {
+                assert_eq!(index, 2);
+                assert_eq!(template, "Hello {{name}}, I am your {{role}}.");
+                assert!(matches!(*source, Error::TemplateVariableNotFound { ref name } if name == "name"));
+            }
+            e => panic!("Expected MessageRendering error, got: {:?}", e),
+        }
+
+        // No placeholder text: the template is kept unrendered
+        let inputs = conversation.render_inputs();
+        assert!(matches!(inputs[2], Input::Template(_)));
+        assert!(conversation.estimate_tokens("gpt-4o").is_err());
+
+        // With the variable set, every message renders
+        let inputs = conversation.var("name", "Alice").try_render_inputs().unwrap();
+        match &inputs[2] {
+            Input::Message(message) => assert_eq!(message.content.trim(), "Hello Alice, I am your assistant."),
+            other => panic!("Expected a message, got: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Tests for the automatic tool loop and approval of sensitive calls.
+
+#[cfg(test)]
+mod tool_loop_tests {
+    use responses::functions::FunctionHandler;
+    use responses::tool_loop::Decision;
+    use responses::types::{CreateResponse, Output};
+    use responses::{tool, CancellationToken, Client, Error, Provider, Result, ToolLoop, Toolbox};
+    use serde_json::{json, Value};
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Provider answering with scripted outputs and recording every request.
+    struct ScriptedProvider {
+        outputs: Mutex<VecDeque<Value>>,
+        requests: Mutex<Vec<Value>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(outputs: Vec<Value>) -> Self {
+            Self {
+                outputs: Mutex::new(outputs.into()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn requests(&self) -> Vec<Value> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl Provider for ScriptedProvider {
+        type Config = ();
+
+        async fn create_response(&self, request: &CreateResponse) -> Result<Vec<Output>> {
+            self.requests.lock().unwrap().push(serde_json::to_value(request)?);
+            let output = self.outputs.lock().unwrap().pop_front().expect("no scripted output left");
+            Ok(serde_json::from_value(output)?)
+        }
+
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+    }
+
+    fn text(text: &str) -> Value {
+        json!([{
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "output_text", "text": text}]
+        }])
+    }
+
+    fn calls(calls: &[(&str, &str, Value)]) -> Value {
+        calls
+            .iter()
+            .map(|(call_id, name, arguments)| json!({
+                "type": "function_call",
+                "call_id": call_id,
+                "name": name,
+                "arguments": arguments.to_string(),
+            }))
+            .collect()
+    }
+
+    static REFUNDS: AtomicUsize = AtomicUsize::new(0);
+
+    #[tool]
+    /// Look up an order
+    fn lookup_order(order_id: String) -> Result<Value> {
+        Ok(json!({"order_id": order_id, "total": 40}))
+    }
+
+    #[tool(requires_approval)]
+    /// Refund an order
+    async fn refund(order_id: String, amount: u32) -> Result<String> {
+        REFUNDS.fetch_add(1, Ordering::SeqCst);
+        Ok(format!("Refunded {} for {}", amount, order_id))
+    }
+
+    static SLOW_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[tool]
+    /// Check the warehouse, which takes a while
+    async fn check_warehouse(item: String) -> Result<String> {
+        let delay = if item == "crate" { 2000 } else { 40 };
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        SLOW_CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(format!("{} in stock", item))
+    }
+
+    fn toolbox() -> Toolbox {
+        Toolbox::new()
+            .register(lookup_order_handler())
+            .register(refund_handler())
+    }
+
+    /// The `function_call_output` for `call_id` in a recorded request.
+    fn output_of(request: &Value, call_id: &str) -> String {
+        request["input"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|input| input["type"] == "function_call_output" && input["call_id"] == call_id)
+            .map(|input| input["output"].as_str().unwrap().to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_requires_approval_flag() {
+        assert!(refund_handler().requires_approval());
+        assert!(!lookup_order_handler().requires_approval());
+        assert!(toolbox().requires_approval("refund"));
+        assert!(!toolbox().requires_approval("unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_loop_runs_calls_until_the_model_answers() {
+        let client = Client::new(ScriptedProvider::new(vec![
+            calls(&[("call_1", "lookup_order", json!({"order_id": "42"}))]),
+            text("Order 42 costs 40."),
+        ]));
+
+        let outcome = client.text().user("How much was order 42?").run_tools(&toolbox()).await.unwrap();
+
+        let ToolLoop::Completed { response, messages } = outcome else {
+            panic!("expected the loop to complete");
+        };
+        assert_eq!(response.text_message().unwrap(), "Order 42 costs 40.");
+        assert_eq!(messages.len(), 4);
+
+        let requests = client.provider().requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["tools"].as_array().unwrap().len(), 2);
+        assert_eq!(output_of(&requests[1], "call_1"), r#"{"order_id":"42","total":40}"#);
+    }
+
+    #[tokio::test]
+    async fn test_loop_pauses_for_approval_and_resumes() {
+        let client = Client::new(ScriptedProvider::new(vec![
+            calls(&[
+                ("call_1", "lookup_order", json!({"order_id": "42"})),
+                ("call_2", "refund", json!({"order_id": "42", "amount": 40})),
+            ]),
+            text("Refunded."),
+        ]));
+        let before = REFUNDS.load(Ordering::SeqCst);
+
+        let outcome = client.text().user("Refund order 42").run_tools(&toolbox()).await.unwrap();
+
+        let ToolLoop::PendingApproval(pending) = outcome else {
+            panic!("expected the loop to wait for approval");
+        };
+        assert_eq!(pending.calls().len(), 2);
+
+        let awaiting: Vec<_> = pending.awaiting_approval().collect();
+        assert_eq!(awaiting.len(), 1);
+        assert_eq!(awaiting[0].name(), "refund");
+        assert_eq!(awaiting[0].arguments, json!({"order_id": "42", "amount": 40}));
+
+        // Nothing of the paused turn has run
+        assert_eq!(REFUNDS.load(Ordering::SeqCst), before);
+        assert_eq!(pending.messages().len(), 1);
+
+        let pending = pending.edit("call_2", json!({"order_id": "42", "amount": 25}));
+        assert!(matches!(pending.calls()[1].decision(), Some(Decision::Edit(_))));
+
+        let outcome = pending.resume(&client).await.unwrap();
+        assert!(matches!(outcome, ToolLoop::Completed { .. }));
+
+        let requests = client.provider().requests();
+        assert_eq!(output_of(&requests[1], "call_1"), r#"{"order_id":"42","total":40}"#);
+        assert_eq!(output_of(&requests[1], "call_2"), "Refunded 25 for 42");
+
+        // The conversation shows the arguments the call ran with
+        let call = requests[1]["input"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|input| input["type"] == "function_call" && input["call_id"] == "call_2")
+            .unwrap();
+        assert_eq!(call["arguments"], r#"{"amount":25,"order_id":"42"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_denied_and_undecided_calls_do_not_run() {
+        let client = Client::new(ScriptedProvider::new(vec![
+            calls(&[
+                ("call_1", "refund", json!({"order_id": "42", "amount": 40})),
+                ("call_2", "refund", json!({"order_id": "43", "amount": 10})),
+            ]),
+            text("I could not refund the orders."),
+        ]));
+
+        let ToolLoop::PendingApproval(pending) = client.text().user("Refund both").run_tools(&toolbox()).await.unwrap() else {
+            panic!("expected the loop to wait for approval");
+        };
+
+        let outcome = pending.deny("call_1", "refunds above 30 need a manager").resume(&client).await.unwrap();
+        assert!(matches!(outcome, ToolLoop::Completed { .. }));
+
+        let requests = client.provider().requests();
+        let denied: Value = serde_json::from_str(&output_of(&requests[1], "call_1")).unwrap();
+        assert_eq!(denied, json!({"error": "The call was denied: refunds above 30 need a manager"}));
+
+        let undecided: Value = serde_json::from_str(&output_of(&requests[1], "call_2")).unwrap();
+        assert_eq!(undecided, json!({"error": "The call was not approved"}));
+    }
+
+    #[tokio::test]
+    async fn test_loop_stops_after_max_turns() {
+        let lookup = || calls(&[("call_1", "lookup_order", json!({"order_id": "42"}))]);
+        let client = Client::new(ScriptedProvider::new(vec![lookup(), lookup(), lookup()]));
+
+        let result = client.text().user("Loop forever").run_tools(&toolbox().max_turns(2)).await;
+
+        assert!(matches!(result, Err(Error::ToolLoopLimit { turns: 2 })));
+        assert_eq!(client.provider().requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_covers_the_whole_loop() {
+        let check = || calls(&[("call_1", "check_warehouse", json!({"item": "box"}))]);
+        let client = Client::new(ScriptedProvider::new(vec![check(), check(), check(), check(), text("In stock.")]));
+        let toolbox = Toolbox::new().register(check_warehouse_handler());
+
+        // Each turn fits the timeout, the whole loop doesn't
+        let result = client
+            .text()
+            .user("Check the box")
+            .timeout(Duration::from_millis(100))
+            .run_tools(&toolbox)
+            .await;
+
+        assert!(matches!(result, Err(Error::Timeout(timeout)) if timeout == Duration::from_millis(100)));
+        assert!(client.provider().requests().len() < 4);
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_stops_running_tools() {
+        let client = Client::new(ScriptedProvider::new(vec![
+            calls(&[("call_1", "check_warehouse", json!({"item": "crate"}))]),
+            text("In stock."),
+        ]));
+        let toolbox = Toolbox::new().register(check_warehouse_handler());
+        let before = SLOW_CALLS.load(Ordering::SeqCst);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        let result = client.text().user("Check the crate").cancel_token(token).run_tools(&toolbox).await;
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(client.provider().requests().len(), 1);
+        assert_eq!(SLOW_CALLS.load(Ordering::SeqCst), before);
+    }
+}
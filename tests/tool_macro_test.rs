@@ -0,0 +1,241 @@
+//! Tests for the schemas and handlers generated by the `#[tool]` macro.
+
+#[cfg(test)]
+mod tool_macro_tests {
+    use responses::{tool, Error, Result, Toolbox};
+    use responses::functions::{FunctionHandler, Validate};
+    use responses::schemars::JsonSchema;
+    use responses::types::{OutputFunctionCall, Tool};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+    pub struct Address {
+        street: String,
+        #[serde(rename = "postalCode")]
+        postal_code: String,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Priority {
+        Low,
+        High,
+    }
+
+    #[tool]
+    /// Create a delivery order
+    async fn create_order(
+        items: Vec<Address>,
+        priority: Priority,
+        note: Option<String>,
+        #[serde(rename = "maxCost")] max_cost: f64,
+    ) -> Result<String> {
+        Ok(format!("{} items, {:?}, {:?}, {}", items.len(), priority, note, max_cost))
+    }
+
+    #[tool]
+    /// Search the product catalog
+    async fn search_products(
+        /// Free-text search query
+        query: String,
+        #[param(description = "Sort order", enum = ["price", "rating"])] sort: Option<String>,
+        #[param(minimum = 1, maximum = 50)] limit: u32,
+        #[param(pattern = "^[A-Z]{2}$")] country: String,
+        #[param(enum = [1, 2, 3])] tier: i32,
+    ) -> Result<usize> {
+        Ok(query.len() + sort.map_or(0, |s| s.len()) + limit as usize + country.len() + tier as usize)
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+    pub struct Filter {
+        field: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    }
+
+    #[tool("filter_rows", strict)]
+    /// Filter table rows
+    async fn filter(filter: Filter, #[serde(default)] limit: Option<u32>) -> Result<String> {
+        Ok(format!("{} {:?} {:?}", filter.field, filter.value, limit))
+    }
+
+    #[tool(strict = false)]
+    /// Ping the server
+    async fn ping(host: String) -> Result<String> {
+        Ok(host)
+    }
+
+    #[tool(validate)]
+    /// Book a room for a date range
+    async fn book(check_in: String, check_out: String) -> Result<String> {
+        Ok(format!("{}..{}", check_in, check_out))
+    }
+
+    impl Validate for BookParams {
+        fn validate(&self) -> std::result::Result<(), String> {
+            if self.check_out <= self.check_in {
+                return Err("check_out must be after check_in".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    #[tool(validate = check_transfer)]
+    /// Transfer money between accounts
+    fn transfer(from: String, to: String, amount: f64) -> Result<String> {
+        Ok(format!("{} from {} to {}", amount, from, to))
+    }
+
+    fn check_transfer(params: &TransferParams) -> std::result::Result<(), String> {
+        if params.from == params.to {
+            return Err(format!("cannot transfer from {} to itself", params.from));
+        }
+        if params.amount <= 0.0 {
+            return Err("amount must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    fn parameters(tool: Tool) -> Value {
+        let Tool::Function(function) = tool;
+        serde_json::to_value(function.parameters).unwrap()
+    }
+
+    fn call(name: &str, arguments: Value) -> OutputFunctionCall {
+        serde_json::from_value(json!({
+            "name": name,
+            "arguments": arguments.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_schema_is_derived_from_parameter_types() {
+        let schema = parameters(create_order_handler().tool());
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema.get("title").is_none());
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["items"]["type"], "array");
+        assert_eq!(properties["items"]["items"]["$ref"], "#/$defs/Address");
+        assert_eq!(properties["priority"]["$ref"], "#/$defs/Priority");
+        assert_eq!(properties["note"]["type"], json!(["string", "null"]));
+        assert_eq!(properties["maxCost"]["type"], "number");
+
+        let address = &schema["$defs"]["Address"];
+        assert_eq!(address["additionalProperties"], false);
+        assert!(address["properties"].get("postalCode").is_some());
+
+        assert_eq!(schema["$defs"]["Priority"]["enum"], json!(["low", "high"]));
+
+        // Optional parameters are nullable rather than omitted
+        let required = schema["required"].as_array().unwrap();
+        for name in ["items", "priority", "note", "maxCost"] {
+            assert!(required.contains(&json!(name)), "{} should be required", name);
+        }
+    }
+
+    #[test]
+    fn test_parameter_descriptions_and_constraints() {
+        let schema = parameters(search_products_handler().tool());
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["query"]["description"], "Free-text search query");
+        assert_eq!(properties["sort"]["description"], "Sort order");
+        assert_eq!(properties["sort"]["enum"], json!(["price", "rating", null]));
+        assert_eq!(properties["limit"]["minimum"], 1);
+        assert_eq!(properties["limit"]["maximum"], 50);
+        assert_eq!(properties["country"]["pattern"], "^[A-Z]{2}$");
+        assert_eq!(properties["tier"]["enum"], json!([1, 2, 3]));
+
+        assert!(properties["limit"].get("description").is_none());
+    }
+
+    #[test]
+    fn test_strict_tool_requires_every_property() {
+        let Tool::Function(function) = filter_handler().tool();
+        assert_eq!(function.name, "filter_rows");
+        assert_eq!(function.strict, Some(true));
+
+        let schema = serde_json::to_value(function.parameters).unwrap();
+        assert_eq!(schema["required"], json!(["filter", "limit"]));
+        assert_eq!(schema["properties"]["limit"]["type"], json!(["integer", "null"]));
+
+        let filter = &schema["$defs"]["Filter"];
+        assert_eq!(filter["required"], json!(["field", "value"]));
+        assert_eq!(filter["properties"]["value"]["type"], json!(["string", "null"]));
+        assert_eq!(filter["additionalProperties"], false);
+
+        let Tool::Function(ping) = ping_handler().tool();
+        assert_eq!(ping.strict, None);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_parses_nested_arguments() {
+        let handler = create_order_handler();
+
+        let arguments = json!({
+            "items": [{"street": "Main St 1", "postalCode": "12345"}],
+            "priority": "high",
+            "note": null,
+            "maxCost": 9.5
+        });
+
+        let result = handler.invoke(&call("create_order", arguments)).await.unwrap();
+        assert_eq!(result, Some("1 items, High, None, 9.5".to_string()));
+
+        let other = handler.invoke(&call("other_tool", json!({}))).await.unwrap();
+        assert_eq!(other, None);
+    }
+
+    #[tokio::test]
+    async fn test_validate_trait_runs_before_the_function() {
+        let handler = book_handler();
+
+        let valid = handler.invoke(&call("book", json!({"check_in": "2024-05-01", "check_out": "2024-05-03"}))).await;
+        assert_eq!(valid.unwrap(), Some("2024-05-01..2024-05-03".to_string()));
+
+        let invalid = handler.invoke(&call("book", json!({"check_in": "2024-05-03", "check_out": "2024-05-01"}))).await;
+        assert!(matches!(
+            invalid,
+            Err(Error::FunctionValidation { reason }) if reason == "check_out must be after check_in"
+        ));
+    }
+
+    #[test]
+    fn test_validate_function_runs_before_the_function() {
+        let handler = transfer_handler();
+
+        let valid = handler.invoke(&call("transfer", json!({"from": "a", "to": "b", "amount": 5.0})));
+        assert_eq!(valid.unwrap(), Some("5 from a to b".to_string()));
+
+        let invalid = handler.invoke(&call("transfer", json!({"from": "a", "to": "a", "amount": 5.0})));
+        assert!(matches!(
+            invalid,
+            Err(Error::FunctionValidation { reason }) if reason == "cannot transfer from a to itself"
+        ));
+
+        // Arguments that fail to parse are reported before validation
+        let unparsed = handler.invoke(&call("transfer", json!({"from": "a"})));
+        assert!(matches!(unparsed, Err(Error::FunctionParameterParsing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors_are_reported_to_the_model() {
+        let toolbox = Toolbox::new().register(transfer_handler()).report_errors(true);
+
+        let mut invalid = call("transfer", json!({"from": "a", "to": "b", "amount": -1.0}));
+        invalid.call_id = "call_1".to_string();
+
+        let outputs = toolbox.dispatch(std::slice::from_ref(&invalid)).await;
+        assert!(matches!(&outputs[0].1, Err(Error::FunctionValidation { .. })));
+
+        let inputs = toolbox.run(&[invalid]).await.unwrap();
+        let request = serde_json::to_value(&inputs).unwrap();
+        let output: Value = serde_json::from_str(request[1]["output"].as_str().unwrap()).unwrap();
+        assert!(output["error"].as_str().unwrap().contains("amount must be positive"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Tests for `#[tools]` on impl blocks, with tools sharing the service's state.
+
+#[cfg(test)]
+mod tool_methods_tests {
+    use responses::functions::FunctionHandler;
+    use responses::types::{FunctionOutput, OutputFunctionCall, Tool};
+    use responses::{tools, Error, Result};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    pub struct Inventory {
+        stock: Mutex<HashMap<String, u32>>,
+        lookups: AtomicUsize,
+    }
+
+    #[tools]
+    impl Inventory {
+        fn new() -> Self {
+            Self {
+                stock: Mutex::new(HashMap::from([("apple".to_string(), 3)])),
+                lookups: AtomicUsize::new(0),
+            }
+        }
+
+        #[tool(strict = false)]
+        /// Get the stock level of an item
+        async fn stock_level(&self, item: String) -> Result<u32> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.stock.lock().unwrap().get(&item).copied().unwrap_or(0))
+        }
+
+        #[tool("restock_item", strict)]
+        /// Add items to the stock
+        fn restock(&self, item: String, #[param(minimum = 1)] quantity: u32) -> Result<u32> {
+            let mut stock = self.stock.lock().unwrap();
+            let level = stock.entry(item).or_insert(0);
+            *level += quantity;
+            Ok(*level)
+        }
+    }
+
+    /// Storage backend for the generic service.
+    pub trait Storage: Send + Sync {
+        fn get(&self, key: &str) -> Option<String>;
+    }
+
+    pub struct MemoryStorage;
+
+    impl Storage for MemoryStorage {
+        fn get(&self, key: &str) -> Option<String> {
+            (key == "greeting").then(|| "hello".to_string())
+        }
+    }
+
+    pub struct KeyValue<S: Storage> {
+        storage: S,
+    }
+
+    #[tools]
+    impl<S: Storage> KeyValue<S> {
+        #[tool(validate = check_key)]
+        /// Read a value
+        async fn read(&self, key: String) -> Result<Option<String>> {
+            Ok(self.storage.get(&key))
+        }
+    }
+
+    fn check_key(params: &KeyValueReadParams) -> std::result::Result<(), String> {
+        if params.key.is_empty() {
+            return Err("key must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    fn call(call_id: &str, name: &str, arguments: Value) -> OutputFunctionCall {
+        serde_json::from_value(json!({
+            "call_id": call_id,
+            "name": name,
+            "arguments": arguments.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_method_tool_definitions() {
+        let inventory = Arc::new(Inventory::new());
+
+        let Tool::Function(stock_level) = inventory.stock_level_handler().tool();
+        assert_eq!(stock_level.name, "stock_level");
+        assert_eq!(stock_level.description.as_deref(), Some("Get the stock level of an item"));
+        assert_eq!(stock_level.strict, None);
+
+        let Tool::Function(restock) = inventory.restock_handler().tool();
+        assert_eq!(restock.name, "restock_item");
+        assert_eq!(restock.strict, Some(true));
+
+        let parameters = serde_json::to_value(restock.parameters).unwrap();
+        assert_eq!(parameters["properties"]["quantity"]["minimum"], 1);
+        assert!(parameters["properties"].get("self").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handlers_share_service_state() {
+        let inventory = Arc::new(Inventory::new());
+        let restock = inventory.restock_handler();
+        let stock_level = inventory.stock_level_handler();
+
+        let level = restock
+            .invoke(&call("call_1", "restock_item", json!({"item": "apple", "quantity": 2})))
+            .await
+            .unwrap();
+        assert_eq!(level, Some(5));
+
+        let level = stock_level
+            .invoke(&call("call_2", "stock_level", json!({"item": "apple"})))
+            .await
+            .unwrap();
+        assert_eq!(level, Some(5));
+
+        let other = stock_level.invoke(&call("call_3", "restock_item", json!({}))).await.unwrap();
+        assert_eq!(other, None);
+
+        assert_eq!(inventory.lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_service_toolbox_dispatches_all_tools() {
+        let inventory = Arc::new(Inventory::new());
+        let toolbox = inventory.toolbox();
+
+        assert_eq!(toolbox.names(), vec!["stock_level", "restock_item"]);
+
+        let calls = vec![
+            call("call_1", "restock_item", json!({"item": "pear", "quantity": 4})),
+            call("call_2", "stock_level", json!({"item": "apple"})),
+        ];
+
+        let results = toolbox.dispatch(&calls).await;
+        assert_eq!(results[0].1.as_ref().unwrap(), &FunctionOutput::text("4"));
+        assert_eq!(results[1].1.as_ref().unwrap(), &FunctionOutput::text("3"));
+    }
+
+    #[tokio::test]
+    async fn test_generic_service() {
+        let service = Arc::new(KeyValue { storage: MemoryStorage });
+
+        let result = service
+            .read_handler()
+            .invoke(&call("call_1", "read", json!({"key": "greeting"})))
+            .await
+            .unwrap();
+        assert_eq!(result, Some(Some("hello".to_string())));
+
+        let results = service.toolbox().dispatch(&[call("call_2", "read", json!({"key": "other"}))]).await;
+        assert_eq!(results[0].1.as_ref().unwrap(), &FunctionOutput::text("null"));
+
+        let results = service.toolbox().dispatch(&[call("call_3", "read", json!({"key": ""}))]).await;
+        assert!(matches!(&results[0].1, Err(Error::FunctionValidation { reason }) if reason == "key must not be empty"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Tests for registering `#[tool]` handlers in a `Toolbox` and dispatching calls.
+
+#[cfg(test)]
+mod toolbox_tests {
+    use responses::types::{FunctionOutput, Input, OutputFunctionCall, Tool};
+    use responses::{tool, Error, Messages, Result, Toolbox};
+    use serde::Serialize;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Serialize)]
+    pub struct Weather {
+        city: String,
+        temperature: f64,
+    }
+
+    #[tool]
+    /// Get weather information for a city
+    async fn get_weather(city: String) -> Result<Weather> {
+        Ok(Weather { city, temperature: 21.5 })
+    }
+
+    #[tool]
+    /// Add two numbers
+    fn add(a: i64, b: i64) -> Result<i64> {
+        Ok(a + b)
+    }
+
+    #[tool]
+    /// Render a chart of the given values
+    async fn chart(values: Vec<f64>) -> Result<FunctionOutput> {
+        if values.is_empty() {
+            return Err(Error::FunctionValidation { reason: "no values to chart".to_string() });
+        }
+        Ok(FunctionOutput::image("data:image/png;base64,iVBORw0KGgo="))
+    }
+
+    #[tool]
+    /// Clear the cache
+    fn clear_cache() -> Result<()> {
+        Ok(())
+    }
+
+    pub struct Counter {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    #[tool]
+    /// Wait for a while, tracking how many calls run at the same time
+    async fn wait(millis: u64, context: &Counter) -> Result<String> {
+        let running = context.running.fetch_add(1, Ordering::SeqCst) + 1;
+        context.max_running.fetch_max(running, Ordering::SeqCst);
+
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+
+        context.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(format!("waited {}ms", millis))
+    }
+
+    fn call(call_id: &str, name: &str, arguments: Value) -> OutputFunctionCall {
+        serde_json::from_value(json!({
+            "call_id": call_id,
+            "name": name,
+            "arguments": arguments.to_string(),
+        }))
+        .unwrap()
+    }
+
+    fn counter() -> Arc<Counter> {
+        Arc::new(Counter {
+            running: AtomicUsize::new(0),
+            max_running: AtomicUsize::new(0),
+        })
+    }
+
+    #[test]
+    fn test_tools_lists_registered_handlers() {
+        let toolbox = Toolbox::new()
+            .register(get_weather_handler())
+            .register(add_handler())
+            .register_with_context(wait_handler(), counter());
+
+        assert_eq!(toolbox.len(), 3);
+        assert_eq!(toolbox.names(), vec!["get_weather", "add", "wait"]);
+        assert!(toolbox.contains("wait"));
+
+        let names: Vec<String> = toolbox
+            .tools()
+            .into_iter()
+            .map(|tool| {
+                let Tool::Function(function) = tool;
+                function.name
+            })
+            .collect();
+        assert_eq!(names, vec!["get_weather", "add", "wait"]);
+
+        // Registering the same name again replaces the handler
+        let toolbox = toolbox.register(add_handler());
+        assert_eq!(toolbox.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_returns_results_in_call_order() {
+        let toolbox = Toolbox::new()
+            .register(get_weather_handler())
+            .register(add_handler());
+
+        let calls = vec![
+            call("call_1", "get_weather", json!({"city": "Paris"})),
+            call("call_2", "add", json!({"a": 2, "b": 3})),
+            call("call_3", "add", json!({"a": "two"})),
+            call("call_4", "unknown", json!({})),
+        ];
+
+        let results = toolbox.dispatch(&calls).await;
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().zip(&calls).all(|((returned, _), call)| returned.call_id == call.call_id));
+
+        assert_eq!(
+            results[0].1.as_ref().unwrap(),
+            &FunctionOutput::text(r#"{"city":"Paris","temperature":21.5}"#)
+        );
+        assert_eq!(results[1].1.as_ref().unwrap(), &FunctionOutput::text("5"));
+        assert!(matches!(
+            &results[2].1,
+            Err(Error::FunctionParameterParsing { function_name, .. }) if function_name == "add"
+        ));
+        assert!(matches!(
+            &results[3].1,
+            Err(Error::FunctionNotFound { name }) if name == "unknown"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_keeps_calls_with_the_same_id_apart() {
+        let toolbox = Toolbox::new().register(add_handler());
+
+        let calls = vec![
+            call("", "add", json!({"a": 1, "b": 1})),
+            call("", "add", json!({"a": 2, "b": 2})),
+        ];
+
+        let outputs: Vec<FunctionOutput> = toolbox
+            .dispatch(&calls)
+            .await
+            .into_iter()
+            .map(|(_, result)| result.unwrap())
+            .collect();
+        assert_eq!(outputs, vec![FunctionOutput::text("2"), FunctionOutput::text("4")]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_respects_parallelism_limit() {
+        let context = counter();
+        let toolbox = Toolbox::new()
+            .register_with_context(wait_handler(), context.clone())
+            .parallelism(2);
+
+        let calls: Vec<_> = (0..5)
+            .map(|i| call(&format!("call_{}", i), "wait", json!({"millis": 20})))
+            .collect();
+
+        let results = toolbox.dispatch(&calls).await;
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[3].1.as_ref().unwrap(), &FunctionOutput::text("waited 20ms"));
+        assert_eq!(context.max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_runs_calls_concurrently_by_default() {
+        let context = counter();
+        let toolbox = Toolbox::new().register_with_context(wait_handler(), context.clone());
+
+        let calls: Vec<_> = (0..4)
+            .map(|i| call(&format!("call_{}", i), "wait", json!({"millis": 20})))
+            .collect();
+
+        toolbox.dispatch(&calls).await;
+
+        assert_eq!(context.max_running.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_calls_followed_by_outputs() {
+        let toolbox = Toolbox::new()
+            .register(chart_handler())
+            .register(clear_cache_handler());
+
+        let calls = vec![
+            call("call_1", "chart", json!({"values": [1.0, 2.0]})),
+            call("call_2", "clear_cache", json!({})),
+        ];
+
+        let inputs = toolbox.run(&calls).await.unwrap();
+        let messages = Messages::new().user("Chart it and clear the cache").extend_inputs(inputs);
+
+        let request = serde_json::to_value(messages.render_inputs()).unwrap();
+        assert_eq!(request[1], json!({
+            "type": "function_call",
+            "call_id": "call_1",
+            "name": "chart",
+            "arguments": r#"{"values":[1.0,2.0]}"#,
+        }));
+        assert_eq!(request[2], json!({
+            "type": "function_call_output",
+            "call_id": "call_1",
+            "output": [{"type": "input_image", "image_url": "data:image/png;base64,iVBORw0KGgo="}],
+        }));
+        assert_eq!(request[4], json!({
+            "type": "function_call_output",
+            "call_id": "call_2",
+            "output": "null",
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_errors_to_the_model() {
+        let calls = vec![
+            call("call_1", "chart", json!({"values": []})),
+            call("call_2", "missing", json!({})),
+        ];
+
+        let failing = Toolbox::new().register(chart_handler());
+        assert!(matches!(failing.run(&calls).await, Err(Error::FunctionValidation { .. })));
+
+        let reporting = failing.report_errors(true);
+        let inputs = reporting.run(&calls).await.unwrap();
+        assert_eq!(inputs.len(), 4);
+
+        let Input::FunctionCallOutput(output) = &inputs[1] else {
+            panic!("expected a function call output");
+        };
+        let FunctionOutput::Text(text) = &output.output else {
+            panic!("expected a text output");
+        };
+        let error: Value = serde_json::from_str(text).unwrap();
+        assert!(error["error"].as_str().unwrap().contains("no values to chart"));
+
+        let Input::FunctionCallOutput(output) = &inputs[3] else {
+            panic!("expected a function call output");
+        };
+        assert_eq!(output.call_id, "call_2");
+        assert!(matches!(&output.output, FunctionOutput::Text(text) if text.contains("missing")));
+    }
+}